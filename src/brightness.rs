@@ -0,0 +1,358 @@
+use chrono::{Local, NaiveTime, Utc};
+use exchange_format::ConfigParam;
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, RwLock},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    config_manager::ConfigManager,
+    sensors::SensorKind,
+    weather::cache::{WeatherCache, CACHE_PATH},
+    CLOSE_REQUESTED, DEVICES, SENSORS,
+};
+
+// the range of the brightness slider, the brightness conversion of the esp32 underflows below it
+pub const MIN_BRIGHTNESS: u32 = 20;
+pub const MAX_BRIGHTNESS: u32 = 100;
+pub const DEFAULT_BRIGHTNESS_MIN: u32 = 20;
+pub const DEFAULT_BRIGHTNESS_MAX: u32 = 100;
+// the manual slider shifts the automatic brightness by up to this many percent
+pub const MAX_BRIGHTNESS_OFFSET: f32 = 40.0;
+
+const UPDATE_INTERVAL: Duration = Duration::from_secs(2);
+// the sun times of the weather cache change once a day
+const SUN_TIMES_RELOAD: Duration = Duration::from_secs(600);
+// a light value older than this is ignored and the brightness is kept
+const MAX_LIGHT_AGE_SECONDS: i64 = 60;
+// the ambient light, that results in the maximum brightness
+const FULL_BRIGHTNESS_LUX: f64 = 1000.0;
+// the brightness ramps up after sunrise and down before sunset
+const TWILIGHT_MINUTES: f64 = 60.0;
+// changes of the ambient level below this are ignored, so the display does not flicker
+const LEVEL_HYSTERESIS: f64 = 0.05;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BrightnessMode {
+    Manual,
+    // an ambient light sensor of a device or the host
+    Light,
+    // sunrise and sunset of the weather location, or fixed day hours without one
+    Daylight,
+}
+
+impl BrightnessMode {
+    pub fn from_key(key: &str) -> BrightnessMode {
+        match key.trim().to_lowercase().as_str() {
+            "light" => BrightnessMode::Light,
+            "daylight" => BrightnessMode::Daylight,
+            _ => BrightnessMode::Manual,
+        }
+    }
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            BrightnessMode::Manual => "manual",
+            BrightnessMode::Light => "light",
+            BrightnessMode::Daylight => "daylight",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BrightnessMode::Manual => "Manual",
+            BrightnessMode::Light => "Light sensor",
+            BrightnessMode::Daylight => "Daylight",
+        }
+    }
+
+    // the order of the mode button in the gui
+    pub fn next(&self) -> BrightnessMode {
+        match self {
+            BrightnessMode::Manual => BrightnessMode::Light,
+            BrightnessMode::Light => BrightnessMode::Daylight,
+            BrightnessMode::Daylight => BrightnessMode::Manual,
+        }
+    }
+}
+
+// the automatic brightness settings of a device, next to "brightness" in settings.json
+#[derive(Clone, Debug, PartialEq)]
+pub struct BrightnessSettings {
+    pub mode: BrightnessMode,
+    pub min: u8,
+    pub max: u8,
+    // set by the slider, while the brightness is automatic
+    pub offset: f32,
+    // e.g. "teensy.light", empty for the newest light value of any source
+    pub light_sensor: String,
+}
+
+impl Default for BrightnessSettings {
+    fn default() -> BrightnessSettings {
+        BrightnessSettings {
+            mode: BrightnessMode::Manual,
+            min: DEFAULT_BRIGHTNESS_MIN as u8,
+            max: DEFAULT_BRIGHTNESS_MAX as u8,
+            offset: 0.0,
+            light_sensor: String::new(),
+        }
+    }
+}
+
+impl BrightnessSettings {
+    pub fn from_config(config_manager: &ConfigManager, device: &str) -> BrightnessSettings {
+        let defaults = BrightnessSettings::default();
+        let integer = |key: &str, default: u8| match config_manager.get_device_value(device, key) {
+            Some(ConfigParam::Integer(value)) => value.clamp(MIN_BRIGHTNESS, MAX_BRIGHTNESS) as u8,
+            _ => default,
+        };
+        let string = |key: &str| match config_manager.get_device_value(device, key) {
+            Some(ConfigParam::String(value)) => value,
+            _ => String::new(),
+        };
+        let min = integer("brightness_min", defaults.min);
+        BrightnessSettings {
+            mode: BrightnessMode::from_key(&string("brightness_mode")),
+            min,
+            max: integer("brightness_max", defaults.max).max(min),
+            offset: match config_manager.get_device_value(device, "brightness_offset") {
+                Some(ConfigParam::Float(offset)) => {
+                    offset.clamp(-MAX_BRIGHTNESS_OFFSET, MAX_BRIGHTNESS_OFFSET)
+                }
+                _ => defaults.offset,
+            },
+            light_sensor: string("light_sensor"),
+        }
+    }
+}
+
+// 0 for darkness, 1 for daylight. the eye perceives light logarithmically.
+pub fn light_level(lux: f64) -> f64 {
+    ((lux.max(0.0) + 1.0).log10() / (FULL_BRIGHTNESS_LUX + 1.0).log10()).clamp(0.0, 1.0)
+}
+
+// 0 at night, 1 during the day and a linear ramp in between
+pub fn daylight_level(now: NaiveTime, sunrise: NaiveTime, sunset: NaiveTime) -> f64 {
+    let after_sunrise = (now - sunrise).num_seconds() as f64 / 60.0;
+    let before_sunset = (sunset - now).num_seconds() as f64 / 60.0;
+    (after_sunrise.min(before_sunset) / TWILIGHT_MINUTES).clamp(0.0, 1.0)
+}
+
+// the offset moves the curve, min and max still hold
+pub fn target_brightness(level: f64, settings: &BrightnessSettings) -> u8 {
+    let min = settings.min as f64;
+    let max = settings.max as f64;
+    (min + level * (max - min) + settings.offset as f64)
+        .round()
+        .clamp(min, max) as u8
+}
+
+#[derive(Default)]
+pub struct Hysteresis {
+    level: Option<f64>,
+}
+
+impl Hysteresis {
+    // keeps the previous level for small changes, darkness and full daylight are always reached
+    pub fn update(&mut self, level: f64) -> f64 {
+        match self.level {
+            Some(current)
+                if (level - current).abs() < LEVEL_HYSTERESIS && level > 0.0 && level < 1.0 =>
+            {
+                current
+            }
+            _ => {
+                self.level = Some(level);
+                level
+            }
+        }
+    }
+}
+
+fn sensor_light_level(light_sensor: &str) -> Option<f64> {
+    let registry = SENSORS.read().unwrap();
+    let value = if light_sensor.trim().is_empty() {
+        registry.latest(SensorKind::Light)
+    } else {
+        registry.value(light_sensor.trim())
+    }?;
+    if Utc::now() - value.time > chrono::Duration::seconds(MAX_LIGHT_AGE_SECONDS) {
+        return None;
+    }
+    Some(light_level(value.value))
+}
+
+// the sun times of the newest weather report, read from the cache of the weather screen
+#[derive(Default)]
+struct Daylight {
+    loaded: Option<Instant>,
+    sun: Option<(NaiveTime, NaiveTime)>,
+    // the difference between the time of the weather location and the local time
+    offset: chrono::Duration,
+}
+
+impl Daylight {
+    fn level(&mut self) -> f64 {
+        if !matches!(self.loaded, Some(loaded) if loaded.elapsed() < SUN_TIMES_RELOAD) {
+            self.loaded = Some(Instant::now());
+            self.sun = None;
+            if let Some(cached) = WeatherCache::load(Path::new(CACHE_PATH)).newest_report() {
+                if let (Some(sun), Some(observed)) = (cached.report.sun, cached.report.observed) {
+                    self.sun = Some((sun.sunrise.time(), sun.sunset.time()));
+                    self.offset = observed - cached.fetched_at.with_timezone(&Local).naive_local();
+                }
+            }
+        }
+        let local = Local::now().naive_local();
+        match self.sun {
+            Some((sunrise, sunset)) => {
+                daylight_level((local + self.offset).time(), sunrise, sunset)
+            }
+            None => daylight_level(
+                local.time(),
+                NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+                NaiveTime::from_hms_opt(19, 0, 0).unwrap(),
+            ),
+        }
+    }
+}
+
+// adjusts the brightness of every device with an automatic mode in the background.
+// switching back to manual restores the brightness of the slider.
+pub fn start_auto_brightness(config_manager: Arc<RwLock<ConfigManager>>) {
+    thread::spawn(move || {
+        let mut hysteresis: HashMap<String, Hysteresis> = HashMap::new();
+        let mut applied: HashMap<String, u8> = HashMap::new();
+        let mut daylight = Daylight::default();
+        loop {
+            for (key, device) in DEVICES.iter() {
+                let settings =
+                    BrightnessSettings::from_config(&config_manager.read().unwrap(), key);
+                let level = match settings.mode {
+                    BrightnessMode::Manual => {
+                        hysteresis.remove(key);
+                        if applied.remove(key).is_some() {
+                            device
+                                .set_brightness(config_manager.read().unwrap().get_brightness(key));
+                        }
+                        continue;
+                    }
+                    BrightnessMode::Light => sensor_light_level(&settings.light_sensor),
+                    BrightnessMode::Daylight => Some(daylight.level()),
+                };
+                // without a light value the brightness stays as it is
+                if let Some(level) = level {
+                    let level = hysteresis.entry(key.clone()).or_default().update(level);
+                    let brightness = target_brightness(level, &settings);
+                    if applied.get(key) != Some(&brightness) {
+                        device.set_brightness(brightness);
+                        applied.insert(key.clone(), brightness);
+                    }
+                }
+            }
+            if CLOSE_REQUESTED.load(std::sync::atomic::Ordering::Acquire) {
+                return;
+            }
+            thread::sleep(UPDATE_INTERVAL);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_dir::TestDir;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_levels() {
+        assert_eq!(light_level(0.0), 0.0);
+        assert_eq!(light_level(FULL_BRIGHTNESS_LUX * 10.0), 1.0);
+        assert!(light_level(10.0) < light_level(100.0));
+
+        let (sunrise, sunset) = (time(7, 0), time(19, 0));
+        assert_eq!(daylight_level(time(3, 0), sunrise, sunset), 0.0);
+        assert_eq!(daylight_level(time(7, 30), sunrise, sunset), 0.5);
+        assert_eq!(daylight_level(time(12, 0), sunrise, sunset), 1.0);
+        assert_eq!(daylight_level(time(18, 45), sunrise, sunset), 0.25);
+        assert_eq!(daylight_level(time(22, 0), sunrise, sunset), 0.0);
+    }
+
+    #[test]
+    fn test_target_brightness_with_offset() {
+        let mut settings = BrightnessSettings {
+            mode: BrightnessMode::Light,
+            min: 30,
+            max: 80,
+            ..Default::default()
+        };
+        assert_eq!(target_brightness(0.0, &settings), 30);
+        assert_eq!(target_brightness(0.5, &settings), 55);
+        assert_eq!(target_brightness(1.0, &settings), 80);
+
+        settings.offset = 10.0;
+        assert_eq!(target_brightness(0.5, &settings), 65);
+        assert_eq!(target_brightness(1.0, &settings), 80);
+        settings.offset = -40.0;
+        assert_eq!(target_brightness(0.5, &settings), 30);
+    }
+
+    #[test]
+    fn test_hysteresis() {
+        let mut hysteresis = Hysteresis::default();
+        assert_eq!(hysteresis.update(0.5), 0.5);
+        assert_eq!(hysteresis.update(0.53), 0.5);
+        assert_eq!(hysteresis.update(0.47), 0.5);
+        assert_eq!(hysteresis.update(0.56), 0.56);
+        // the bounds are reached, even if the step is small
+        assert_eq!(hysteresis.update(0.98), 0.98);
+        assert_eq!(hysteresis.update(1.0), 1.0);
+    }
+
+    #[test]
+    fn test_settings_from_config() {
+        let dir = TestDir::new("brightness_settings");
+        let path = dir.join("settings.json");
+        let mut config_manager = ConfigManager::new(path.to_str());
+        assert_eq!(
+            BrightnessSettings::from_config(&config_manager, "teensy"),
+            BrightnessSettings::default()
+        );
+
+        config_manager.set_device_value(
+            "teensy",
+            "brightness_mode",
+            ConfigParam::String("light".into()),
+        );
+        config_manager.set_device_value("teensy", "brightness_min", ConfigParam::Integer(50));
+        config_manager.set_device_value("teensy", "brightness_max", ConfigParam::Integer(40));
+        config_manager.set_device_value("teensy", "brightness_offset", ConfigParam::Float(-90.0));
+        let settings = BrightnessSettings::from_config(&config_manager, "teensy");
+        assert_eq!(settings.mode, BrightnessMode::Light);
+        assert_eq!((settings.min, settings.max), (50, 50));
+        assert_eq!(settings.offset, -MAX_BRIGHTNESS_OFFSET);
+        assert_eq!(BrightnessMode::Daylight.next(), BrightnessMode::Manual);
+    }
+
+    #[test]
+    fn test_settings_stay_in_the_slider_range() {
+        let dir = TestDir::new("brightness_range");
+        let path = dir.join("settings.json");
+        let mut config_manager = ConfigManager::new(path.to_str());
+        config_manager.set_device_value("esp32", "brightness_min", ConfigParam::Integer(0));
+        config_manager.set_device_value("esp32", "brightness_max", ConfigParam::Integer(0));
+        let settings = BrightnessSettings::from_config(&config_manager, "esp32");
+        assert_eq!((settings.min, settings.max), (20, 20));
+
+        config_manager.set_device_value("esp32", "brightness_max", ConfigParam::Integer(255));
+        let settings = BrightnessSettings::from_config(&config_manager, "esp32");
+        assert_eq!((settings.min, settings.max), (20, 100));
+    }
+}
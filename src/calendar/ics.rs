@@ -0,0 +1,367 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use super::recurrence::{parse_rule, RecurrenceRule};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EventZone {
+    Utc,
+    Named(Tz),
+    // no time zone given, the local time of the machine is used
+    Floating,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventTime {
+    pub time: NaiveDateTime,
+    pub zone: EventZone,
+    pub all_day: bool,
+}
+
+impl EventTime {
+    pub fn with_time(&self, time: NaiveDateTime) -> EventTime {
+        EventTime {
+            time,
+            ..self.clone()
+        }
+    }
+
+    // times, that do not exist because of a daylight saving switch, are dropped
+    pub fn to_utc(&self) -> Option<DateTime<Utc>> {
+        match self.zone {
+            EventZone::Utc => Some(Utc.from_utc_datetime(&self.time)),
+            EventZone::Named(tz) => tz
+                .from_local_datetime(&self.time)
+                .earliest()
+                .map(|time| time.with_timezone(&Utc)),
+            EventZone::Floating => Local
+                .from_local_datetime(&self.time)
+                .earliest()
+                .map(|time| time.with_timezone(&Utc)),
+        }
+    }
+
+    // the given point in time, expressed in the time zone of this event
+    pub fn local_time(&self, time: &DateTime<Utc>) -> NaiveDateTime {
+        match self.zone {
+            EventZone::Utc => time.naive_utc(),
+            EventZone::Named(tz) => time.with_timezone(&tz).naive_local(),
+            EventZone::Floating => time.with_timezone(&Local).naive_local(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub summary: String,
+    pub location: Option<String>,
+    pub start: EventTime,
+    pub duration: Duration,
+    pub rule: Option<RecurrenceRule>,
+    pub exception_dates: Vec<DateTime<Utc>>,
+    // set for modified instances of a recurring event, they replace the original instance
+    pub recurrence_id: Option<DateTime<Utc>>,
+}
+
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+// long lines are folded by a line break followed by a space or tab
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        let line = line.trim_end_matches('\r');
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+// "DTSTART;TZID="Europe/Berlin":20261019T090000"
+fn parse_property(line: &str) -> Option<Property> {
+    // the value starts at the first colon, that is not part of a quoted parameter
+    let mut quoted = false;
+    let split = line.char_indices().find_map(|(index, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(index),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..split], &line[split + 1..]);
+
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_uppercase();
+    let params = parts
+        .filter_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            Some((
+                key.trim().to_uppercase(),
+                value.trim_matches('"').to_string(),
+            ))
+        })
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => result.push(' '),
+            Some(escaped) => result.push(escaped),
+            None => {}
+        }
+    }
+    result
+}
+
+// "20261019T090000Z", "20261019T090000" with an optional TZID or "20261019" for all day events
+pub fn parse_time(value: &str, time_zone: Option<&str>) -> Option<EventTime> {
+    let value = value.trim();
+    if value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some(EventTime {
+            time: date.and_hms_opt(0, 0, 0)?,
+            zone: EventZone::Floating,
+            all_day: true,
+        });
+    }
+
+    let (value, utc) = match value.strip_suffix('Z') {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let zone = if utc {
+        EventZone::Utc
+    } else {
+        // unknown names (e.g. windows zone names in outlook exports) fall back to local time
+        match time_zone.and_then(|name| name.parse::<Tz>().ok()) {
+            Some(tz) => EventZone::Named(tz),
+            None => EventZone::Floating,
+        }
+    };
+    Some(EventTime {
+        time,
+        zone,
+        all_day: false,
+    })
+}
+
+// "PT15M", "P1DT2H", "-PT10M" or "P1W"
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(value) => (true, value),
+        None => (false, value.trim_start_matches('+')),
+    };
+    let value = value.strip_prefix('P')?;
+
+    let mut seconds = 0i64;
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let amount: i64 = number.parse().ok()?;
+                number.clear();
+                seconds += amount
+                    * match unit {
+                        'W' => 7 * 86400,
+                        'D' => 86400,
+                        'H' => 3600,
+                        'M' => 60,
+                        'S' => 1,
+                        _ => return None,
+                    };
+            }
+        }
+    }
+    if !number.is_empty() {
+        return None;
+    }
+    Some(Duration::seconds(if negative { -seconds } else { seconds }))
+}
+
+#[derive(Default)]
+struct EventBuilder {
+    uid: String,
+    summary: String,
+    location: Option<String>,
+    start: Option<EventTime>,
+    end: Option<EventTime>,
+    duration: Option<Duration>,
+    rule: Option<RecurrenceRule>,
+    exception_dates: Vec<DateTime<Utc>>,
+    recurrence_id: Option<DateTime<Utc>>,
+}
+
+impl EventBuilder {
+    fn add(&mut self, property: Property) {
+        let time_zone = property.param("TZID");
+        match property.name.as_str() {
+            "UID" => self.uid = property.value.trim().to_string(),
+            "SUMMARY" => self.summary = unescape(&property.value),
+            "LOCATION" => {
+                let location = unescape(&property.value);
+                if !location.trim().is_empty() {
+                    self.location = Some(location);
+                }
+            }
+            "DTSTART" => self.start = parse_time(&property.value, time_zone),
+            "DTEND" => self.end = parse_time(&property.value, time_zone),
+            "DURATION" => self.duration = parse_duration(&property.value),
+            "RRULE" => self.rule = parse_rule(&property.value),
+            "EXDATE" => {
+                // a single line may list several dates
+                for value in property.value.split(',') {
+                    if let Some(time) = parse_time(value, time_zone).and_then(|time| time.to_utc())
+                    {
+                        self.exception_dates.push(time);
+                    }
+                }
+            }
+            "RECURRENCE-ID" => {
+                self.recurrence_id =
+                    parse_time(&property.value, time_zone).and_then(|time| time.to_utc())
+            }
+            _ => {}
+        }
+    }
+
+    fn build(self) -> Option<CalendarEvent> {
+        let start = self.start?;
+        let duration = match (&self.end, self.duration) {
+            (Some(end), _) => match (end.to_utc(), start.to_utc()) {
+                (Some(end), Some(begin)) => end - begin,
+                _ => end.time - start.time,
+            },
+            (None, Some(duration)) => duration,
+            (None, None) if start.all_day => Duration::days(1),
+            (None, None) => Duration::zero(),
+        };
+        Some(CalendarEvent {
+            uid: self.uid,
+            summary: self.summary,
+            location: self.location,
+            start,
+            duration: duration.max(Duration::zero()),
+            rule: self.rule,
+            exception_dates: self.exception_dates,
+            recurrence_id: self.recurrence_id,
+        })
+    }
+}
+
+// reads all events of an iCalendar file, broken events are skipped
+pub fn parse_calendar(content: &str) -> Vec<CalendarEvent> {
+    let mut events = Vec::new();
+    let mut event: Option<EventBuilder> = None;
+    // nested components like alarms are ignored
+    let mut depth = 0;
+
+    for line in unfold(content) {
+        let property = match parse_property(&line) {
+            Some(property) => property,
+            None => continue,
+        };
+        let component = property.value.trim().to_uppercase();
+        match property.name.as_str() {
+            "BEGIN" if component == "VEVENT" && event.is_none() => {
+                event = Some(EventBuilder::default());
+                depth = 0;
+            }
+            "BEGIN" if event.is_some() => depth += 1,
+            "END" if component == "VEVENT" && depth == 0 => {
+                if let Some(built) = event.take().and_then(|builder| builder.build()) {
+                    events.push(built);
+                }
+            }
+            "END" if event.is_some() => depth -= 1,
+            _ if depth == 0 => {
+                if let Some(builder) = event.as_mut() {
+                    builder.add(property);
+                }
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_calendar() {
+        let events = parse_calendar(include_str!("fixtures/single.ics"));
+        assert_eq!(events.len(), 3);
+
+        let standup = &events[0];
+        assert_eq!(
+            standup.summary,
+            "Standup with a very long summary, that was folded by the exporter"
+        );
+        assert_eq!(standup.location.as_deref(), Some("Room 1; 2nd floor"));
+        assert_eq!(standup.start.zone, EventZone::Utc);
+        assert_eq!(
+            standup.start.to_utc().unwrap().to_rfc3339(),
+            "2026-10-19T07:00:00+00:00"
+        );
+        assert_eq!(standup.duration, Duration::minutes(15));
+
+        let review = &events[1];
+        assert_eq!(
+            review.start.zone,
+            EventZone::Named(chrono_tz::Europe::Berlin)
+        );
+        // berlin is at UTC+2 until the end of october
+        assert_eq!(
+            review.start.to_utc().unwrap().to_rfc3339(),
+            "2026-10-20T12:00:00+00:00"
+        );
+        assert_eq!(review.duration, Duration::minutes(90));
+
+        let holiday = &events[2];
+        assert!(holiday.start.all_day);
+        assert_eq!(holiday.duration, Duration::days(1));
+        assert_eq!(holiday.location, None);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("PT15M"), Some(Duration::minutes(15)));
+        assert_eq!(parse_duration("P1DT2H"), Some(Duration::hours(26)));
+        assert_eq!(parse_duration("P1W"), Some(Duration::days(7)));
+        assert_eq!(parse_duration("-PT10M"), Some(Duration::minutes(-10)));
+        assert_eq!(parse_duration("15M"), None);
+    }
+}
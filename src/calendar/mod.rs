@@ -0,0 +1,258 @@
+pub mod ics;
+pub mod recurrence;
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use std::{collections::HashSet, fs, path::Path};
+
+use ics::{parse_calendar, parse_time, CalendarEvent, EventZone};
+use recurrence::expand;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Occurrence {
+    pub summary: String,
+    pub location: Option<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub all_day: bool,
+}
+
+fn read_calendar(path: &Path, events: &mut Vec<CalendarEvent>) {
+    match fs::read_to_string(path) {
+        Ok(content) => events.extend(parse_calendar(&content)),
+        Err(e) => eprintln!("Failed to read calendar {}: {:?}", path.display(), e),
+    }
+}
+
+// ';' separated list of .ics files or directories containing .ics files
+pub fn load_events(paths: &str) -> Vec<CalendarEvent> {
+    let mut events = Vec::new();
+    for path in paths
+        .split(';')
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+    {
+        let path = Path::new(path);
+        if !path.is_dir() {
+            read_calendar(path, &mut events);
+            continue;
+        }
+        let mut files: Vec<_> = match fs::read_dir(path) {
+            Ok(entries) => entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|file| {
+                    file.extension()
+                        .map(|extension| extension.eq_ignore_ascii_case("ics"))
+                        .unwrap_or(false)
+                })
+                .collect(),
+            Err(e) => {
+                eprintln!(
+                    "Failed to read calendar directory {}: {:?}",
+                    path.display(),
+                    e
+                );
+                continue;
+            }
+        };
+        files.sort();
+        for file in files {
+            read_calendar(&file, &mut events);
+        }
+    }
+    events
+}
+
+fn occurrence(event: &CalendarEvent, start: DateTime<Utc>) -> Occurrence {
+    Occurrence {
+        summary: event.summary.clone(),
+        location: event.location.clone(),
+        start,
+        end: start + event.duration,
+        all_day: event.start.all_day,
+    }
+}
+
+// start times of all instances of an event, recurring events are expanded up to the given limit
+fn instances(event: &CalendarEvent, limit: &DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    let rule = match (&event.rule, event.recurrence_id) {
+        (Some(rule), None) => rule,
+        _ => return event.start.to_utc().into_iter().collect(),
+    };
+
+    // UNTIL is either given in UTC, as date or in the time zone of the event
+    let until = rule
+        .until
+        .as_deref()
+        .and_then(|until| parse_time(until, None))
+        .map(|until| match (until.zone, until.all_day) {
+            (EventZone::Utc, _) => event.start.local_time(&Utc.from_utc_datetime(&until.time)),
+            (_, true) => until
+                .time
+                .date()
+                .and_hms_opt(23, 59, 59)
+                .unwrap_or(until.time),
+            _ => until.time,
+        });
+
+    expand(event.start.time, rule, until, event.start.local_time(limit))
+        .into_iter()
+        .filter_map(|time| event.start.with_time(time).to_utc())
+        .filter(|time| !event.exception_dates.contains(time))
+        .collect()
+}
+
+// all occurrences overlapping the given range, sorted by start
+pub fn occurrences(
+    events: &[CalendarEvent],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<Occurrence> {
+    // modified instances replace the instance of the recurring event
+    let overridden: HashSet<(&str, DateTime<Utc>)> = events
+        .iter()
+        .filter_map(|event| Some((event.uid.as_str(), event.recurrence_id?)))
+        .collect();
+
+    let mut result: Vec<Occurrence> = events
+        .iter()
+        .flat_map(|event| {
+            let is_master = event.recurrence_id.is_none();
+            instances(event, &to)
+                .into_iter()
+                .filter(|start| !is_master || !overridden.contains(&(event.uid.as_str(), *start)))
+                .map(|start| occurrence(event, start))
+                .collect::<Vec<Occurrence>>()
+        })
+        .filter(|occurrence| occurrence.end > from && occurrence.start < to)
+        .collect();
+    result.sort_by_key(|occurrence| occurrence.start);
+    result
+}
+
+// "now", "in 12 min", "in 2 h 5 min", "tomorrow" or "in 3 days"
+pub fn format_until(now: DateTime<Utc>, start: DateTime<Utc>, today: NaiveDate) -> String {
+    let remaining = start - now;
+    if remaining <= Duration::zero() {
+        return "now".into();
+    }
+    let minutes = (remaining.num_seconds() + 59) / 60;
+    if minutes < 60 {
+        return format!("in {} min", minutes);
+    }
+    if remaining < Duration::hours(12) {
+        return match minutes % 60 {
+            0 => format!("in {} h", minutes / 60),
+            rest => format!("in {} h {} min", minutes / 60, rest),
+        };
+    }
+    match (start.with_timezone(&chrono::Local).date_naive() - today).num_days() {
+        0 => "today".into(),
+        1 => "tomorrow".into(),
+        days => format!("in {} days", days),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_dir::TestDir;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_recurring_occurrences() {
+        let events = parse_calendar(include_str!("fixtures/recurring.ics"));
+        let occurrences = occurrences(
+            &events,
+            utc("2026-10-19T00:00:00Z"),
+            utc("2027-01-01T00:00:00Z"),
+        );
+        let summaries: Vec<(String, String)> = occurrences
+            .iter()
+            .map(|occurrence| {
+                (
+                    occurrence.start.format("%Y-%m-%d %H:%M").to_string(),
+                    occurrence.summary.clone(),
+                )
+            })
+            .collect();
+
+        // times in UTC, berlin switches from UTC+2 to UTC+1 on 2026-10-25
+        let expected = vec![
+            ("2026-10-19 07:30", "Daily"),
+            ("2026-10-20 07:30", "Daily"),
+            // 21st and 22nd are excluded, the 23rd was moved
+            ("2026-10-23 09:00", "Daily (moved)"),
+            ("2026-10-26 08:30", "Daily"),
+            ("2026-10-27 08:30", "Daily"),
+            ("2026-10-28 08:30", "Daily"),
+            ("2026-10-29 08:30", "Daily"),
+            ("2026-10-30 08:30", "Daily"),
+            ("2026-11-10 08:00", "Planning"),
+            ("2026-12-08 08:00", "Planning"),
+        ];
+        assert_eq!(
+            summaries,
+            expected
+                .iter()
+                .map(|(start, summary)| (start.to_string(), summary.to_string()))
+                .collect::<Vec<(String, String)>>()
+        );
+        assert_eq!(
+            occurrences[0].end - occurrences[0].start,
+            Duration::minutes(15)
+        );
+    }
+
+    #[test]
+    fn test_ongoing_occurrences_are_included() {
+        let events = parse_calendar(include_str!("fixtures/single.ics"));
+        let occurrences = occurrences(
+            &events,
+            utc("2026-10-19T07:10:00Z"),
+            utc("2026-10-20T00:00:00Z"),
+        );
+        assert_eq!(occurrences.len(), 1);
+        assert!(occurrences[0].summary.starts_with("Standup"));
+    }
+
+    #[test]
+    fn test_load_events_from_directory() {
+        let dir = TestDir::new("calendar");
+        fs::write(dir.join("work.ics"), include_str!("fixtures/single.ics")).unwrap();
+        fs::write(dir.join("team.ICS"), include_str!("fixtures/recurring.ics")).unwrap();
+        fs::write(dir.join("notes.txt"), "BEGIN:VEVENT").unwrap();
+
+        let events = load_events(&format!("{}; /does/not/exist.ics", dir.path().display()));
+        assert_eq!(events.len(), 6);
+    }
+
+    #[test]
+    fn test_format_until() {
+        let now = utc("2026-10-19T07:00:00Z");
+        let today = now.with_timezone(&chrono::Local).date_naive();
+        assert_eq!(format_until(now, now, today), "now");
+        assert_eq!(
+            format_until(now, now + Duration::seconds(11 * 60 + 30), today),
+            "in 12 min"
+        );
+        assert_eq!(
+            format_until(now, now + Duration::minutes(125), today),
+            "in 2 h 5 min"
+        );
+        assert_eq!(format_until(now, now + Duration::hours(2), today), "in 2 h");
+        assert_eq!(
+            format_until(
+                now,
+                Utc.with_ymd_and_hms(2026, 10, 22, 12, 0, 0).unwrap(),
+                today
+            ),
+            "in 3 days"
+        );
+    }
+}
@@ -0,0 +1,351 @@
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveDateTime, Weekday};
+
+// stops runaway rules, e.g. a daily rule that started decades ago
+const MAX_PERIODS: u32 = 50_000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+// the subset of RFC 5545 recurrence rules, that calendar tools export for everyday meetings
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    // raw UNTIL value, the event decides about its time zone
+    pub until: Option<String>,
+    // weekday with an optional ordinal, e.g. "2TU" or "-1FR" for monthly rules
+    pub by_day: Vec<(Option<i32>, Weekday)>,
+    pub by_month_day: Vec<i32>,
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    match value {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+// "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=10"
+pub fn parse_rule(value: &str) -> Option<RecurrenceRule> {
+    let mut frequency = None;
+    let mut rule = RecurrenceRule {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+        by_month_day: Vec::new(),
+    };
+
+    for part in value.split(';') {
+        let (key, value) = match part.split_once('=') {
+            Some(pair) => pair,
+            None => continue,
+        };
+        match key.trim().to_uppercase().as_str() {
+            "FREQ" => {
+                frequency = match value.trim().to_uppercase().as_str() {
+                    "DAILY" => Some(Frequency::Daily),
+                    "WEEKLY" => Some(Frequency::Weekly),
+                    "MONTHLY" => Some(Frequency::Monthly),
+                    "YEARLY" => Some(Frequency::Yearly),
+                    // secondly, minutely and hourly rules are not supported
+                    _ => return None,
+                }
+            }
+            "INTERVAL" => rule.interval = value.trim().parse().unwrap_or(1).max(1),
+            "COUNT" => rule.count = value.trim().parse().ok(),
+            "UNTIL" => rule.until = Some(value.trim().to_string()),
+            "BYDAY" => {
+                rule.by_day = value
+                    .split(',')
+                    .filter_map(|day| {
+                        let day = day.trim().to_uppercase();
+                        if day.len() < 2 {
+                            return None;
+                        }
+                        let (ordinal, weekday) = day.split_at(day.len() - 2);
+                        let ordinal = if ordinal.is_empty() {
+                            None
+                        } else {
+                            Some(ordinal.trim_start_matches('+').parse().ok()?)
+                        };
+                        Some((ordinal, parse_weekday(weekday)?))
+                    })
+                    .collect()
+            }
+            "BYMONTHDAY" => {
+                rule.by_month_day = value
+                    .split(',')
+                    .filter_map(|day| day.trim().parse().ok())
+                    .collect()
+            }
+            _ => {}
+        }
+    }
+
+    rule.frequency = frequency?;
+    Some(rule)
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap_or_default();
+    let next = first + Months::new(1);
+    (next - first).num_days() as u32
+}
+
+// the n-th weekday of a month, counted from the end for negative ordinals
+fn nth_weekday(year: i32, month: u32, ordinal: i32, weekday: Weekday) -> Option<NaiveDate> {
+    if ordinal > 0 {
+        NaiveDate::from_weekday_of_month_opt(year, month, weekday, ordinal as u8)
+    } else {
+        let last = NaiveDate::from_ymd_opt(year, month, days_in_month(year, month))?;
+        let offset = (7 + last.weekday().num_days_from_monday() as i32
+            - weekday.num_days_from_monday() as i32)
+            % 7;
+        let date = last.checked_sub_days(Days::new((offset + (-ordinal - 1) * 7) as u64))?;
+        if date.month() == month {
+            Some(date)
+        } else {
+            None
+        }
+    }
+}
+
+// all dates of the given period (0 is the period of the first occurrence), sorted
+fn period_dates(start: NaiveDate, rule: &RecurrenceRule, period: u32) -> Vec<NaiveDate> {
+    let step = period * rule.interval;
+    let mut dates: Vec<NaiveDate> = match rule.frequency {
+        Frequency::Daily => start
+            .checked_add_days(Days::new(step as u64))
+            .into_iter()
+            .collect(),
+        Frequency::Weekly => {
+            let week_start = start - Days::new(start.weekday().num_days_from_monday() as u64)
+                + Days::new(step as u64 * 7);
+            let weekdays: Vec<Weekday> = if rule.by_day.is_empty() {
+                vec![start.weekday()]
+            } else {
+                rule.by_day.iter().map(|(_, weekday)| *weekday).collect()
+            };
+            weekdays
+                .iter()
+                .map(|weekday| week_start + Days::new(weekday.num_days_from_monday() as u64))
+                .collect()
+        }
+        Frequency::Monthly => {
+            let first = match NaiveDate::from_ymd_opt(start.year(), start.month(), 1)
+                .and_then(|first| first.checked_add_months(Months::new(step)))
+            {
+                Some(first) => first,
+                None => return vec![],
+            };
+            let (year, month) = (first.year(), first.month());
+            let days = days_in_month(year, month) as i32;
+            if !rule.by_month_day.is_empty() {
+                rule.by_month_day
+                    .iter()
+                    .filter_map(|day| {
+                        let day = if *day < 0 { days + day + 1 } else { *day };
+                        NaiveDate::from_ymd_opt(year, month, day as u32)
+                    })
+                    .collect()
+            } else if !rule.by_day.is_empty() {
+                rule.by_day
+                    .iter()
+                    .flat_map(|(ordinal, weekday)| match ordinal {
+                        Some(ordinal) => nth_weekday(year, month, *ordinal, *weekday)
+                            .into_iter()
+                            .collect::<Vec<NaiveDate>>(),
+                        // every matching weekday of the month
+                        None => (1..=days)
+                            .filter_map(|day| NaiveDate::from_ymd_opt(year, month, day as u32))
+                            .filter(|date| date.weekday() == *weekday)
+                            .collect(),
+                    })
+                    .collect()
+            } else {
+                // months without this day are skipped, like the RFC demands
+                NaiveDate::from_ymd_opt(year, month, start.day())
+                    .into_iter()
+                    .collect()
+            }
+        }
+        Frequency::Yearly => {
+            NaiveDate::from_ymd_opt(start.year() + step as i32, start.month(), start.day())
+                .into_iter()
+                .collect()
+        }
+    };
+    dates.sort();
+    dates.dedup();
+    dates
+}
+
+// all occurrences from the start until the given limit (inclusive), in the time zone of the event
+pub fn expand(
+    start: NaiveDateTime,
+    rule: &RecurrenceRule,
+    until: Option<NaiveDateTime>,
+    limit: NaiveDateTime,
+) -> Vec<NaiveDateTime> {
+    let end = match until {
+        Some(until) if until < limit => until,
+        _ => limit,
+    };
+    let mut occurrences = Vec::new();
+    let mut count = 0;
+
+    for period in 0..MAX_PERIODS {
+        let dates = period_dates(start.date(), rule, period);
+        for date in dates {
+            let occurrence = date.and_time(start.time());
+            // the first period may contain days before the start
+            if occurrence < start {
+                continue;
+            }
+            if occurrence > end {
+                return occurrences;
+            }
+            if let Some(max) = rule.count {
+                if count >= max {
+                    return occurrences;
+                }
+            }
+            count += 1;
+            occurrences.push(occurrence);
+        }
+    }
+    occurrences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date_time(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn formatted(occurrences: Vec<NaiveDateTime>) -> Vec<String> {
+        occurrences
+            .iter()
+            .map(|occurrence| occurrence.format("%Y-%m-%d %H:%M").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_rule() {
+        let rule =
+            parse_rule("FREQ=MONTHLY;INTERVAL=2;BYDAY=2TU,-1FR;UNTIL=20261231T000000Z").unwrap();
+        assert_eq!(rule.frequency, Frequency::Monthly);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(
+            rule.by_day,
+            vec![(Some(2), Weekday::Tue), (Some(-1), Weekday::Fri)]
+        );
+        assert_eq!(rule.until, Some("20261231T000000Z".into()));
+        assert!(parse_rule("FREQ=HOURLY").is_none());
+        assert!(parse_rule("INTERVAL=2").is_none());
+    }
+
+    #[test]
+    fn test_expand_weekly_by_day_with_count() {
+        // 2026-10-12 is a monday
+        let rule = parse_rule("FREQ=WEEKLY;BYDAY=MO,WE,FR;COUNT=5").unwrap();
+        let occurrences = expand(
+            date_time("2026-10-14 09:30"),
+            &rule,
+            None,
+            date_time("2027-01-01 00:00"),
+        );
+        assert_eq!(
+            formatted(occurrences),
+            vec![
+                "2026-10-14 09:30",
+                "2026-10-16 09:30",
+                "2026-10-19 09:30",
+                "2026-10-21 09:30",
+                "2026-10-23 09:30"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_daily_until_and_interval() {
+        let rule = parse_rule("FREQ=DAILY;INTERVAL=3").unwrap();
+        let occurrences = expand(
+            date_time("2026-10-30 08:00"),
+            &rule,
+            Some(date_time("2026-11-05 08:00")),
+            date_time("2027-01-01 00:00"),
+        );
+        assert_eq!(
+            formatted(occurrences),
+            vec!["2026-10-30 08:00", "2026-11-02 08:00", "2026-11-05 08:00"]
+        );
+    }
+
+    #[test]
+    fn test_expand_monthly() {
+        let second_tuesday = parse_rule("FREQ=MONTHLY;BYDAY=2TU").unwrap();
+        assert_eq!(
+            formatted(expand(
+                date_time("2026-10-13 10:00"),
+                &second_tuesday,
+                None,
+                date_time("2026-12-31 00:00"),
+            )),
+            vec!["2026-10-13 10:00", "2026-11-10 10:00", "2026-12-08 10:00"]
+        );
+
+        let last_friday = parse_rule("FREQ=MONTHLY;BYDAY=-1FR;COUNT=2").unwrap();
+        assert_eq!(
+            formatted(expand(
+                date_time("2026-10-30 16:00"),
+                &last_friday,
+                None,
+                date_time("2027-12-31 00:00"),
+            )),
+            vec!["2026-10-30 16:00", "2026-11-27 16:00"]
+        );
+
+        // months without a 31st are skipped
+        let end_of_month = parse_rule("FREQ=MONTHLY").unwrap();
+        assert_eq!(
+            formatted(expand(
+                date_time("2026-10-31 12:00"),
+                &end_of_month,
+                None,
+                date_time("2027-01-31 12:00"),
+            )),
+            vec!["2026-10-31 12:00", "2026-12-31 12:00", "2027-01-31 12:00"]
+        );
+    }
+
+    #[test]
+    fn test_expand_yearly_skips_missing_leap_days() {
+        let rule = parse_rule("FREQ=YEARLY").unwrap();
+        assert_eq!(
+            formatted(expand(
+                date_time("2024-02-29 00:00"),
+                &rule,
+                None,
+                date_time("2029-01-01 00:00"),
+            )),
+            vec!["2024-02-29 00:00", "2028-02-29 00:00"]
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use chrono::{DateTime, Duration, Utc};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use super::ClimateReading;
+
+pub const HISTORY_PATH: &str = "./climate_history.csv";
+const HEADER: &str = "time,temperature,humidity,pressure";
+
+// the sensor is read every two seconds, the history keeps one reading per minute
+const LOG_INTERVAL_SECONDS: i64 = 60;
+// older lines are dropped from the file when it is loaded
+const RETENTION_DAYS: i64 = 30;
+// enough for the graphs of the screen
+const MEMORY_HOURS: i64 = 48;
+
+fn to_line(reading: &ClimateReading) -> String {
+    format!(
+        "{},{:.2},{:.2},{}",
+        reading.time.to_rfc3339(),
+        reading.temperature,
+        reading.humidity,
+        reading
+            .pressure
+            .map(|pressure| format!("{:.2}", pressure))
+            .unwrap_or_default()
+    )
+}
+
+fn from_line(line: &str) -> Option<ClimateReading> {
+    let mut fields = line.trim().split(',');
+    let time = DateTime::parse_from_rfc3339(fields.next()?).ok()?;
+    let temperature = fields.next()?.parse().ok()?;
+    let humidity = fields.next()?.parse().ok()?;
+    let pressure = fields.next().and_then(|pressure| pressure.parse().ok());
+    Some(ClimateReading {
+        time: time.with_timezone(&Utc),
+        temperature,
+        humidity,
+        pressure,
+    })
+}
+
+// readings of the indoor sensor, kept in memory and appended to a csv file
+pub struct ClimateHistory {
+    path: PathBuf,
+    readings: Vec<ClimateReading>,
+}
+
+impl ClimateHistory {
+    pub fn load(path: &Path, now: DateTime<Utc>) -> ClimateHistory {
+        let content = fs::read_to_string(path).unwrap_or_default();
+        let mut readings: Vec<ClimateReading> = content.lines().filter_map(from_line).collect();
+        readings.sort_by_key(|reading| reading.time);
+
+        let retention = now - Duration::days(RETENTION_DAYS);
+        if readings.first().is_some_and(|first| first.time < retention) {
+            readings.retain(|reading| reading.time >= retention);
+            let mut content = vec![HEADER.to_string()];
+            content.extend(readings.iter().map(to_line));
+            if let Err(e) = fs::write(path, content.join("\n") + "\n") {
+                eprintln!("Error: {:?}", e);
+            }
+        }
+
+        let mut history = ClimateHistory {
+            path: path.to_path_buf(),
+            readings,
+        };
+        history.trim(now);
+        history
+    }
+
+    fn trim(&mut self, now: DateTime<Utc>) {
+        let oldest = now - Duration::hours(MEMORY_HOURS);
+        self.readings.retain(|reading| reading.time >= oldest);
+    }
+
+    // returns true, if the reading was logged
+    pub fn record(&mut self, reading: &ClimateReading) -> bool {
+        if matches!(self.readings.last(), Some(last)
+            if reading.time - last.time < Duration::seconds(LOG_INTERVAL_SECONDS))
+        {
+            return false;
+        }
+
+        let new_file = !self.path.exists();
+        let result = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| {
+                if new_file {
+                    writeln!(file, "{}", HEADER)?;
+                }
+                writeln!(file, "{}", to_line(reading))
+            });
+        if let Err(e) = result {
+            eprintln!("Error: {:?}", e);
+        }
+
+        self.readings.push(*reading);
+        self.trim(reading.time);
+        true
+    }
+
+    pub fn since(&self, time: DateTime<Utc>) -> Vec<ClimateReading> {
+        self.readings
+            .iter()
+            .filter(|reading| reading.time >= time)
+            .copied()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_dir::TestDir;
+
+    fn reading(time: DateTime<Utc>, temperature: f64) -> ClimateReading {
+        ClimateReading {
+            time,
+            temperature,
+            humidity: 45.5,
+            pressure: None,
+        }
+    }
+
+    #[test]
+    fn test_history_is_logged_once_per_minute() {
+        let dir = TestDir::new("climate_history");
+        let path = dir.join("climate_history.csv");
+        let now = Utc::now();
+
+        let mut history = ClimateHistory::load(&path, now);
+        assert!(history.record(&reading(now, 21.0)));
+        assert!(!history.record(&reading(now + Duration::seconds(2), 21.1)));
+        assert!(history.record(&reading(now + Duration::seconds(60), 21.2)));
+
+        let reloaded = ClimateHistory::load(&path, now);
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with(HEADER));
+        assert_eq!(content.lines().count(), 3);
+        let readings = reloaded.since(now);
+        assert_eq!(readings.len(), 2);
+        assert_eq!(readings[1].temperature, 21.2);
+        assert_eq!(readings[1].pressure, None);
+    }
+
+    #[test]
+    fn test_old_lines_are_dropped() {
+        let dir = TestDir::new("climate_retention");
+        let path = dir.join("climate_history.csv");
+        let now = Utc::now();
+        let old = reading(now - Duration::days(RETENTION_DAYS + 1), 19.0);
+        let recent = reading(now - Duration::hours(1), 20.0);
+        fs::write(
+            &path,
+            format!(
+                "{}\n{}\nbroken line\n{}\n",
+                HEADER,
+                to_line(&old),
+                to_line(&recent)
+            ),
+        )
+        .unwrap();
+
+        let history = ClimateHistory::load(&path, now);
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(history.since(now - Duration::days(365)).len(), 1);
+        assert_eq!(content.lines().count(), 2);
+    }
+}
@@ -0,0 +1,320 @@
+use chrono::{DateTime, Duration, Utc};
+use std::fmt::{Display, Error as FmtError, Formatter};
+
+use crate::sensors::{sensor_name, SensorKind, SensorRegistry};
+
+pub mod history;
+
+// the measuring range of the bme280
+const TEMPERATURE_RANGE: (f64, f64) = (-40.0, 85.0);
+const HUMIDITY_RANGE: (f64, f64) = (0.0, 100.0);
+const PRESSURE_RANGE: (f64, f64) = (300.0, 1100.0);
+
+// a trend compares the current reading with the one an hour ago
+const TREND_MINUTES: i64 = 60;
+// older readings mean, that the sensor is gone
+const MAX_READING_AGE_SECONDS: i64 = 30;
+
+pub const DEFAULT_HUMIDITY_MIN: u32 = 30;
+pub const DEFAULT_HUMIDITY_MAX: u32 = 60;
+pub const DEFAULT_TEMPERATURE_MIN: u32 = 18;
+pub const DEFAULT_TEMPERATURE_MAX: u32 = 26;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClimateReading {
+    pub time: DateTime<Utc>,
+    // degrees celsius
+    pub temperature: f64,
+    // percent
+    pub humidity: f64,
+    // hPa, only sent by sensors that measure it
+    pub pressure: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReadingError {
+    Empty,
+    Malformed(String),
+    OutOfRange(&'static str, f64),
+}
+
+impl Display for ReadingError {
+    fn fmt(&self, f: &mut Formatter) -> core::result::Result<(), FmtError> {
+        match self {
+            Self::Empty => write!(f, "no reading"),
+            Self::Malformed(reply) => write!(f, "malformed reading {:?}", reply),
+            Self::OutOfRange(name, value) => write!(f, "{} out of range: {}", name, value),
+        }
+    }
+}
+
+fn checked(name: &'static str, value: f64, range: (f64, f64)) -> Result<f64, ReadingError> {
+    if value.is_finite() && value >= range.0 && value <= range.1 {
+        Ok(value)
+    } else {
+        Err(ReadingError::OutOfRange(name, value))
+    }
+}
+
+// the sensor replies with "temperature humidity [pressure]", padded with zero bytes
+pub fn parse_reading(reply: &str, time: DateTime<Utc>) -> Result<ClimateReading, ReadingError> {
+    let reply = reply.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    if reply.is_empty() {
+        return Err(ReadingError::Empty);
+    }
+    let values = reply
+        .split_whitespace()
+        .map(|value| value.parse::<f64>())
+        .collect::<Result<Vec<f64>, _>>()
+        .map_err(|_| ReadingError::Malformed(reply.into()))?;
+    match values.as_slice() {
+        [temperature, humidity] | [temperature, humidity, _] => Ok(ClimateReading {
+            time,
+            temperature: checked("temperature", *temperature, TEMPERATURE_RANGE)?,
+            humidity: checked("humidity", *humidity, HUMIDITY_RANGE)?,
+            pressure: match values.get(2) {
+                Some(pressure) => Some(checked("pressure", *pressure, PRESSURE_RANGE)?),
+                None => None,
+            },
+        }),
+        _ => Err(ReadingError::Malformed(reply.into())),
+    }
+}
+
+impl ClimateReading {
+    // the values a device publishes into the registry
+    pub fn sensor_values(&self) -> Vec<(SensorKind, f64)> {
+        let mut values = vec![
+            (SensorKind::Temperature, self.temperature),
+            (SensorKind::Humidity, self.humidity),
+        ];
+        if let Some(pressure) = self.pressure {
+            values.push((SensorKind::Pressure, pressure));
+        }
+        values
+    }
+
+    // temperature and humidity have to come from the same source, the newest one wins
+    pub fn from_sensors(registry: &SensorRegistry) -> Option<ClimateReading> {
+        let temperature = registry.latest(SensorKind::Temperature)?;
+        let humidity = registry.value(&sensor_name(&temperature.source, SensorKind::Humidity))?;
+        let pressure = registry.value(&sensor_name(&temperature.source, SensorKind::Pressure));
+        Some(ClimateReading {
+            time: temperature.time.min(humidity.time),
+            temperature: temperature.value,
+            humidity: humidity.value,
+            pressure: pressure.map(|pressure| pressure.value),
+        })
+    }
+
+    // false once the sensor stopped sending
+    pub fn is_recent(&self, now: DateTime<Utc>) -> bool {
+        now - self.time <= Duration::seconds(MAX_READING_AGE_SECONDS)
+    }
+}
+
+pub fn celsius_to_fahrenheit(celsius: f64) -> f64 {
+    celsius * 9.0 / 5.0 + 32.0
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Trend {
+    Rising,
+    Steady,
+    Falling,
+}
+
+impl Trend {
+    pub fn arrow(&self) -> &'static str {
+        match self {
+            Trend::Rising => "\u{2191}",
+            Trend::Steady => "\u{2192}",
+            Trend::Falling => "\u{2193}",
+        }
+    }
+}
+
+// changes below the threshold are noise of the sensor
+pub fn trend(
+    history: &[ClimateReading],
+    current: &ClimateReading,
+    value: impl Fn(&ClimateReading) -> Option<f64>,
+    threshold: f64,
+) -> Option<Trend> {
+    let before = current.time - Duration::minutes(TREND_MINUTES);
+    // the history is sorted by time
+    let earlier = history
+        .iter()
+        .rev()
+        .find(|reading| reading.time <= before)?;
+    let change = value(current)? - value(earlier)?;
+    Some(if change > threshold {
+        Trend::Rising
+    } else if change < -threshold {
+        Trend::Falling
+    } else {
+        Trend::Steady
+    })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ComfortThresholds {
+    pub humidity_min: f64,
+    pub humidity_max: f64,
+    pub temperature_min: f64,
+    pub temperature_max: f64,
+}
+
+impl Default for ComfortThresholds {
+    fn default() -> ComfortThresholds {
+        ComfortThresholds {
+            humidity_min: DEFAULT_HUMIDITY_MIN as f64,
+            humidity_max: DEFAULT_HUMIDITY_MAX as f64,
+            temperature_min: DEFAULT_TEMPERATURE_MIN as f64,
+            temperature_max: DEFAULT_TEMPERATURE_MAX as f64,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Advice {
+    Ventilate,
+    TooWarm,
+    TooCold,
+    TooDry,
+}
+
+impl Advice {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Advice::Ventilate => "Ventilate",
+            Advice::TooWarm => "Too warm",
+            Advice::TooCold => "Too cold",
+            Advice::TooDry => "Too dry",
+        }
+    }
+}
+
+// the most important advice comes first, humid air leads to mould
+pub fn advice(reading: &ClimateReading, thresholds: &ComfortThresholds) -> Vec<Advice> {
+    let mut advice = Vec::new();
+    if reading.humidity > thresholds.humidity_max {
+        advice.push(Advice::Ventilate);
+    }
+    if reading.temperature > thresholds.temperature_max {
+        advice.push(Advice::TooWarm);
+    }
+    if reading.temperature < thresholds.temperature_min {
+        advice.push(Advice::TooCold);
+    }
+    if reading.humidity < thresholds.humidity_min {
+        advice.push(Advice::TooDry);
+    }
+    advice
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(minutes: i64, temperature: f64, humidity: f64) -> ClimateReading {
+        ClimateReading {
+            time: DateTime::from_timestamp(1_760_000_000 + minutes * 60, 0).unwrap(),
+            temperature,
+            humidity,
+            pressure: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_reading() {
+        let now = Utc::now();
+        let parsed = parse_reading("22.41 48.30\0\0\0", now).unwrap();
+        assert_eq!(parsed.temperature, 22.41);
+        assert_eq!(parsed.humidity, 48.3);
+        assert_eq!(parsed.pressure, None);
+        assert_eq!(
+            parse_reading("22.4 48.3 1013.2", now).unwrap().pressure,
+            Some(1013.2)
+        );
+
+        assert_eq!(parse_reading("\0\0\0", now), Err(ReadingError::Empty));
+        assert!(matches!(
+            parse_reading("22.4", now),
+            Err(ReadingError::Malformed(_))
+        ));
+        assert!(matches!(
+            parse_reading("22.4 4x.3", now),
+            Err(ReadingError::Malformed(_))
+        ));
+        assert_eq!(
+            parse_reading("22.4 148.3", now),
+            Err(ReadingError::OutOfRange("humidity", 148.3))
+        );
+    }
+
+    #[test]
+    fn test_reading_from_sensors() {
+        let mut registry = SensorRegistry::default();
+        assert_eq!(ClimateReading::from_sensors(&registry), None);
+
+        let reading = reading(0, 21.5, 44.0);
+        registry.advertise("teensy", &[SensorKind::Temperature, SensorKind::Humidity]);
+        for (kind, value) in reading.sensor_values() {
+            registry
+                .publish("teensy", kind, value, reading.time)
+                .unwrap();
+        }
+        assert_eq!(ClimateReading::from_sensors(&registry), Some(reading));
+
+        // a newer temperature without humidity is no climate reading
+        registry.advertise("desk", &[SensorKind::Temperature]);
+        registry
+            .publish("desk", SensorKind::Temperature, 25.0, Utc::now())
+            .unwrap();
+        assert_eq!(ClimateReading::from_sensors(&registry), None);
+    }
+
+    #[test]
+    fn test_reading_is_recent() {
+        let reading = reading(0, 21.5, 44.0);
+        assert!(reading.is_recent(reading.time + Duration::seconds(MAX_READING_AGE_SECONDS)));
+        assert!(!reading.is_recent(reading.time + Duration::seconds(MAX_READING_AGE_SECONDS + 1)));
+    }
+
+    #[test]
+    fn test_trend() {
+        let history = vec![reading(0, 21.0, 50.0), reading(30, 21.5, 50.0)];
+        let current = reading(60, 22.0, 51.0);
+        let temperature = |reading: &ClimateReading| Some(reading.temperature);
+        let humidity = |reading: &ClimateReading| Some(reading.humidity);
+        assert_eq!(
+            trend(&history, &current, temperature, 0.5),
+            Some(Trend::Rising)
+        );
+        assert_eq!(
+            trend(&history, &current, humidity, 3.0),
+            Some(Trend::Steady)
+        );
+        assert_eq!(
+            trend(&history, &current, |reading| reading.pressure, 1.0),
+            None
+        );
+        // not enough history yet
+        assert_eq!(trend(&history[1..], &current, temperature, 0.5), None);
+    }
+
+    #[test]
+    fn test_advice() {
+        let thresholds = ComfortThresholds::default();
+        assert_eq!(advice(&reading(0, 21.0, 45.0), &thresholds), vec![]);
+        assert_eq!(
+            advice(&reading(0, 27.0, 65.0), &thresholds),
+            vec![Advice::Ventilate, Advice::TooWarm]
+        );
+        assert_eq!(
+            advice(&reading(0, 16.0, 25.0), &thresholds),
+            vec![Advice::TooCold, Advice::TooDry]
+        );
+    }
+}
@@ -1,3 +1,6 @@
+use crate::screen_manager::Hotkey;
+use crate::sensors::file::FileSensor;
+use crate::system::alerts::AlertRule;
 use exchange_format::ConfigParam;
 use indexmap::*;
 use serde::{Deserialize, Serialize};
@@ -7,6 +10,14 @@ use std::collections::HashMap;
 pub struct Config {
     pub devices: HashMap<String, DeviceConfig>,
     pub screens: HashMap<String, ScreenConfig>,
+    #[serde(default)]
+    pub alerts: Vec<AlertRule>,
+    // host side sensors, devices advertise their sensors themselves
+    #[serde(default)]
+    pub sensors: Vec<FileSensor>,
+    // media keys that show a screen in a mode, instead of the media screen
+    #[serde(default)]
+    pub hotkeys: Vec<Hotkey>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -52,6 +63,16 @@ impl Config {
         };
     }
 
+    pub fn set_device_value(&mut self, device: &str, key: &str, value: ConfigParam) {
+        self.devices
+            .entry(device.to_string())
+            .or_insert_with(|| DeviceConfig {
+                config_attributes: IndexMap::new(),
+            })
+            .config_attributes
+            .insert(key.to_string(), value);
+    }
+
     pub fn set_device_brightness(&mut self, device: &str, value: u8) {
         match self.devices.get_mut(&device.to_string()) {
             Some(config) => {
@@ -81,6 +102,9 @@ mod tests {
         let mut config = Config {
             devices: HashMap::new(),
             screens: HashMap::new(),
+            alerts: Vec::new(),
+            sensors: Vec::new(),
+            hotkeys: Vec::new(),
         };
 
         let screen_name = "screen1".to_string();
@@ -94,6 +118,9 @@ mod tests {
         let mut config = Config {
             devices: HashMap::new(),
             screens: HashMap::new(),
+            alerts: Vec::new(),
+            sensors: Vec::new(),
+            hotkeys: Vec::new(),
         };
 
         let screen_name = "screen1".to_string();
@@ -108,6 +135,9 @@ mod tests {
         let mut config = Config {
             devices: HashMap::new(),
             screens: HashMap::new(),
+            alerts: Vec::new(),
+            sensors: Vec::new(),
+            hotkeys: Vec::new(),
         };
 
         let screen_name = "screen1".to_string();
@@ -133,6 +163,9 @@ mod tests {
         let mut config = Config {
             devices: HashMap::new(),
             screens: HashMap::new(),
+            alerts: Vec::new(),
+            sensors: Vec::new(),
+            hotkeys: Vec::new(),
         };
 
         let screen_name = "screen1".to_string();
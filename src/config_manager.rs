@@ -1,4 +1,7 @@
 use crate::config::{Config, ScreenConfig};
+use crate::screen_manager::Hotkey;
+use crate::sensors::file::FileSensor;
+use crate::system::alerts::AlertRule;
 
 use exchange_format::ConfigParam;
 use indexmap::*;
@@ -7,6 +10,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
     fs::{self, File},
+    sync::{Arc, RwLock},
 };
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -22,6 +26,9 @@ impl ConfigManager {
         let config = Config {
             devices: HashMap::new(),
             screens: HashMap::new(),
+            alerts: Vec::new(),
+            sensors: Vec::new(),
+            hotkeys: Vec::new(),
         };
         let mut this = ConfigManager {
             config,
@@ -90,6 +97,14 @@ impl ConfigManager {
         }
     }
 
+    // like screen_enabled, but without registering unknown screens, so a read lock is enough
+    pub fn is_screen_enabled(&self, screen: &str) -> bool {
+        self.config
+            .screens
+            .get(screen)
+            .is_some_and(|screen_config| screen_config.active)
+    }
+
     pub fn set_screen_status(&mut self, screen: String, enabled: bool) {
         self.config.set_screen_active(screen, enabled);
     }
@@ -104,6 +119,20 @@ impl ConfigManager {
         }
     }
 
+    pub fn get_string_value(&self, screen: &str, key: &str) -> Option<String> {
+        match self.get_value(screen, key) {
+            Some(ConfigParam::String(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_integer_value(&self, screen: &str, key: &str) -> Option<u32> {
+        match self.get_value(screen, key) {
+            Some(ConfigParam::Integer(value)) => Some(value),
+            _ => None,
+        }
+    }
+
     pub fn get_screen_config(&self, screen: &str) -> Option<exchange_format::ExchangeableConfig> {
         match self.config.screens.get(screen) {
             Some(screen_config) => Some(exchange_format::ExchangeableConfig {
@@ -133,6 +162,56 @@ impl ConfigManager {
     pub fn set_brightness(&mut self, device: &str, value: u8) {
         self.config.set_device_brightness(device, value)
     }
+
+    pub fn get_device_value(&self, device: &str, key: &str) -> Option<ConfigParam> {
+        self.config
+            .devices
+            .get(device)
+            .and_then(|config| config.config_attributes.get(key).cloned())
+    }
+
+    pub fn set_device_value(&mut self, device: &str, key: &str, value: ConfigParam) {
+        self.config.set_device_value(device, key, value)
+    }
+
+    pub fn get_alert_rules(&self) -> Vec<AlertRule> {
+        self.config.alerts.clone()
+    }
+
+    pub fn get_host_sensors(&self) -> Vec<FileSensor> {
+        self.config.sensors.clone()
+    }
+
+    pub fn get_hotkeys(&self) -> Vec<Hotkey> {
+        self.config.hotkeys.clone()
+    }
+}
+
+// the integer setting of a screen, for threads that share the config manager
+pub fn integer_value(
+    config_manager: &Arc<RwLock<ConfigManager>>,
+    screen: &str,
+    key: &str,
+    default: u32,
+) -> u32 {
+    config_manager
+        .read()
+        .unwrap()
+        .get_integer_value(screen, key)
+        .unwrap_or(default)
+}
+
+pub fn string_value(
+    config_manager: &Arc<RwLock<ConfigManager>>,
+    screen: &str,
+    key: &str,
+    default: &str,
+) -> String {
+    config_manager
+        .read()
+        .unwrap()
+        .get_string_value(screen, key)
+        .unwrap_or_else(|| default.into())
 }
 
 #[cfg(test)]
@@ -161,8 +240,12 @@ mod tests {
         config_manager.set_screen_status(screen_name.clone(), true);
         assert_eq!(config_manager.screen_enabled(screen_name.clone()), true);
 
+        assert!(config_manager.is_screen_enabled(&screen_name));
+
         config_manager.set_screen_status(screen_name.clone(), false);
+        assert!(!config_manager.is_screen_enabled(&screen_name));
         assert_eq!(config_manager.screen_enabled(screen_name), false);
+        assert!(!config_manager.is_screen_enabled("unknown"));
     }
 
     #[test]
@@ -178,6 +261,41 @@ mod tests {
         assert!(matches!(retrieved_value.unwrap(), ConfigParam::Integer(42)));
     }
 
+    #[test]
+    fn test_get_typed_values() {
+        let mut config_manager = ConfigManager::new(PATH);
+        let screen_name = "screen1".to_string();
+
+        config_manager.set_value(
+            screen_name.clone(),
+            "string".into(),
+            ConfigParam::String("value".into()),
+        );
+        config_manager.set_value(
+            screen_name.clone(),
+            "integer".into(),
+            ConfigParam::Integer(7),
+        );
+
+        assert_eq!(
+            config_manager.get_string_value(&screen_name, "string"),
+            Some("value".into())
+        );
+        assert_eq!(
+            config_manager.get_integer_value(&screen_name, "integer"),
+            Some(7)
+        );
+        // wrong types are treated as missing values
+        assert_eq!(
+            config_manager.get_integer_value(&screen_name, "string"),
+            None
+        );
+        assert_eq!(
+            config_manager.get_string_value(&screen_name, "missing"),
+            None
+        );
+    }
+
     #[test]
     fn test_get_screen_config() {
         let mut config_manager = ConfigManager::new(PATH);
@@ -193,4 +311,22 @@ mod tests {
             Some(ConfigParam::Integer(42))
         ));
     }
+
+    #[test]
+    fn test_alert_rules() {
+        let mut config_manager = ConfigManager::new(PATH);
+        let rule: AlertRule = serde_json::from_str(
+            r#"{"metric": "cpu", "comparison": "above", "threshold": 90, "duration": 30}"#,
+        )
+        .unwrap();
+
+        config_manager.config.alerts = vec![rule.clone()];
+        assert_eq!(config_manager.get_alert_rules(), vec![rule]);
+
+        // configs without alert rules are still valid
+        let config: Config = serde_json::from_str(r#"{"devices": {}, "screens": {}}"#).unwrap();
+        assert!(config.alerts.is_empty());
+        assert!(config.sensors.is_empty());
+        assert!(config.hotkeys.is_empty());
+    }
 }
@@ -3,18 +3,26 @@ use std::{
     thread,
 };
 
+use chrono::Utc;
 use crossbeam_channel::{bounded, Receiver, Sender};
 
 use crate::{
-    adjust_brightness_rgb, converters::image::ImageProcessor, dada_packet::DadaPacket,
-    helpers::display_serial_com::*, CLOSE_REQUESTED, HIBERNATING, LAST_BME_INFO,
+    adjust_brightness_rgb,
+    climate::{parse_reading, ClimateReading, ReadingError},
+    converters::image::ImageProcessor,
+    dada_packet::DadaPacket,
+    helpers::display_serial_com::*,
+    media::{self, MediaCommand},
+    sensors::SensorKind,
+    CLOSE_REQUESTED, HIBERNATING, SENSORS,
 };
 
 pub struct Device {
     identifier: String,
     baud: u32,
     use_dada_packet: bool,
-    has_bme_sensor: bool,
+    // the sensors the device advertises, read with ACCESS_BME_SENSOR
+    sensors: Vec<SensorKind>,
     background_workers_started: std::sync::atomic::AtomicBool,
     image_processor: ImageProcessor,
     adjust_brightness_on_device: bool,
@@ -31,6 +39,7 @@ impl Device {
     const KEEP_ALIVE: u8 = 229;
     const SEND_NEW_IMAGE: u8 = 228;
     const ACCESS_BME_SENSOR: u8 = 205;
+    const READ_BUTTONS: u8 = 206;
     const RESET_DISPLAY: u8 = 17;
     const STAND_BY: u8 = 18;
     const WAKE_UP: u8 = 19;
@@ -41,7 +50,7 @@ impl Device {
         baud: u32,
         use_dada_packet: bool,
         image_processor: ImageProcessor,
-        has_bme_sensor: bool,
+        sensors: Vec<SensorKind>,
         adjust_brightness_on_device: bool,
         brightness_calculation_adjustment: Arc<dyn Fn(u8) -> u8 + Send + Sync>,
     ) -> Device {
@@ -52,7 +61,7 @@ impl Device {
             use_dada_packet,
             sender,
             receiver,
-            has_bme_sensor,
+            sensors,
             image_processor,
             adjust_brightness_on_device,
             brightness_calculation_adjustment,
@@ -113,17 +122,21 @@ impl Device {
         false
     }
 
-    pub fn get_bme_info(&self) -> (String, String) {
+    pub fn get_climate_reading(&self) -> Result<ClimateReading, ReadingError> {
         if self.send_command(Self::ACCESS_BME_SENSOR) {
-            let mut result = read_bme_sensor(&mut self.port.lock().unwrap());
-            result = result.trim_end_matches('\0').into();
-            let mut parts = result.split(' ');
-            return (
-                parts.next().unwrap_or_default().into(),
-                parts.next().unwrap_or_default().into(),
-            );
+            let reply = read_bme_sensor(&mut self.port.lock().unwrap());
+            return parse_reading(&reply, Utc::now());
         }
-        (String::new(), String::new())
+        Err(ReadingError::Empty)
+    }
+
+    // the code of the button pressed since the last poll, the port is held until the reply
+    pub fn read_button(&self) -> Option<u8> {
+        let mut port = self.port.lock().unwrap();
+        if send_command(&mut port, &Self::READ_BUTTONS.to_le_bytes()) {
+            return read_button(&mut port);
+        }
+        None
     }
 
     pub fn reset_display(&self) {
@@ -174,22 +187,42 @@ impl Device {
         }
     }
 
-    pub fn start_background_workers(self: &'static Device) {
+    // the key of the device is the name of its sensors in the registry, e.g. "teensy.temperature"
+    // the buttons are only polled, if the firmware of the device reports them
+    pub fn start_background_workers(self: &'static Device, key: &'static str, media_buttons: bool) {
         if !self.background_workers_started.load(Ordering::Acquire) {
             self.background_workers_started
                 .store(true, Ordering::Release);
             self.start_writer();
-            self.start_bme_sensor_background_thread();
+            self.start_sensor_background_thread(key);
+            if media_buttons {
+                self.start_button_thread();
+            }
         }
     }
 
-    fn start_bme_sensor_background_thread(self: &'static Device) {
-        if self.has_bme_sensor {
+    fn publish_climate_reading(&self, key: &str, reading: &ClimateReading) {
+        let mut registry = SENSORS.write().unwrap();
+        for (kind, value) in reading.sensor_values() {
+            // e.g. pressure of a sensor, that only advertises temperature and humidity
+            if self.sensors.contains(&kind) {
+                if let Err(e) = registry.publish(key, kind, value, reading.time) {
+                    eprintln!("Error: {}", e);
+                }
+            }
+        }
+    }
+
+    fn start_sensor_background_thread(self: &'static Device, key: &'static str) {
+        if !self.sensors.is_empty() {
+            SENSORS.write().unwrap().advertise(key, &self.sensors);
             thread::spawn(move || loop {
                 if self.is_connected() {
-                    let bme_info = self.get_bme_info();
-                    if !bme_info.0.is_empty() && !bme_info.1.is_empty() {
-                        *LAST_BME_INFO.lock().unwrap() = bme_info;
+                    match self.get_climate_reading() {
+                        Ok(reading) => self.publish_climate_reading(key, &reading),
+                        // no reply, e.g. while the device reconnects
+                        Err(ReadingError::Empty) => {}
+                        Err(e) => eprintln!("Error: {}", e),
                     }
                 }
                 if CLOSE_REQUESTED.load(std::sync::atomic::Ordering::Acquire) {
@@ -200,6 +233,21 @@ impl Device {
         }
     }
 
+    // the physical buttons control the player like the buttons of the app
+    fn start_button_thread(self: &'static Device) {
+        thread::spawn(move || loop {
+            if self.is_connected() && !*HIBERNATING.lock().unwrap() {
+                if let Some(command) = self.read_button().and_then(MediaCommand::from_button) {
+                    media::send_command(command);
+                }
+            }
+            if CLOSE_REQUESTED.load(std::sync::atomic::Ordering::Acquire) {
+                return;
+            }
+            thread::sleep(std::time::Duration::from_millis(100));
+        });
+    }
+
     fn start_writer(self: &'static Device) {
         thread::spawn(move || {
             let mut last_sum = 0;
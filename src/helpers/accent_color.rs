@@ -0,0 +1,108 @@
+use image::Rgb;
+
+// hue buckets of 10 degrees
+const HUE_BUCKETS: usize = 36;
+// every n-th pixel is enough to find the dominant color of a cover
+const PIXEL_STEP: usize = 4;
+// dark and grey pixels carry no color
+const MIN_VALUE: f32 = 0.25;
+const MIN_SATURATION: f32 = 0.3;
+// the accent is drawn on a dark background, so it has to be bright enough
+const MIN_BRIGHTNESS: f32 = 180.0;
+
+#[derive(Clone, Copy, Default)]
+struct Bucket {
+    weight: f32,
+    red: f32,
+    green: f32,
+    blue: f32,
+}
+
+fn hue(red: f32, green: f32, blue: f32, max: f32, delta: f32) -> f32 {
+    let hue = if max == red {
+        60.0 * (((green - blue) / delta) % 6.0)
+    } else if max == green {
+        60.0 * ((blue - red) / delta + 2.0)
+    } else {
+        60.0 * ((red - green) / delta + 4.0)
+    };
+    if hue < 0.0 {
+        hue + 360.0
+    } else {
+        hue
+    }
+}
+
+// the most prominent saturated color of the rgb bytes, none for grey or dark images
+pub fn accent_color(rgb: &[u8]) -> Option<Rgb<u8>> {
+    let mut buckets = [Bucket::default(); HUE_BUCKETS];
+    for pixel in rgb.chunks_exact(3).step_by(PIXEL_STEP) {
+        let (red, green, blue) = (pixel[0] as f32, pixel[1] as f32, pixel[2] as f32);
+        let max = red.max(green).max(blue);
+        let delta = max - red.min(green).min(blue);
+        let value = max / 255.0;
+        if value < MIN_VALUE || delta / max < MIN_SATURATION {
+            continue;
+        }
+        let weight = delta / max * value;
+        let index = (hue(red, green, blue, max, delta) / 360.0 * HUE_BUCKETS as f32) as usize;
+        let bucket = &mut buckets[index.min(HUE_BUCKETS - 1)];
+        bucket.weight += weight;
+        bucket.red += red * weight;
+        bucket.green += green * weight;
+        bucket.blue += blue * weight;
+    }
+
+    let bucket = buckets
+        .iter()
+        .filter(|bucket| bucket.weight > 0.0)
+        .max_by(|a, b| a.weight.total_cmp(&b.weight))?;
+    let color = [
+        bucket.red / bucket.weight,
+        bucket.green / bucket.weight,
+        bucket.blue / bucket.weight,
+    ];
+    let brightest = color[0].max(color[1]).max(color[2]);
+    let scale = (MIN_BRIGHTNESS / brightest).max(1.0);
+    Some(Rgb(
+        color.map(|channel| (channel * scale).round().min(255.0) as u8)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(pixels: &[([u8; 3], usize)]) -> Vec<u8> {
+        pixels
+            .iter()
+            .flat_map(|(color, count)| std::iter::repeat_n(*color, *count))
+            .flatten()
+            .collect()
+    }
+
+    #[test]
+    fn test_dominant_color_wins() {
+        let cover = image(&[
+            ([200, 30, 30], 400),
+            ([30, 30, 200], 100),
+            ([10, 10, 10], 1000),
+            ([240, 240, 240], 1000),
+        ]);
+        assert_eq!(accent_color(&cover), Some(Rgb([200, 30, 30])));
+    }
+
+    #[test]
+    fn test_dark_colors_are_brightened() {
+        let cover = image(&[([0, 90, 45], 100)]);
+        let accent = accent_color(&cover).unwrap();
+        assert_eq!(accent[1], MIN_BRIGHTNESS as u8);
+        assert!(accent[2] > accent[0]);
+    }
+
+    #[test]
+    fn test_grey_cover_has_no_accent() {
+        assert_eq!(accent_color(&image(&[([211, 211, 211], 100)])), None);
+        assert_eq!(accent_color(&[]), None);
+    }
+}
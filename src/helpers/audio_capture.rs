@@ -0,0 +1,118 @@
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    FromSample, Sample, SizedSample,
+};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use super::spectrum::FFT_SIZE;
+
+// the latest frames of the captured audio, left and right
+pub type SampleBuffer = Arc<Mutex<VecDeque<[f32; 2]>>>;
+
+// captures, until it is dropped
+pub struct AudioCapture {
+    _stream: cpal::Stream,
+    pub buffer: SampleBuffer,
+    pub sample_rate: u32,
+}
+
+fn name_contains(device: &cpal::Device, name: &str) -> bool {
+    device
+        .name()
+        .map(|device_name| device_name.to_lowercase().contains(name))
+        .unwrap_or(false)
+}
+
+// without a configured name, windows captures the default output in loopback mode. pulseaudio
+// offers the output as monitor source, e.g. "Monitor of Built-in Audio". the monitor can be
+// picked with the PULSE_SOURCE environment variable for the "pulse" device as well.
+fn find_device(
+    host: &cpal::Host,
+    name: &str,
+) -> Option<(cpal::Device, cpal::SupportedStreamConfig)> {
+    let name = name.trim().to_lowercase();
+    if !name.is_empty() {
+        if let Some(device) = host
+            .input_devices()
+            .ok()?
+            .find(|device| name_contains(device, &name))
+        {
+            let config = device.default_input_config().ok()?;
+            return Some((device, config));
+        }
+        let device = host
+            .output_devices()
+            .ok()?
+            .find(|device| name_contains(device, &name))?;
+        let config = device.default_output_config().ok()?;
+        return Some((device, config));
+    }
+    if cfg!(windows) {
+        let device = host.default_output_device()?;
+        let config = device.default_output_config().ok()?;
+        return Some((device, config));
+    }
+    let device = host
+        .input_devices()
+        .ok()?
+        .find(|device| name_contains(device, "monitor"))
+        .or_else(|| host.default_input_device())?;
+    let config = device.default_input_config().ok()?;
+    Some((device, config))
+}
+
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    buffer: SampleBuffer,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels.max(1) as usize;
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            let mut buffer = buffer.lock().unwrap();
+            for frame in data.chunks(channels) {
+                let left = frame[0].to_sample::<f32>();
+                let right = frame
+                    .get(1)
+                    .map(|sample| sample.to_sample::<f32>())
+                    .unwrap_or(left);
+                buffer.push_back([left, right]);
+            }
+            let excess = buffer.len().saturating_sub(FFT_SIZE);
+            buffer.drain(..excess);
+        },
+        |e| eprintln!("Audio capture error: {}", e),
+        None,
+    )
+}
+
+pub fn start_capture(device_name: &str) -> Result<AudioCapture, String> {
+    let host = cpal::default_host();
+    let (device, config) =
+        find_device(&host, device_name).ok_or_else(|| String::from("No audio device"))?;
+    let sample_rate = config.sample_rate().0;
+    let buffer: SampleBuffer = Arc::new(Mutex::new(VecDeque::with_capacity(FFT_SIZE)));
+    let stream_config = config.config();
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::F32 => build_stream::<f32>(&device, &stream_config, buffer.clone()),
+        cpal::SampleFormat::I16 => build_stream::<i16>(&device, &stream_config, buffer.clone()),
+        cpal::SampleFormat::U16 => build_stream::<u16>(&device, &stream_config, buffer.clone()),
+        cpal::SampleFormat::I32 => build_stream::<i32>(&device, &stream_config, buffer.clone()),
+        format => return Err(format!("Unsupported sample format {}", format)),
+    }
+    .map_err(|e| e.to_string())?;
+    stream.play().map_err(|e| e.to_string())?;
+    Ok(AudioCapture {
+        _stream: stream,
+        buffer,
+        sample_rate,
+    })
+}
@@ -0,0 +1,137 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use chrono_tz::Tz;
+use std::{f32::consts::PI, time::Duration};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorldTime {
+    pub label: String,
+    pub time_zone: Tz,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Countdown {
+    pub label: String,
+    pub target: NaiveDateTime,
+}
+
+// "Europe/Berlin, NYC=America/New_York" - without a label, the city of the time zone is used
+pub fn parse_time_zones(value: &str) -> Vec<WorldTime> {
+    value
+        .split(',')
+        .filter_map(|entry| {
+            let (label, name) = match entry.split_once('=') {
+                Some((label, name)) => (label.trim().to_string(), name.trim()),
+                None => {
+                    let name = entry.trim();
+                    let city = name.rsplit('/').next().unwrap_or(name).replace('_', " ");
+                    (city, name)
+                }
+            };
+            let time_zone: Tz = name.parse().ok()?;
+            Some(WorldTime { label, time_zone })
+        })
+        .collect()
+}
+
+// "Release=2026-12-24 18:00; Vacation=2026-08-01" - events without a time start at midnight
+pub fn parse_countdowns(value: &str) -> Vec<Countdown> {
+    value
+        .split(';')
+        .filter_map(|entry| {
+            let (label, date) = entry.split_once('=')?;
+            let date = date.trim();
+            let target = NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M")
+                .ok()
+                .or_else(|| {
+                    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                        .ok()
+                        .and_then(|date| date.and_hms_opt(0, 0, 0))
+                })?;
+            Some(Countdown {
+                label: label.trim().to_string(),
+                target,
+            })
+        })
+        .collect()
+}
+
+// events, that are still ahead, sorted by the remaining time
+pub fn upcoming(countdowns: &[Countdown], now: NaiveDateTime) -> Vec<(Countdown, Duration)> {
+    let mut upcoming: Vec<(Countdown, Duration)> = countdowns
+        .iter()
+        .filter_map(|countdown| {
+            let remaining = (countdown.target - now).to_std().ok()?;
+            Some((countdown.clone(), remaining))
+        })
+        .collect();
+    upcoming.sort_by_key(|(_, remaining)| *remaining);
+    upcoming
+}
+
+// end point of a clock hand, the fraction is the part of a full turn starting at 12 o'clock
+pub fn hand_end(center: (f32, f32), length: f32, fraction: f32) -> (f32, f32) {
+    let angle = fraction * 2.0 * PI - PI / 2.0;
+    (
+        center.0 + length * angle.cos(),
+        center.1 + length * angle.sin(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time_zones() {
+        let zones = parse_time_zones("Europe/Berlin, NYC=America/New_York, Invalid/Zone");
+        assert_eq!(
+            zones,
+            vec![
+                WorldTime {
+                    label: "Berlin".into(),
+                    time_zone: chrono_tz::Europe::Berlin
+                },
+                WorldTime {
+                    label: "NYC".into(),
+                    time_zone: chrono_tz::America::New_York
+                }
+            ]
+        );
+        assert_eq!(
+            parse_time_zones("America/Los_Angeles")[0].label,
+            "Los Angeles"
+        );
+        assert!(parse_time_zones("").is_empty());
+    }
+
+    #[test]
+    fn test_countdowns() {
+        let countdowns = parse_countdowns("Release=2026-12-24 18:00; Vacation=2026-08-01; broken");
+        assert_eq!(countdowns.len(), 2);
+
+        let now = NaiveDate::from_ymd_opt(2026, 7, 31)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let events = upcoming(&countdowns, now);
+        assert_eq!(events[0].0.label, "Vacation");
+        assert_eq!(events[0].1, Duration::from_secs(12 * 3600));
+        assert_eq!(events[1].0.label, "Release");
+        assert_eq!(events[1].1, Duration::from_secs(146 * 86400 + 6 * 3600));
+
+        // past events are dropped
+        let later = NaiveDate::from_ymd_opt(2026, 9, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        assert_eq!(upcoming(&countdowns, later).len(), 1);
+    }
+
+    #[test]
+    fn test_hand_end() {
+        let (x, y) = hand_end((50.0, 50.0), 10.0, 0.0);
+        assert!((x - 50.0).abs() < 0.001 && (y - 40.0).abs() < 0.001);
+        let (x, y) = hand_end((50.0, 50.0), 10.0, 0.25);
+        assert!((x - 60.0).abs() < 0.001 && (y - 50.0).abs() < 0.001);
+    }
+}
@@ -0,0 +1,374 @@
+use image::{imageops::FilterType, DynamicImage, Rgb, RgbImage};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{Debug, Error as FmtError, Formatter},
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use super::track_metadata::TrackMetadata;
+
+// covers of the last albums, so skipping back and forth does not decode them again
+const DEFAULT_CAPACITY: usize = 16;
+// larger covers are scaled down once, the displays are much smaller anyway
+const MAX_SOURCE_SIZE: u32 = 512;
+const IMAGE_EXTENSIONS: [&str; 6] = ["jpg", "jpeg", "png", "webp", "bmp", "gif"];
+// the folder images of common players first, any image last
+pub const DEFAULT_COVER_PATTERNS: &str = "Folder.jpg, AlbumArtSmall.jpg, AlbumArt.jpg, Album.jpg, \
+    .folder.png, cover.jpg, thumb.jpg, Folder.png, cover.png, Folder.webp, cover.webp, \
+    *.jpg, *.png, *.webp";
+
+// decodes the formats of the image crate and webp, which the image crate does not decode here
+pub fn decode_cover(bytes: &[u8]) -> Option<DynamicImage> {
+    if bytes.len() > 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return webp::Decoder::new(bytes)
+            .decode()
+            .map(|image| image.to_image());
+    }
+    image::load_from_memory(bytes).ok()
+}
+
+pub fn is_image_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| IMAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+pub fn load_cover_file(path: &Path) -> Option<DynamicImage> {
+    decode_cover(&fs::read(path).ok()?)
+}
+
+// "Folder.jpg, *.png" to its patterns
+pub fn cover_patterns(patterns: &str) -> Vec<String> {
+    patterns
+        .split(',')
+        .map(|pattern| pattern.trim().to_string())
+        .filter(|pattern| !pattern.is_empty())
+        .collect()
+}
+
+fn is_disc_directory(directory: &Path) -> bool {
+    let name = directory
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    let number = name
+        .strip_prefix("cd")
+        .or_else(|| name.strip_prefix("disc"))
+        .or_else(|| name.strip_prefix("disk"))
+        .map(|number| number.trim_start_matches([' ', '_', '-']));
+    number.is_some_and(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+}
+
+// the first image in the folder of the track, that matches a pattern. patterns are tried in
+// their order and compared case insensitive, since "folder.jpg" is as good as "Folder.jpg".
+// tracks in disc folders like "CD1" use the cover of the album folder as well.
+pub fn find_cover_file(track_path: &Path, patterns: &[String]) -> Option<PathBuf> {
+    let options = glob::MatchOptions {
+        case_sensitive: false,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+    let patterns: Vec<glob::Pattern> = patterns
+        .iter()
+        .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+        .collect();
+
+    let directory = track_path.parent()?;
+    let mut directories = vec![directory];
+    if is_disc_directory(directory) {
+        directories.extend(directory.parent());
+    }
+    directories.into_iter().find_map(|directory| {
+        let mut files: Vec<PathBuf> = fs::read_dir(directory)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && is_image_file(path))
+            .collect();
+        files.sort();
+        patterns.iter().find_map(|pattern| {
+            files
+                .iter()
+                .find(|file| {
+                    file.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| pattern.matches_with(name, options))
+                })
+                .cloned()
+        })
+    })
+}
+
+// "file:///home/me/My%20Album/cover.jpg" or "file:///C:/Music/cover.jpg", e.g. the art url of
+// mpris players. other urls are no local files.
+pub fn file_url_to_path(url: &str) -> Option<PathBuf> {
+    let path = url.strip_prefix("file://")?;
+    let path = path.strip_prefix("localhost").unwrap_or(path);
+
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = tail
+            .get(..2)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(decoded) if byte == b'%' => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    let path = String::from_utf8(bytes).ok()?;
+
+    // windows drives are written as "/C:/..."
+    let drive = path.as_bytes();
+    if drive.len() > 2 && drive[0] == b'/' && drive[1].is_ascii_alphabetic() && drive[2] == b':' {
+        return Some(PathBuf::from(&path[1..]));
+    }
+    Some(PathBuf::from(path))
+}
+
+// tracks of an album share their cover, other tracks are cached by their path
+pub fn cover_key(track_path: &str, metadata: Option<&TrackMetadata>) -> String {
+    match metadata {
+        Some(metadata) if !metadata.album.is_empty() => {
+            let artist = if metadata.album_artist.is_empty() {
+                &metadata.artist
+            } else {
+                &metadata.album_artist
+            };
+            format!(
+                "album:{}/{}",
+                artist.to_lowercase(),
+                metadata.album.to_lowercase()
+            )
+        }
+        _ => format!("path:{}", track_path),
+    }
+}
+
+// the cover in the aspect ratio of the source, centered on a black square.
+// transparent parts of the source are black as well.
+pub fn thumbnail(image: &DynamicImage, size: u32) -> RgbImage {
+    let scaled = image.resize(size, size, FilterType::Lanczos3).to_rgba8();
+    let left = (size - scaled.width().min(size)) / 2;
+    let top = (size - scaled.height().min(size)) / 2;
+    let mut thumbnail = RgbImage::new(size, size);
+    for (x, y, pixel) in scaled.enumerate_pixels() {
+        let alpha = pixel[3] as u32;
+        thumbnail.put_pixel(
+            left + x,
+            top + y,
+            Rgb([0, 1, 2].map(|channel| (pixel[channel] as u32 * alpha / 255) as u8)),
+        );
+    }
+    thumbnail
+}
+
+// a decoded cover and its thumbnails, every device asks for the size it needs
+pub struct CoverArt {
+    pub key: String,
+    source: DynamicImage,
+    thumbnails: Mutex<HashMap<u32, RgbImage>>,
+}
+
+impl Debug for CoverArt {
+    fn fmt(&self, f: &mut Formatter) -> core::result::Result<(), FmtError> {
+        f.debug_struct("CoverArt").field("key", &self.key).finish()
+    }
+}
+
+impl CoverArt {
+    pub fn new(key: String, source: DynamicImage) -> CoverArt {
+        let source = if source.width() > MAX_SOURCE_SIZE || source.height() > MAX_SOURCE_SIZE {
+            source.resize(MAX_SOURCE_SIZE, MAX_SOURCE_SIZE, FilterType::Triangle)
+        } else {
+            source
+        };
+        CoverArt {
+            key,
+            source,
+            thumbnails: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn thumbnail(&self, size: u32) -> RgbImage {
+        self.thumbnails
+            .lock()
+            .unwrap()
+            .entry(size)
+            .or_insert_with(|| thumbnail(&self.source, size))
+            .clone()
+    }
+}
+
+// the least recently used covers are dropped first
+#[derive(Clone)]
+pub struct CoverCache {
+    covers: VecDeque<Arc<CoverArt>>,
+    capacity: usize,
+}
+
+impl Default for CoverCache {
+    fn default() -> CoverCache {
+        CoverCache::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl CoverCache {
+    pub fn new(capacity: usize) -> CoverCache {
+        CoverCache {
+            covers: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    // covers, that could not be loaded, are not cached and loaded again next time
+    pub fn get_or_load(
+        &mut self,
+        key: &str,
+        load: impl FnOnce() -> Option<DynamicImage>,
+    ) -> Option<Arc<CoverArt>> {
+        let cover = match self.covers.iter().position(|cover| cover.key == key) {
+            Some(index) => self.covers.remove(index)?,
+            None => Arc::new(CoverArt::new(key.to_string(), load()?)),
+        };
+        self.covers.push_back(cover.clone());
+        while self.covers.len() > self.capacity {
+            self.covers.pop_front();
+        }
+        Some(cover)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_dir::TestDir;
+    use image::{GrayImage, Luma, Rgba, RgbaImage};
+
+    #[test]
+    fn test_thumbnail_keeps_the_aspect_ratio() {
+        let wide = DynamicImage::ImageRgb8(RgbImage::from_pixel(200, 100, Rgb([200, 40, 40])));
+        let letterboxed = thumbnail(&wide, 50);
+        assert_eq!(letterboxed.dimensions(), (50, 50));
+        assert_eq!(*letterboxed.get_pixel(25, 2), Rgb([0, 0, 0]));
+        assert_eq!(*letterboxed.get_pixel(25, 25), Rgb([200, 40, 40]));
+        assert_eq!(*letterboxed.get_pixel(25, 47), Rgb([0, 0, 0]));
+
+        let gray = DynamicImage::ImageLuma8(GrayImage::from_pixel(10, 10, Luma([90])));
+        assert_eq!(*thumbnail(&gray, 20).get_pixel(3, 3), Rgb([90, 90, 90]));
+
+        // transparent pixels are drawn on black
+        let mut rgba = RgbaImage::from_pixel(10, 10, Rgba([255, 255, 255, 0]));
+        rgba.put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        let flattened = thumbnail(&DynamicImage::ImageRgba8(rgba), 10);
+        assert_eq!(*flattened.get_pixel(0, 0), Rgb([255, 255, 255]));
+        assert_eq!(*flattened.get_pixel(5, 5), Rgb([0, 0, 0]));
+    }
+
+    #[test]
+    fn test_decode_webp_cover() {
+        let rgba = RgbaImage::from_pixel(8, 4, Rgba([10, 200, 30, 255]));
+        let encoded = webp::Encoder::from_rgba(rgba.as_raw(), 8, 4).encode_lossless();
+        let decoded = decode_cover(&encoded).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (8, 4));
+        assert_eq!(decoded.to_rgb8().get_pixel(1, 1), &Rgb([10, 200, 30]));
+        assert!(decode_cover(b"no image").is_none());
+    }
+
+    #[test]
+    fn test_file_url_to_path() {
+        assert_eq!(
+            file_url_to_path("file:///home/me/My%20Album/cover%C3%A9.jpg"),
+            Some(PathBuf::from("/home/me/My Album/cover\u{e9}.jpg"))
+        );
+        assert_eq!(
+            file_url_to_path("file://localhost/C:/Music/cover.png"),
+            Some(PathBuf::from("C:/Music/cover.png"))
+        );
+        assert_eq!(
+            file_url_to_path("file:///100%"),
+            Some(PathBuf::from("/100%"))
+        );
+        assert_eq!(file_url_to_path("https://example.org/cover.jpg"), None);
+        assert!(is_image_file(Path::new("/music/Cover.WEBP")));
+        assert!(!is_image_file(Path::new("/music/track.mp3")));
+    }
+
+    #[test]
+    fn test_find_cover_file_in_a_directory_tree() {
+        let root = TestDir::new("cover_lookup");
+        let album = root.join("Artist").join("Album");
+        let disc = album.join("CD 2");
+        fs::create_dir_all(&disc).unwrap();
+        // a directory is no cover, even if its name matches
+        fs::create_dir_all(album.join("cover.jpg")).unwrap();
+        for file in [
+            "FOLDER.JPG",
+            "back.jpg",
+            "Scan.PNG",
+            "notes.txt",
+            "01 Track.mp3",
+        ] {
+            fs::write(album.join(file), b"").unwrap();
+        }
+        fs::write(disc.join("01 Track.mp3"), b"").unwrap();
+        let track = album.join("01 Track.mp3");
+
+        assert_eq!(
+            find_cover_file(&track, &cover_patterns(DEFAULT_COVER_PATTERNS)),
+            Some(album.join("FOLDER.JPG"))
+        );
+        assert_eq!(
+            find_cover_file(&track, &cover_patterns("cover.jpg, *.png, folder.jpg")),
+            Some(album.join("Scan.PNG"))
+        );
+        assert_eq!(
+            find_cover_file(&track, &cover_patterns("*.jpg")),
+            Some(album.join("FOLDER.JPG"))
+        );
+        assert_eq!(
+            find_cover_file(&track, &cover_patterns("*.txt, *.webp")),
+            None
+        );
+        assert_eq!(
+            find_cover_file(&disc.join("01 Track.mp3"), &cover_patterns("back.jpg")),
+            Some(album.join("back.jpg"))
+        );
+        assert_eq!(
+            find_cover_file(
+                &root.join("Artist").join("track.mp3"),
+                &cover_patterns("*.jpg")
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cache_drops_the_least_recently_used_cover() {
+        let image = || Some(DynamicImage::ImageRgb8(RgbImage::new(4, 4)));
+        let mut cache = CoverCache::new(2);
+        let first = cache.get_or_load("first", image).unwrap();
+        cache.get_or_load("second", image).unwrap();
+        // a cached cover is not loaded again
+        let again = cache
+            .get_or_load("first", || panic!("loaded again"))
+            .unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+        assert!(cache.get_or_load("missing", || None).is_none());
+
+        cache.get_or_load("third", image).unwrap();
+        assert!(cache.get_or_load("first", || None).is_some());
+        assert!(cache.get_or_load("second", || None).is_none());
+        assert_eq!(first.thumbnail(3).dimensions(), (3, 3));
+    }
+}
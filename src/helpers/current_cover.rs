@@ -1,10 +1,17 @@
 use audiotags::Tag;
-use image::EncodableLayout;
+use id3::TagLike;
+use image::DynamicImage;
+use std::path::{Path, PathBuf};
+
+use super::{
+    cover_cache::{
+        decode_cover, file_url_to_path, find_cover_file, is_image_file, load_cover_file,
+    },
+    lyrics::{parse_lrc, read_lrc_file, Lyrics},
+    track_metadata::TrackMetadata,
+};
 
 use winsafe::{co, msg::WndMsg, HWND};
-pub struct Cover {
-    pub data: Vec<u8>,
-}
 
 pub fn extract_current_cover_path(window: &winsafe::HWND) -> String {
     let (_thread_id, process_id) = HWND::GetWindowThreadProcessId(&window);
@@ -44,110 +51,74 @@ pub fn extract_current_cover_path(window: &winsafe::HWND) -> String {
     }
 }
 
-pub fn extract_cover_image(path: &String) -> Option<Cover> {
-    match Tag::new().read_from_path(path) {
-        Ok(tag) => match tag.album_cover() {
-            Some(cover) => {
-                let cover_as_image = image::load_from_memory(cover.data as &[u8]);
-                match cover_as_image {
-                    Ok(cover_image) => {
-                        match cover_image
-                            .resize_exact(170, 170, image::imageops::FilterType::Lanczos3)
-                            .as_mut_rgb8()
-                        {
-                            Some(resized_cover) => {
-                                return Some(Cover {
-                                    data: resized_cover.as_bytes().to_vec(),
-                                });
-                            }
-                            None => {}
-                        }
-                    }
-                    Err(_) => {}
-                }
-            }
-            None => {}
-        },
-        Err(_) => {}
-    }
-    match alternative_cover(path) {
-        Ok(cover) => return Some(cover),
-        Err(_) => return None, // cover not found, we don't care for now, why
+// the cover of a track or an image, both can be given as file url as well, e.g. the art url
+// of mpris players. the embedded cover of a track is preferred over the images in its folder,
+// which are looked up with the patterns in their order.
+pub fn load_cover(location: &str, patterns: &[String]) -> Option<DynamicImage> {
+    let path = file_url_to_path(location).unwrap_or_else(|| PathBuf::from(location));
+    if is_image_file(&path) {
+        return load_cover_file(&path);
     }
+    embedded_cover(path.to_str()?)
+        .or_else(|| find_cover_file(&path, patterns).and_then(|cover| load_cover_file(&cover)))
 }
 
-pub fn extract_cover_path(original_path: &String) -> Option<String> {
-    let patterns = [
-        "Folder.jpg",
-        "AlbumArtSmall.jpg",
-        "AlbumArt.jpg",
-        "Album.jpg",
-        ".folder.png",
-        "cover.jpg",
-        "thumb.jpg",
-        "*.jpg",
-    ];
+fn embedded_cover(path: &str) -> Option<DynamicImage> {
+    let tag = Tag::new().read_from_path(path).ok()?;
+    decode_cover(tag.album_cover()?.data)
+}
 
-    for pattern in &patterns {
-        // Glob-Muster erstellen
-        let glob_pattern = format!("\\{}", pattern);
-        match std::path::Path::new(original_path).parent() {
-            Some(p) => match p.to_str() {
-                Some(p) => {
-                    let complete_path = format!("{}{}", p, glob_pattern);
-                    if let Ok(entries) = glob::glob(&complete_path) {
-                        for entry in entries {
-                            if let Ok(path) = entry {
-                                if path.is_file() {
-                                    match path.into_os_string().into_string() {
-                                        Ok(p) => return Some(p),
-                                        Err(_) => {}
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-                None => {}
-            },
-            None => {}
-        }
+// none, if the file has no tags or no title
+pub fn extract_track_metadata(path: &str) -> Option<TrackMetadata> {
+    let tag = Tag::new().read_from_path(path).ok()?;
+    let text = |value: Option<&str>| value.unwrap_or_default().trim().to_string();
+    let metadata = TrackMetadata {
+        artist: text(tag.artist()),
+        title: text(tag.title()),
+        album: text(tag.album_title()),
+        album_artist: text(tag.album_artist()),
+        track_number: tag.track_number(),
+        year: tag.year(),
+        genre: text(tag.genre()),
+        duration: tag
+            .duration()
+            .filter(|seconds| seconds.is_finite() && *seconds > 0.0)
+            .map(std::time::Duration::from_secs_f64),
+    };
+    if metadata.title.is_empty() {
+        return None;
     }
+    Some(metadata)
+}
 
-    None
+// the lrc file next to the track is preferred, since embedded lyrics are rarely synced
+pub fn extract_lyrics(path: &str) -> Option<Lyrics> {
+    let content = read_lrc_file(path).or_else(|| embedded_lyrics(path))?;
+    let lyrics = parse_lrc(&content);
+    if lyrics.lines.is_empty() {
+        return None;
+    }
+    Some(lyrics)
 }
 
-pub fn alternative_cover(original_path: &String) -> Result<Cover, std::io::Error> {
-    // since NTFS does not differentiate between case sensitivity,
-    // folder.jpg will be as appropriate as Folder.jpg.
-    // TODO: replace me with a Dir.glob based approach.
-    let folder_image = extract_cover_path(original_path);
-    match folder_image {
-        Some(path) => {
-            let image = image::open(path.clone());
-            match image {
-                Ok(img) => {
-                    return Result::Ok(Cover {
-                        data: img
-                            .resize_exact(170, 170, image::imageops::FilterType::Lanczos3)
-                            .as_mut_rgb8()
-                            .unwrap()
-                            .to_vec(),
-                    });
-                }
-                Err(e) => {
-                    return Result::Err(std::io::Error::new(
-                        std::io::ErrorKind::NotFound,
-                        format!("Could not open image! Reason: {:?}", e),
-                    ));
-                }
-            }
+// the unsynchronised lyrics frame of mp3 files or the lyrics comment of flac files
+fn embedded_lyrics(path: &str) -> Option<String> {
+    let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+    match extension.as_str() {
+        "mp3" => id3::Tag::read_from_path(path)
+            .ok()?
+            .lyrics()
+            .map(|lyrics| lyrics.text.clone())
+            .find(|text| !text.trim().is_empty()),
+        "flac" => {
+            let tag = metaflac::Tag::read_from_path(path).ok()?;
+            let text = tag
+                .get_vorbis("LYRICS")
+                .or_else(|| tag.get_vorbis("UNSYNCEDLYRICS"))?
+                .collect::<Vec<&str>>()
+                .join("\n");
+            Some(text)
         }
-        None => {}
+        _ => None,
     }
-
-    return Result::Err(std::io::Error::new(
-        std::io::ErrorKind::NotFound,
-        "Cover not found",
-    ));
 }
@@ -47,8 +47,8 @@ pub fn read_bme_sensor(port: &mut Option<std::boxed::Box<dyn serialport::SerialP
     if port.as_deref_mut().is_some() {
         match port.as_deref_mut().unwrap().read_exact(&mut data) {
             Ok(_) => {
-                // everything alright,
-                return std::str::from_utf8(&data).unwrap().to_string();
+                // everything alright, garbage is rejected when the reading is parsed
+                return String::from_utf8_lossy(&data).to_string();
             }
             Err(_) => {
                 return String::new();
@@ -58,6 +58,13 @@ pub fn read_bme_sensor(port: &mut Option<std::boxed::Box<dyn serialport::SerialP
     String::new()
 }
 
+// the reply to a poll of the buttons, a single code
+pub fn read_button(port: &mut Option<std::boxed::Box<dyn serialport::SerialPort>>) -> Option<u8> {
+    let mut data: [u8; 1] = [0; 1];
+    port.as_deref_mut()?.read_exact(&mut data).ok()?;
+    Some(data[0])
+}
+
 pub fn send_command(
     port: &mut Option<std::boxed::Box<dyn serialport::SerialPort>>,
     command: &[u8],
@@ -0,0 +1,51 @@
+use ab_glyph::{FontArc, PxScale};
+use image::{ImageBuffer, Rgb};
+use imageproc::drawing::{draw_text_mut, text_size};
+
+pub fn draw_text(
+    image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+    font: &FontArc,
+    text: &str,
+    x: i32,
+    y: i32,
+    scale: PxScale,
+    color: Rgb<u8>,
+) {
+    draw_text_mut(image, color, x, y, scale, font, text);
+}
+
+// the text ends at the right position
+pub fn draw_right_aligned(
+    image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+    font: &FontArc,
+    text: &str,
+    right: i32,
+    y: i32,
+    scale: PxScale,
+    color: Rgb<u8>,
+) {
+    let (width, _) = text_size(scale, font, text);
+    draw_text_mut(image, color, right - width as i32, y, scale, font, text);
+}
+
+// the text is centered around the center position
+pub fn draw_centered(
+    image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+    font: &FontArc,
+    text: &str,
+    center: i32,
+    y: i32,
+    scale: PxScale,
+    color: Rgb<u8>,
+) {
+    let (width, _) = text_size(scale, font, text);
+    draw_text_mut(
+        image,
+        color,
+        center - width as i32 / 2,
+        y,
+        scale,
+        font,
+        text,
+    );
+}
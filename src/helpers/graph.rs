@@ -0,0 +1,182 @@
+use image::{ImageBuffer, Rgb};
+use imageproc::{
+    drawing::{
+        draw_filled_circle_mut, draw_hollow_circle_mut, draw_hollow_rect_mut, draw_line_segment_mut,
+    },
+    rect::Rect,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GraphStyle {
+    Line,
+    Area,
+    Sparkline,
+}
+
+impl GraphStyle {
+    pub fn from_key(key: &str) -> GraphStyle {
+        match key.trim().to_lowercase().as_str() {
+            "line" => GraphStyle::Line,
+            "sparkline" => GraphStyle::Sparkline,
+            _ => GraphStyle::Area,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Graph {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub style: GraphStyle,
+    pub color: Rgb<u8>,
+    // fixed value range (e.g. 0 - 100 for percentages), otherwise the values are auto scaled
+    pub range: Option<(f64, f64)>,
+    pub min_max_markers: bool,
+}
+
+pub fn value_range(values: &[f64]) -> (f64, f64) {
+    let min = values.iter().copied().reduce(f64::min).unwrap_or(0.0);
+    let max = values.iter().copied().reduce(f64::max).unwrap_or(0.0);
+    if (max - min).abs() < f64::EPSILON {
+        return (min - 1.0, max + 1.0);
+    }
+    (min, max)
+}
+
+// maps the values onto points relative to the top left corner of the graph.
+// the newest value is always placed at the right border, so a history that is not
+// completely filled yet grows from the right to the left.
+pub fn graph_points(
+    values: &[f64],
+    capacity: usize,
+    width: u32,
+    height: u32,
+    range: (f64, f64),
+) -> Vec<(f32, f32)> {
+    let capacity = capacity.max(values.len()).max(2);
+    let step = (width.saturating_sub(1)) as f32 / (capacity - 1) as f32;
+    let offset = capacity - values.len();
+    let span = (range.1 - range.0).max(f64::EPSILON);
+    let bottom = height.saturating_sub(1) as f32;
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let ratio = ((value - range.0) / span).clamp(0.0, 1.0) as f32;
+            ((offset + index) as f32 * step, bottom - ratio * bottom)
+        })
+        .collect()
+}
+
+fn dimmed(color: Rgb<u8>) -> Rgb<u8> {
+    Rgb([color[0] / 3, color[1] / 3, color[2] / 3])
+}
+
+pub fn draw_graph(
+    image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+    graph: &Graph,
+    values: &[f64],
+    capacity: usize,
+) {
+    if graph.style != GraphStyle::Sparkline {
+        draw_hollow_rect_mut(
+            image,
+            Rect::at(graph.x, graph.y).of_size(graph.width.max(1), graph.height.max(1)),
+            Rgb([90, 90, 90]),
+        );
+    }
+    if values.is_empty() {
+        return;
+    }
+
+    let range = graph.range.unwrap_or_else(|| value_range(values));
+    let points: Vec<(f32, f32)> = graph_points(values, capacity, graph.width, graph.height, range)
+        .into_iter()
+        .map(|(x, y)| (x + graph.x as f32, y + graph.y as f32))
+        .collect();
+    let bottom = (graph.y + graph.height as i32 - 1) as f32;
+
+    if graph.style == GraphStyle::Area {
+        for window in points.windows(2) {
+            let (start, end) = (window[0], window[1]);
+            let mut x = start.0.ceil();
+            while x <= end.0 {
+                let ratio = if end.0 > start.0 {
+                    (x - start.0) / (end.0 - start.0)
+                } else {
+                    0.0
+                };
+                let y = start.1 + (end.1 - start.1) * ratio;
+                draw_line_segment_mut(image, (x, y), (x, bottom), dimmed(graph.color));
+                x += 1.0;
+            }
+        }
+    }
+
+    if points.len() == 1 {
+        draw_line_segment_mut(image, points[0], points[0], graph.color);
+    }
+    for window in points.windows(2) {
+        draw_line_segment_mut(image, window[0], window[1], graph.color);
+    }
+
+    if graph.min_max_markers {
+        let radius = if graph.height >= 40 { 2 } else { 1 };
+        let (mut min_index, mut max_index) = (0, 0);
+        for (index, value) in values.iter().enumerate() {
+            if *value < values[min_index] {
+                min_index = index;
+            }
+            if *value > values[max_index] {
+                max_index = index;
+            }
+        }
+        let max_point = points[max_index];
+        let min_point = points[min_index];
+        draw_filled_circle_mut(
+            image,
+            (max_point.0 as i32, max_point.1 as i32),
+            radius,
+            Rgb([255, 255, 255]),
+        );
+        draw_hollow_circle_mut(
+            image,
+            (min_point.0 as i32, min_point.1 as i32),
+            radius,
+            Rgb([255, 255, 255]),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph_points_fill_from_the_right() {
+        let points = graph_points(&[0.0, 100.0], 5, 9, 11, (0.0, 100.0));
+        assert_eq!(points, vec![(6.0, 10.0), (8.0, 0.0)]);
+    }
+
+    #[test]
+    fn test_graph_points_clamp_values_outside_range() {
+        let points = graph_points(&[-10.0, 50.0, 200.0], 3, 3, 3, (0.0, 100.0));
+        assert_eq!(points, vec![(0.0, 2.0), (1.0, 1.0), (2.0, 0.0)]);
+    }
+
+    #[test]
+    fn test_value_range_of_constant_values() {
+        assert_eq!(value_range(&[5.0, 5.0]), (4.0, 6.0));
+        assert_eq!(value_range(&[1.0, 3.0, 2.0]), (1.0, 3.0));
+    }
+
+    #[test]
+    fn test_graph_style_from_key() {
+        assert_eq!(GraphStyle::from_key("Line"), GraphStyle::Line);
+        assert_eq!(GraphStyle::from_key("sparkline"), GraphStyle::Sparkline);
+        assert_eq!(GraphStyle::from_key("unknown"), GraphStyle::Area);
+    }
+}
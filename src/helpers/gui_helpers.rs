@@ -3,7 +3,9 @@ use iced::{Length, Theme};
 use indexmap::IndexMap;
 
 use crate::{
-    config_manager::ConfigManager, rgb_bytes_to_rgba_image, swap_rgb, Message, DEVICES, ICONS,
+    config_manager::ConfigManager,
+    media::{MediaCommand, SEEK_STEP},
+    rgb_bytes_to_rgba_image, swap_rgb, Message, DEVICES, ICONS,
 };
 
 use super::text_manipulation::{determine_field_value, humanize_string};
@@ -34,6 +36,43 @@ pub fn device_connected_icon<'a>(
     .into()
 }
 
+fn media_button<'a>(
+    icon: &str,
+    command: MediaCommand,
+) -> iced::Element<'a, Message, Theme, iced::Renderer> {
+    iced::widget::button(
+        iced::widget::text(icon.to_string())
+            .font(ICONS)
+            .center()
+            .style(|_theme| crate::style::text()),
+    )
+    .on_press(Message::MediaControl(command))
+    .width(Length::Fixed(36f32))
+    .into()
+}
+
+// transport controls of the active player, two rows to fit the width of the left column
+pub fn media_controls<'a>() -> Vec<iced::Element<'a, Message, Theme, iced::Renderer>> {
+    vec![
+        iced::widget::Row::with_children(vec![
+            media_button("\u{f048}", MediaCommand::Previous),
+            media_button("\u{f04a}", MediaCommand::Seek(-SEEK_STEP)),
+            media_button("\u{f04b}", MediaCommand::PlayPause),
+            media_button("\u{f04e}", MediaCommand::Seek(SEEK_STEP)),
+            media_button("\u{f051}", MediaCommand::Next),
+        ])
+        .spacing(5)
+        .into(),
+        iced::widget::Row::with_children(vec![
+            media_button("\u{f04d}", MediaCommand::Stop),
+            media_button("\u{f027}", MediaCommand::VolumeDown),
+            media_button("\u{f028}", MediaCommand::VolumeUp),
+        ])
+        .spacing(5)
+        .into(),
+    ]
+}
+
 pub fn device_status<'a>(device: &str) -> Vec<iced::Element<'a, Message, Theme, iced::Renderer>> {
     vec![
         iced::widget::Text::new(device.to_uppercase())
@@ -1,7 +1,15 @@
 use rdev::{grab, Event, EventType, Key};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 
-use crate::{LAST_KEY, LAST_KEY_VALUE};
+use crate::{
+    media::{send_command, MediaCommand, SEEK_STEP},
+    LAST_KEY, LAST_KEY_VALUE,
+};
+
+// the grab only reports single keys, so the modifiers of the hotkeys are tracked here
+static CONTROL_PRESSED: AtomicBool = AtomicBool::new(false);
+static ALT_PRESSED: AtomicBool = AtomicBool::new(false);
 
 pub fn start_global_key_grabber(callback: rdev::GrabCallback) {
     thread::spawn({
@@ -16,7 +24,49 @@ pub fn start_global_key_grabber(callback: rdev::GrabCallback) {
     });
 }
 
+// ctrl + alt + space, arrows and page up/down control the player
+fn hotkey_command(key: Key) -> Option<MediaCommand> {
+    match key {
+        Key::Space => Some(MediaCommand::PlayPause),
+        Key::RightArrow => Some(MediaCommand::Next),
+        Key::LeftArrow => Some(MediaCommand::Previous),
+        Key::UpArrow => Some(MediaCommand::VolumeUp),
+        Key::DownArrow => Some(MediaCommand::VolumeDown),
+        Key::PageUp => Some(MediaCommand::Seek(SEEK_STEP)),
+        Key::PageDown => Some(MediaCommand::Seek(-SEEK_STEP)),
+        _ => None,
+    }
+}
+
 pub fn callback(event: Event) -> Option<Event> {
+    match event.event_type {
+        EventType::KeyPress(Key::ControlLeft | Key::ControlRight) => {
+            CONTROL_PRESSED.store(true, Ordering::Release);
+            return Some(event);
+        }
+        EventType::KeyRelease(Key::ControlLeft | Key::ControlRight) => {
+            CONTROL_PRESSED.store(false, Ordering::Release);
+            return Some(event);
+        }
+        EventType::KeyPress(Key::Alt | Key::AltGr) => {
+            ALT_PRESSED.store(true, Ordering::Release);
+            return Some(event);
+        }
+        EventType::KeyRelease(Key::Alt | Key::AltGr) => {
+            ALT_PRESSED.store(false, Ordering::Release);
+            return Some(event);
+        }
+        EventType::KeyPress(key)
+            if CONTROL_PRESSED.load(Ordering::Acquire) && ALT_PRESSED.load(Ordering::Acquire) =>
+        {
+            // hotkeys are swallowed, the focused window should not see them
+            if let Some(command) = hotkey_command(key) {
+                send_command(command);
+                return None;
+            }
+        }
+        _ => {}
+    }
     match event.event_type {
         EventType::KeyPress(Key::Unknown(178)) => {
             set_last_key(178);
@@ -0,0 +1,144 @@
+use ab_glyph::PxScale;
+
+// displays lower than this get the compact layout: no header, fewer rows and gray levels only
+const COMPACT_MAX_HEIGHT: u32 = 100;
+// the resolutions the compact and the regular layouts are designed for
+const COMPACT_REFERENCE: (u32, u32) = (256, 64);
+const REGULAR_REFERENCE: (u32, u32) = (320, 170);
+
+// a layout is designed once for a reference resolution (e.g. 256x64) and
+// translated to the actual resolution of the device it is drawn for.
+#[derive(Clone, Copy, Debug)]
+pub struct Layout {
+    width: u32,
+    height: u32,
+    reference_width: u32,
+    reference_height: u32,
+}
+
+impl Layout {
+    pub fn new(width: u32, height: u32, reference_width: u32, reference_height: u32) -> Layout {
+        Layout {
+            width,
+            height,
+            reference_width: reference_width.max(1),
+            reference_height: reference_height.max(1),
+        }
+    }
+
+    // the layout of a display, designed for the reference resolution of its size class
+    pub fn for_display(width: u32, height: u32) -> Layout {
+        let (reference_width, reference_height) = if height < COMPACT_MAX_HEIGHT {
+            COMPACT_REFERENCE
+        } else {
+            REGULAR_REFERENCE
+        };
+        Layout::new(width, height, reference_width, reference_height)
+    }
+
+    pub fn compact(&self) -> bool {
+        self.height < COMPACT_MAX_HEIGHT
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn ratio_x(&self) -> f32 {
+        self.width as f32 / self.reference_width as f32
+    }
+
+    fn ratio_y(&self) -> f32 {
+        self.height as f32 / self.reference_height as f32
+    }
+
+    // text has to keep its aspect ratio, so the smaller ratio wins
+    fn ratio(&self) -> f32 {
+        self.ratio_x().min(self.ratio_y())
+    }
+
+    pub fn x(&self, x: i32) -> i32 {
+        (x as f32 * self.ratio_x()).round() as i32
+    }
+
+    // a width measured on the device, e.g. of a text, in the coordinates of the reference
+    pub fn reference_width(&self, width: u32) -> i32 {
+        (width as f32 / self.ratio_x()).ceil() as i32
+    }
+
+    pub fn y(&self, y: i32) -> i32 {
+        (y as f32 * self.ratio_y()).round() as i32
+    }
+
+    pub fn w(&self, w: u32) -> u32 {
+        ((w as f32 * self.ratio_x()).round() as u32).max(1)
+    }
+
+    pub fn h(&self, h: u32) -> u32 {
+        ((h as f32 * self.ratio_y()).round() as u32).max(1)
+    }
+
+    // lengths of round shapes, e.g. a radius, keep the aspect ratio like text
+    pub fn length(&self, length: i32) -> i32 {
+        (length as f32 * self.ratio()).round() as i32
+    }
+
+    pub fn scale(&self, size: f32) -> PxScale {
+        let size = size * self.ratio();
+        PxScale { x: size, y: size }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_layout() {
+        let layout = Layout::new(256, 64, 256, 64);
+        assert_eq!(layout.x(222), 222);
+        assert_eq!(layout.y(48), 48);
+        assert_eq!(layout.w(256), 256);
+        assert_eq!(layout.scale(16.0).x, 16.0);
+    }
+
+    #[test]
+    fn test_scaled_layout() {
+        let layout = Layout::new(320, 170, 256, 64);
+        assert_eq!(layout.x(256), 320);
+        assert_eq!(layout.y(64), 170);
+        assert_eq!(layout.w(256), 320);
+        // scale is limited by the horizontal ratio
+        assert_eq!(layout.scale(16.0).y, 20.0);
+    }
+
+    #[test]
+    fn test_layout_for_display() {
+        let compact = Layout::for_display(256, 64);
+        assert!(compact.compact());
+        assert_eq!(compact.y(48), 48);
+
+        let regular = Layout::for_display(320, 170);
+        assert!(!regular.compact());
+        assert_eq!(regular.y(136), 136);
+
+        // a larger color display scales the regular layout
+        let large = Layout::for_display(640, 340);
+        assert!(!large.compact());
+        assert_eq!(large.y(136), 272);
+        assert_eq!(large.scale(16.0).x, 32.0);
+        assert_eq!(large.length(30), 60);
+        assert_eq!(large.reference_width(101), 51);
+    }
+
+    #[test]
+    fn test_sizes_never_collapse() {
+        let layout = Layout::new(16, 8, 256, 64);
+        assert_eq!(layout.w(1), 1);
+        assert_eq!(layout.h(1), 1);
+    }
+}
@@ -0,0 +1,215 @@
+use std::{fs, path::Path, time::Duration};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LyricsLine {
+    pub time: Duration,
+    pub text: String,
+}
+
+// lyrics without any timestamp are not synced, no line is highlighted then
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Lyrics {
+    pub lines: Vec<LyricsLine>,
+    pub synced: bool,
+}
+
+impl Lyrics {
+    // the index of the line, that is sung at the position
+    pub fn current_line(&self, position: Duration) -> Option<usize> {
+        if !self.synced {
+            return None;
+        }
+        self.lines
+            .partition_point(|line| line.time <= position)
+            .checked_sub(1)
+    }
+}
+
+// "01:02.34", "01:02:34", "01:02.345" or "01:02"
+fn parse_timestamp(tag: &str) -> Option<Duration> {
+    let (minutes, rest) = tag.split_once(':')?;
+    let (seconds, fraction) = match rest.split_once(['.', ':']) {
+        Some((seconds, fraction)) => (seconds, fraction),
+        None => (rest, ""),
+    };
+    let minutes: u64 = minutes.trim().parse().ok()?;
+    let seconds: u64 = seconds.parse().ok()?;
+    if seconds >= 60 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    // the fraction is given in hundredths in most files, but in milliseconds in some
+    let millis = format!("{:0<3}", fraction.get(..3).unwrap_or(fraction))
+        .parse::<u64>()
+        .ok()?;
+    Some(Duration::from_millis(
+        (minutes * 60 + seconds) * 1000 + millis,
+    ))
+}
+
+// removes the word timestamps of enhanced lrc files, e.g. "<00:12.50>"
+fn strip_word_timestamps(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        match rest[start..].find('>') {
+            Some(end) if parse_timestamp(&rest[start + 1..start + end]).is_some() => {
+                result.push_str(&rest[..start]);
+                rest = &rest[start + end + 1..];
+            }
+            _ => {
+                result.push_str(&rest[..=start]);
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result.trim().to_string()
+}
+
+// parses lrc files and plain lyrics. a line can have several timestamps, e.g. for a chorus,
+// and the "offset" tag moves all lines by the given milliseconds.
+pub fn parse_lrc(content: &str) -> Lyrics {
+    let mut offset: i64 = 0;
+    let mut timed = Vec::new();
+    let mut plain = Vec::new();
+
+    for line in content.trim_start_matches('\u{feff}').lines() {
+        let mut rest = line.trim();
+        let mut times = Vec::new();
+        let mut tagged = false;
+        while rest.starts_with('[') {
+            let Some(end) = rest.find(']') else {
+                break;
+            };
+            let tag = &rest[1..end];
+            match parse_timestamp(tag) {
+                Some(time) => times.push(time),
+                None => {
+                    if let Some((key, value)) = tag.split_once(':') {
+                        if key.trim().eq_ignore_ascii_case("offset") {
+                            offset = value.trim().parse().unwrap_or(offset);
+                        }
+                    }
+                }
+            }
+            tagged = true;
+            rest = rest[end + 1..].trim_start();
+        }
+
+        let text = strip_word_timestamps(rest);
+        if !times.is_empty() {
+            timed.extend(times.into_iter().map(|time| (time, text.clone())));
+        } else if !tagged && !text.is_empty() {
+            plain.push(text);
+        }
+    }
+
+    if timed.is_empty() {
+        return Lyrics {
+            lines: plain
+                .into_iter()
+                .map(|text| LyricsLine {
+                    time: Duration::ZERO,
+                    text,
+                })
+                .collect(),
+            synced: false,
+        };
+    }
+
+    // a positive offset shows the lines earlier
+    let mut lines: Vec<LyricsLine> = timed
+        .into_iter()
+        .map(|(time, text)| LyricsLine {
+            time: Duration::from_millis((time.as_millis() as i64 - offset).max(0) as u64),
+            text,
+        })
+        .collect();
+    lines.sort_by_key(|line| line.time);
+    Lyrics {
+        lines,
+        synced: true,
+    }
+}
+
+// the lrc file next to the track, e.g. "Artist - Title.lrc" for "Artist - Title.mp3".
+// the extension is compared case insensitive, so "Artist - Title.LRC" is found as well.
+pub fn read_lrc_file(track_path: &str) -> Option<String> {
+    let path = Path::new(track_path);
+    let name = format!("{}.lrc", path.file_stem()?.to_str()?).to_lowercase();
+    fs::read_dir(path.parent()?)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|entry| entry.is_file())
+        .find(|entry| {
+            entry
+                .file_name()
+                .and_then(|file_name| file_name.to_str())
+                .is_some_and(|file_name| file_name.to_lowercase() == name)
+        })
+        .and_then(|entry| fs::read(entry).ok())
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_dir::TestDir;
+
+    fn millis(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_parse_lrc() {
+        let lyrics = parse_lrc(
+            "\u{feff}[ar:Someone]\n[ti:Something]\n[offset:+500]\n\
+             [00:12.00]first line\n[00:17.20][01:02.5]chorus\n\
+             [00:15.123]<00:15.123>word <00:15.500>by <00:16.000>word\n[00:20.00]\n",
+        );
+        assert!(lyrics.synced);
+        let lines: Vec<(Duration, &str)> = lyrics
+            .lines
+            .iter()
+            .map(|line| (line.time, line.text.as_str()))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                (millis(11_500), "first line"),
+                (millis(14_623), "word by word"),
+                (millis(16_700), "chorus"),
+                (millis(19_500), ""),
+                (millis(62_000), "chorus"),
+            ]
+        );
+
+        let plain = parse_lrc("first line\n\n<not a timestamp> second line\n");
+        assert!(!plain.synced);
+        assert_eq!(plain.lines[1].text, "<not a timestamp> second line");
+        assert_eq!(plain.current_line(millis(5_000)), None);
+    }
+
+    #[test]
+    fn test_current_line() {
+        let lyrics = parse_lrc("[00:01.00]one\n[00:03.00]two\n[00:03.00]three\n[00:05.00]four");
+        assert_eq!(lyrics.current_line(millis(0)), None);
+        assert_eq!(lyrics.current_line(millis(1_000)), Some(0));
+        assert_eq!(lyrics.current_line(millis(2_999)), Some(0));
+        assert_eq!(lyrics.current_line(millis(3_000)), Some(2));
+        assert_eq!(lyrics.current_line(millis(600_000)), Some(3));
+    }
+
+    #[test]
+    fn test_read_lrc_file_next_to_the_track() {
+        let dir = TestDir::new("lyrics");
+        let track = dir.join("Artist - Title [Live].mp3");
+        let track = track.to_str().unwrap();
+        assert_eq!(read_lrc_file(track), None);
+
+        fs::write(dir.join("Artist - Title [Live].LRC"), "[00:01.00]one").unwrap();
+        fs::write(dir.join("Artist - Title.lrc"), "[00:01.00]two").unwrap();
+        assert_eq!(read_lrc_file(track).as_deref(), Some("[00:01.00]one"));
+    }
+}
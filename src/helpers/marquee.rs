@@ -0,0 +1,180 @@
+use ab_glyph::{FontArc, PxScale};
+use image::{GenericImage, GenericImageView, ImageBuffer, Rgb};
+use imageproc::drawing::{draw_text_mut, text_size};
+use std::time::{Duration, Instant};
+
+// pixels per second
+pub const DEFAULT_SCROLL_SPEED: u32 = 30;
+pub const DEFAULT_SCROLL_PAUSE: Duration = Duration::from_secs(2);
+
+// where the text is drawn, longer texts are clipped to the width
+#[derive(Clone, Copy, Debug)]
+pub struct MarqueeArea {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    // texts, that fit into the width, are centered instead of left aligned
+    pub centered: bool,
+}
+
+// the offset of the text, it waits for the pause at the start of every round
+pub fn scroll_offset(elapsed: Duration, distance: f32, speed: f32, pause: Duration) -> f32 {
+    if speed <= 0.0 || distance <= 0.0 {
+        return 0.0;
+    }
+    let round = pause.as_secs_f32() + distance / speed;
+    let time = elapsed.as_secs_f32() % round;
+    (time - pause.as_secs_f32()).max(0.0) * speed
+}
+
+// a text, that scrolls pixel by pixel, if it does not fit into its area.
+// the offset depends on the time only, so it can be drawn for several devices
+// and with any frame rate.
+pub struct Marquee {
+    text: String,
+    started: Instant,
+    speed: u32,
+    pause: Duration,
+}
+
+impl Default for Marquee {
+    fn default() -> Marquee {
+        Marquee::new(DEFAULT_SCROLL_SPEED, DEFAULT_SCROLL_PAUSE)
+    }
+}
+
+impl Marquee {
+    pub fn new(speed: u32, pause: Duration) -> Marquee {
+        Marquee {
+            text: String::new(),
+            started: Instant::now(),
+            speed,
+            pause,
+        }
+    }
+
+    pub fn set_speed(&mut self, speed: u32, pause: Duration) {
+        self.speed = speed;
+        self.pause = pause;
+    }
+
+    // a new text starts at the beginning again
+    pub fn set_text(&mut self, text: &str) {
+        if self.text != text {
+            self.text = text.to_string();
+            self.restart();
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.started = Instant::now();
+    }
+
+    // returns true, if the text does not fit and is scrolled
+    pub fn draw(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        font: &FontArc,
+        scale: PxScale,
+        color: Rgb<u8>,
+        area: MarqueeArea,
+    ) -> bool {
+        let text_width = text_size(scale, font, &self.text).0 as i32;
+        if text_width <= area.width {
+            let x = if area.centered {
+                area.x + (area.width - text_width) / 2
+            } else {
+                area.x
+            };
+            draw_text_mut(image, color, x, area.y, scale, font, &self.text);
+            return false;
+        }
+
+        // the text is drawn into a copy of the area, so nothing outside of it is touched
+        let left = area.x.clamp(0, image.width() as i32) as u32;
+        let top = area.y.clamp(0, image.height() as i32) as u32;
+        let width = (area.width.max(0) as u32).min(image.width() - left);
+        let height = ((scale.y * 1.3).ceil() as u32).min(image.height() - top);
+        if width == 0 || height == 0 {
+            return true;
+        }
+        let mut clip = image.view(left, top, width, height).to_image();
+
+        let gap = (scale.x * 2.0) as i32;
+        let offset = scroll_offset(
+            self.started.elapsed(),
+            (text_width + gap) as f32,
+            self.speed as f32,
+            self.pause,
+        ) as i32;
+        let y = area.y - top as i32;
+        draw_text_mut(&mut clip, color, -offset, y, scale, font, &self.text);
+        draw_text_mut(
+            &mut clip,
+            color,
+            text_width + gap - offset,
+            y,
+            scale,
+            font,
+            &self.text,
+        );
+        image.copy_from(&clip, left, top).unwrap_or_default();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn font() -> FontArc {
+        FontArc::try_from_slice(include_bytes!("../fonts/Liberation.ttf") as &[u8]).unwrap()
+    }
+
+    #[test]
+    fn test_scroll_offset_pauses_at_the_start() {
+        let pause = Duration::from_secs(2);
+        let offset = |millis| scroll_offset(Duration::from_millis(millis), 100.0, 50.0, pause);
+        assert_eq!(offset(0), 0.0);
+        assert_eq!(offset(1900), 0.0);
+        assert_eq!(offset(2500), 25.0);
+        assert!((offset(3900) - 95.0).abs() < 0.01);
+        // the next round starts with a pause again
+        assert_eq!(offset(4000), 0.0);
+        assert_eq!(offset(4500), 0.0);
+        assert_eq!(
+            scroll_offset(Duration::from_secs(9), 100.0, 0.0, pause),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_long_text_is_clipped_to_its_area() {
+        let font = font();
+        let scale = PxScale { x: 16.0, y: 16.0 };
+        let white = Rgb([255u8, 255u8, 255u8]);
+        let mut marquee = Marquee::default();
+
+        marquee.set_text("short");
+        let mut image = ImageBuffer::new(200, 40);
+        let area = MarqueeArea {
+            x: 20,
+            y: 10,
+            width: 120,
+            centered: false,
+        };
+        assert!(!marquee.draw(&mut image, &font, scale, white, area));
+
+        marquee.set_text("a very long title, that does not fit into the area at all");
+        let mut image = ImageBuffer::new(200, 40);
+        assert!(marquee.draw(&mut image, &font, scale, white, area));
+        let drawn = |from: u32, to: u32| {
+            image
+                .enumerate_pixels()
+                .any(|(x, _, pixel)| x >= from && x < to && pixel[0] > 0)
+        };
+        assert!(drawn(20, 140));
+        assert!(!drawn(0, 20));
+        assert!(!drawn(140, 200));
+    }
+}
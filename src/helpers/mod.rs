@@ -1,9 +1,24 @@
+pub mod accent_color;
+pub mod audio_capture;
+pub mod clock;
 pub mod convert;
 pub mod convert_image;
+pub mod cover_cache;
 pub mod current_cover;
 pub mod display_serial_com;
+pub mod drawing;
+pub mod graph;
 pub mod gui_helpers;
 pub mod keyboard;
+pub mod layout;
+pub mod lyrics;
+pub mod marquee;
 pub mod master_volume;
+pub mod paging;
 pub mod power;
+pub mod spectrum;
+#[cfg(test)]
+pub mod test_dir;
 pub mod text_manipulation;
+pub mod track_metadata;
+pub mod warning_frame;
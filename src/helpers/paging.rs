@@ -0,0 +1,16 @@
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use crate::config_manager::{integer_value, ConfigManager};
+
+// how long a page of a paged screen is shown, from its "page_duration" setting in seconds
+pub fn page_duration(
+    config_manager: &Arc<RwLock<ConfigManager>>,
+    key: &str,
+    default: u32,
+) -> Duration {
+    let seconds = integer_value(config_manager, key, "page_duration", default);
+    Duration::from_secs(seconds.max(1) as u64)
+}
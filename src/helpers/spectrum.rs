@@ -0,0 +1,211 @@
+use std::f32::consts::PI;
+
+// about 46 ms at 44.1 kHz, enough resolution for the lowest bands
+pub const FFT_SIZE: usize = 2048;
+pub const DEFAULT_BANDS: u32 = 32;
+const MIN_FREQUENCY: f32 = 40.0;
+const MAX_FREQUENCY: f32 = 16000.0;
+// levels below this are drawn as silence
+const FLOOR_DB: f32 = -60.0;
+
+// in place radix 2 fft, the length has to be a power of two
+pub fn fft(real: &mut [f32], imaginary: &mut [f32]) {
+    let n = real.len();
+    debug_assert!(n.is_power_of_two() && imaginary.len() == n);
+
+    // bit reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            real.swap(i, j);
+            imaginary.swap(i, j);
+        }
+    }
+
+    let mut length = 2;
+    while length <= n {
+        let angle = -2.0 * PI / length as f32;
+        for start in (0..n).step_by(length) {
+            for k in 0..length / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let a = start + k;
+                let b = a + length / 2;
+                let t_real = real[b] * cos - imaginary[b] * sin;
+                let t_imaginary = real[b] * sin + imaginary[b] * cos;
+                real[b] = real[a] - t_real;
+                imaginary[b] = imaginary[a] - t_imaginary;
+                real[a] += t_real;
+                imaginary[a] += t_imaginary;
+            }
+        }
+        length <<= 1;
+    }
+}
+
+// 0 for the floor, 1 for full scale
+fn normalize_db(amplitude: f32) -> f32 {
+    let db = 20.0 * amplitude.max(1e-9).log10();
+    ((db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0)
+}
+
+// the levels of logarithmically spaced bands between 40 Hz and 16 kHz, from 0 to 1.
+// the samples are mono, a hann window is applied before the transformation.
+pub fn spectrum_bands(samples: &[f32], sample_rate: u32, bands: usize) -> Vec<f32> {
+    if bands == 0 || samples.len() < 4 || sample_rate == 0 {
+        return vec![0.0; bands];
+    }
+    let n = samples.len().next_power_of_two().min(FFT_SIZE);
+    let samples = &samples[samples.len().saturating_sub(n)..];
+    let mut real = vec![0.0f32; n];
+    let mut imaginary = vec![0.0f32; n];
+    for (index, sample) in samples.iter().enumerate() {
+        let window = 0.5 - 0.5 * (2.0 * PI * index as f32 / (n - 1).max(1) as f32).cos();
+        real[index] = sample * window;
+    }
+    fft(&mut real, &mut imaginary);
+
+    // the hann window halves the amplitude
+    let scale = 4.0 / n as f32;
+    let bin_width = sample_rate as f32 / n as f32;
+    let max_frequency = MAX_FREQUENCY.min(sample_rate as f32 / 2.0);
+    let ratio = (max_frequency / MIN_FREQUENCY).powf(1.0 / bands as f32);
+    (0..bands)
+        .map(|band| {
+            let low = MIN_FREQUENCY * ratio.powi(band as i32);
+            let high = low * ratio;
+            let first = ((low / bin_width) as usize).clamp(1, n / 2 - 1);
+            let last = ((high / bin_width) as usize).clamp(first, n / 2 - 1);
+            // the strongest bin, narrow low bands share their bin with the neighbours
+            let amplitude = (first..=last)
+                .map(|bin| (real[bin] * real[bin] + imaginary[bin] * imaginary[bin]).sqrt())
+                .fold(0.0f32, f32::max);
+            normalize_db(amplitude * scale)
+        })
+        .collect()
+}
+
+// the rms level of the samples, from 0 to 1
+pub fn vu_level(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let mean_square =
+        samples.iter().map(|sample| sample * sample).sum::<f32>() / samples.len() as f32;
+    normalize_db(mean_square.sqrt())
+}
+
+// bars rise immediately and fall slowly, the peaks stay on top for a moment
+#[derive(Clone, Debug, Default)]
+pub struct BarSmoother {
+    pub values: Vec<f32>,
+    pub peaks: Vec<f32>,
+    peak_holds: Vec<u32>,
+}
+
+// per frame, at 20 frames per second
+const FALL_SPEED: f32 = 0.06;
+const PEAK_FALL_SPEED: f32 = 0.02;
+const PEAK_HOLD_FRAMES: u32 = 10;
+
+impl BarSmoother {
+    pub fn update(&mut self, levels: &[f32]) {
+        if self.values.len() != levels.len() {
+            self.values = vec![0.0; levels.len()];
+            self.peaks = vec![0.0; levels.len()];
+            self.peak_holds = vec![0; levels.len()];
+        }
+        for (index, level) in levels.iter().enumerate() {
+            self.values[index] = level.max(self.values[index] - FALL_SPEED);
+            if self.values[index] >= self.peaks[index] {
+                self.peaks[index] = self.values[index];
+                self.peak_holds[index] = PEAK_HOLD_FRAMES;
+            } else if self.peak_holds[index] > 0 {
+                self.peak_holds[index] -= 1;
+            } else {
+                self.peaks[index] = (self.peaks[index] - PEAK_FALL_SPEED).max(self.values[index]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(frequency: f32, amplitude: f32, sample_rate: u32) -> Vec<f32> {
+        (0..FFT_SIZE)
+            .map(|index| {
+                amplitude * (2.0 * PI * frequency * index as f32 / sample_rate as f32).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fft() {
+        // an impulse has a flat spectrum
+        let mut real = vec![0.0; 8];
+        let mut imaginary = vec![0.0; 8];
+        real[0] = 1.0;
+        fft(&mut real, &mut imaginary);
+        assert!(real.iter().all(|value| (value - 1.0).abs() < 1e-6));
+        assert!(imaginary.iter().all(|value| value.abs() < 1e-6));
+
+        // a cosine with two periods ends up in the second bin
+        let mut real: Vec<f32> = (0..8)
+            .map(|index| (2.0 * PI * 2.0 * index as f32 / 8.0).cos())
+            .collect();
+        let mut imaginary = vec![0.0; 8];
+        fft(&mut real, &mut imaginary);
+        assert!((real[2] - 4.0).abs() < 1e-4);
+        assert!((real[6] - 4.0).abs() < 1e-4);
+        assert!(real[1].abs() < 1e-4 && real[3].abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_spectrum_bands() {
+        let bands = spectrum_bands(&sine(1000.0, 0.5, 44100), 44100, 16);
+        let loudest = bands.iter().enumerate().fold(0, |loudest, (index, level)| {
+            if *level > bands[loudest] {
+                index
+            } else {
+                loudest
+            }
+        });
+        // 1 khz is in the middle of the logarithmic scale from 40 hz to 16 khz
+        assert!((8..=9).contains(&loudest), "{:?}", bands);
+        assert!(bands[loudest] > 0.8);
+        assert!(bands[0] < 0.3 && bands[15] < 0.3);
+
+        assert!(spectrum_bands(&vec![0.0; FFT_SIZE], 44100, 16)
+            .iter()
+            .all(|level| *level == 0.0));
+        assert_eq!(spectrum_bands(&[], 44100, 4), vec![0.0; 4]);
+        assert_eq!(spectrum_bands(&[0.5; 5], 8000, 64).len(), 64);
+    }
+
+    #[test]
+    fn test_vu_and_smoothing() {
+        assert_eq!(vu_level(&[0.0; 64]), 0.0);
+        assert!((vu_level(&[1.0; 64]) - 1.0).abs() < 1e-6);
+        // -20 db
+        assert!((vu_level(&[0.1; 64]) - 2.0 / 3.0).abs() < 1e-3);
+
+        let mut smoother = BarSmoother::default();
+        smoother.update(&[1.0, 0.5]);
+        smoother.update(&[0.0, 0.8]);
+        assert!((smoother.values[0] - (1.0 - FALL_SPEED)).abs() < 1e-6);
+        assert_eq!(smoother.values[1], 0.8);
+        assert_eq!(smoother.peaks[0], 1.0);
+        for _ in 0..PEAK_HOLD_FRAMES + 1 {
+            smoother.update(&[0.0, 0.0]);
+        }
+        assert!(smoother.peaks[0] < 1.0);
+        assert!(smoother.peaks[0] >= smoother.values[0]);
+    }
+}
@@ -1,19 +1,5 @@
 use regex::Regex;
 
-pub enum Direction {
-    Left,
-    //Right,
-}
-
-pub fn rotate(str: &str, direction: Direction, count: usize) -> String {
-    let mut str_vec: Vec<char> = str.chars().collect();
-    match direction {
-        Direction::Left => str_vec.rotate_left(count),
-        //Direction::Right => str_vec.rotate_right(count),
-    }
-    str_vec.iter().collect()
-}
-
 pub fn humanize_string(input: &str) -> String {
     let snake_case_regex = Regex::new(r"_(.)").unwrap();
     let camel_case_regex = Regex::new(r"([a-z])([A-Z])").unwrap();
@@ -54,3 +40,91 @@ pub fn determine_field_value(input: &str) -> String {
         parsed_val.to_string()
     }
 }
+
+pub fn humanize_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "K", "M", "G", "T"];
+    let mut value = bytes.max(0.0);
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 || value >= 100.0 {
+        format!("{:.0}{}", value, UNITS[unit])
+    } else {
+        format!("{:.1}{}", value, UNITS[unit])
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DurationFormat {
+    // "04:10:33", or "12d 04:10" from a day on
+    Clock,
+    // "3:07" or "1:02:45", like players show the position and length of a track
+    Track,
+    // "45 min" or "2 h 05 min"
+    Minutes,
+}
+
+// the one formatter for durations shown on the screens
+pub fn format_duration(duration: std::time::Duration, format: DurationFormat) -> String {
+    let seconds = duration.as_secs();
+    let days = seconds / 86400;
+    let hours = (seconds / 3600) % 24;
+    let minutes = (seconds / 60) % 60;
+    match format {
+        DurationFormat::Clock if days > 0 => {
+            format!("{}d {:0>2}:{:0>2}", days, hours, minutes)
+        }
+        DurationFormat::Clock => format!("{:0>2}:{:0>2}:{:0>2}", hours, minutes, seconds % 60),
+        DurationFormat::Track if seconds >= 3600 => {
+            format!("{}:{:0>2}:{:0>2}", seconds / 3600, minutes, seconds % 60)
+        }
+        DurationFormat::Track => format!("{}:{:0>2}", seconds / 60, seconds % 60),
+        DurationFormat::Minutes if seconds >= 3600 => {
+            format!("{} h {:0>2} min", seconds / 3600, minutes)
+        }
+        DurationFormat::Minutes => format!("{} min", minutes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(
+            format_duration(Duration::from_secs(43200), DurationFormat::Clock),
+            "12:00:00"
+        );
+        assert_eq!(
+            format_duration(Duration::from_secs(15033), DurationFormat::Clock),
+            "04:10:33"
+        );
+        assert_eq!(
+            format_duration(
+                Duration::from_secs(12 * 86400 + 15033),
+                DurationFormat::Clock
+            ),
+            "12d 04:10"
+        );
+        assert_eq!(
+            format_duration(Duration::from_secs(187), DurationFormat::Track),
+            "3:07"
+        );
+        assert_eq!(
+            format_duration(Duration::from_secs(3765), DurationFormat::Track),
+            "1:02:45"
+        );
+        assert_eq!(
+            format_duration(Duration::from_secs(420), DurationFormat::Minutes),
+            "7 min"
+        );
+        assert_eq!(
+            format_duration(Duration::from_secs(7500), DurationFormat::Minutes),
+            "2 h 05 min"
+        );
+    }
+}
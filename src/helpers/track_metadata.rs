@@ -0,0 +1,115 @@
+use super::text_manipulation::{format_duration, DurationFormat};
+use std::time::Duration;
+
+// the fields of the detail line, if nothing is configured
+pub const DEFAULT_DETAILS: &str = "album,year";
+
+// the tags of the current track, empty strings for missing tags
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TrackMetadata {
+    pub artist: String,
+    pub title: String,
+    pub album: String,
+    pub album_artist: String,
+    pub track_number: Option<u16>,
+    pub year: Option<i32>,
+    pub genre: String,
+    pub duration: Option<Duration>,
+}
+
+impl TrackMetadata {
+    // tracks of compilations often only have an album artist
+    pub fn display_artist(&self) -> &str {
+        if self.artist.is_empty() {
+            &self.album_artist
+        } else {
+            &self.artist
+        }
+    }
+
+    // the configured fields, e.g. "album,year", separated by dots. missing tags are skipped.
+    pub fn details(&self, fields: &str) -> String {
+        fields
+            .split(',')
+            .filter_map(|field| match field.trim() {
+                "album" => Some(self.album.clone()),
+                "album_artist" => Some(self.album_artist.clone()),
+                "track" => self.track_number.map(|number| format!("#{}", number)),
+                "year" => self.year.map(|year| year.to_string()),
+                "genre" => Some(self.genre.clone()),
+                "duration" => self
+                    .duration
+                    .map(|duration| format_duration(duration, DurationFormat::Track)),
+                _ => None,
+            })
+            .filter(|value| !value.trim().is_empty())
+            .collect::<Vec<String>>()
+            .join(" \u{00B7} ")
+    }
+}
+
+// artist and title of a winamp caption like "12. Artist - Title - Winamp [Paused]".
+// only the first " - " separates the artist, so titles may contain it as well.
+// captions of tracks without artist return an empty artist.
+pub fn parse_window_title(caption: &str) -> Option<(String, String)> {
+    let caption = caption.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    // the player name follows the last dash, the playlist position precedes the first space
+    let (track, _) = caption.rsplit_once('-')?;
+    let (_, track) = track.split_once(char::is_whitespace)?;
+    let track = track.trim();
+    if track.is_empty() {
+        return None;
+    }
+    match track.split_once(" - ") {
+        Some((artist, title)) => Some((artist.trim().to_string(), title.trim().to_string())),
+        None => Some((String::new(), track.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caption(artist: &str, title: &str) -> Option<(String, String)> {
+        Some((artist.to_string(), title.to_string()))
+    }
+
+    #[test]
+    fn test_parse_window_title() {
+        assert_eq!(
+            parse_window_title("12. Daft Punk - One More Time - Winamp\0"),
+            caption("Daft Punk", "One More Time")
+        );
+        assert_eq!(
+            parse_window_title("3. Artist - Title - Live - Winamp [Paused]"),
+            caption("Artist", "Title - Live")
+        );
+        assert_eq!(
+            parse_window_title("7. Untitled Track - Winamp"),
+            caption("", "Untitled Track")
+        );
+        assert_eq!(parse_window_title("Winamp 5.9"), None);
+        assert_eq!(parse_window_title("1. - Winamp"), None);
+        assert_eq!(parse_window_title(""), None);
+    }
+
+    #[test]
+    fn test_details() {
+        let metadata = TrackMetadata {
+            artist: String::new(),
+            title: "Title".into(),
+            album: "Album".into(),
+            album_artist: "Various Artists".into(),
+            track_number: Some(3),
+            year: None,
+            genre: "Jazz".into(),
+            duration: Some(Duration::from_secs(187)),
+        };
+        assert_eq!(metadata.display_artist(), "Various Artists");
+        assert_eq!(metadata.details(DEFAULT_DETAILS), "Album");
+        assert_eq!(
+            metadata.details("track, genre,duration,unknown"),
+            "#3 \u{00B7} Jazz \u{00B7} 3:07"
+        );
+    }
+}
@@ -0,0 +1,56 @@
+use ab_glyph::{FontArc, PxScale};
+use image::{ImageBuffer, Rgb};
+use imageproc::{
+    drawing::{draw_filled_rect_mut, draw_hollow_rect_mut, draw_text_mut, text_size},
+    rect::Rect,
+};
+
+// draws a red frame with the given message on top of the rgb bytes of a screen
+pub fn draw_warning_frame(
+    bytes: Vec<u8>,
+    width: u32,
+    height: u32,
+    message: &str,
+    font: &FontArc,
+) -> Vec<u8> {
+    let mut image: ImageBuffer<Rgb<u8>, Vec<u8>> = match ImageBuffer::from_raw(width, height, bytes)
+    {
+        Some(image) => image,
+        None => return vec![],
+    };
+    let red = Rgb([230u8, 40u8, 40u8]);
+    let thickness = (height / 32).max(2);
+    for offset in 0..thickness {
+        draw_hollow_rect_mut(
+            &mut image,
+            Rect::at(offset as i32, offset as i32).of_size(
+                width.saturating_sub(offset * 2).max(1),
+                height.saturating_sub(offset * 2).max(1),
+            ),
+            red,
+        );
+    }
+
+    let size = (height as f32 / 5.0).clamp(12.0, 28.0);
+    let scale = PxScale { x: size, y: size };
+    let (text_width, text_height) = text_size(scale, font, message);
+    let box_width = (text_width + 8).min(width);
+    let box_height = text_height + 8;
+    let x = (width as i32 - box_width as i32) / 2;
+    let y = (height as i32 - box_height as i32) / 2;
+    draw_filled_rect_mut(
+        &mut image,
+        Rect::at(x, y).of_size(box_width, box_height),
+        red,
+    );
+    draw_text_mut(
+        &mut image,
+        Rgb([255u8, 255u8, 255u8]),
+        x + 4,
+        y + 4,
+        scale,
+        font,
+        message,
+    );
+    image.into_vec()
+}
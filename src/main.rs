@@ -1,18 +1,27 @@
 #![windows_subsystem = "windows"]
 extern crate winapi;
 
+mod brightness;
+mod calendar;
+mod climate;
 mod config;
 mod config_manager;
 mod converters;
 mod dada_packet;
 mod device;
 mod helpers;
+mod media;
 mod screen_manager;
 mod screens;
+mod sensors;
 mod style;
+mod system;
 mod weather;
 
 use ab_glyph::FontArc;
+use brightness::{
+    BrightnessMode, BrightnessSettings, MAX_BRIGHTNESS, MAX_BRIGHTNESS_OFFSET, MIN_BRIGHTNESS,
+};
 use converters::image::{GrayscaleConverter, ImageProcessor, WebPConverter};
 use debounce::EventDebouncer;
 use device::*;
@@ -22,7 +31,7 @@ use helpers::keyboard::{self, set_last_key, start_global_key_grabber};
 use helpers::power::window_proc;
 use helpers::{
     convert_image::*, gui_helpers::*, power::register_power_broadcast,
-    text_manipulation::humanize_string,
+    text_manipulation::humanize_string, warning_frame::draw_warning_frame,
 };
 use iced::widget::{Space, Text};
 use iced::{time, window, Element, Length, Subscription, Task, Theme};
@@ -32,10 +41,14 @@ use lazy_static::lazy_static;
 use named_lock::NamedLock;
 use named_lock::Result;
 use once_cell::sync::Lazy;
+use screen_manager::hotkey_request;
+use sensors::SensorKind;
+use system::{alerts::AlertAction, service::MetricService};
 
 use std::{
     error::Error,
     fmt,
+    path::Path,
     sync::{atomic::AtomicBool, Arc, Mutex, RwLock},
 };
 
@@ -105,7 +118,18 @@ lazy_static! {
     static ref LAST_KEY_VALUE: Mutex<u32> = Mutex::new(0);
     static ref CLOSE_REQUESTED: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
     static ref HIBERNATING: Mutex<bool> = Mutex::new(false);
-    static ref LAST_BME_INFO: Mutex<(String, String)> = Mutex::new((String::new(), String::new()));
+    static ref SENSORS: RwLock<sensors::SensorRegistry> =
+        RwLock::new(sensors::SensorRegistry::default());
+    // transport commands of buttons, hotkeys and the local api, sent by the media controller
+    static ref MEDIA_COMMANDS: (
+        crossbeam_channel::Sender<media::MediaCommand>,
+        crossbeam_channel::Receiver<media::MediaCommand>
+    ) = crossbeam_channel::unbounded();
+    // screens and their modes, requested by the media keys and the local api
+    static ref SCREEN_REQUESTS: (
+        crossbeam_channel::Sender<screen_manager::ScreenRequest>,
+        crossbeam_channel::Receiver<screen_manager::ScreenRequest>
+    ) = crossbeam_channel::unbounded();
 }
 const TEENSY: &str = "teensy";
 const ESP32: &str = "esp32";
@@ -119,7 +143,7 @@ static DEVICES: Lazy<IndexMap<String, Device>> = Lazy::new(|| {
             4608000,
             false,
             ImageProcessor::new(Box::new(GrayscaleConverter), 256, 64),
-            true,
+            vec![SensorKind::Temperature, SensorKind::Humidity],
             false,
             Arc::new(|value| value),
         ),
@@ -131,7 +155,7 @@ static DEVICES: Lazy<IndexMap<String, Device>> = Lazy::new(|| {
             921600,
             true,
             ImageProcessor::new(Box::new(WebPConverter), 320, 170),
-            false,
+            Vec::new(),
             true,
             Arc::new(|value| (value as f32 * 2.55f32) as u8 - 1),
         ),
@@ -198,6 +222,12 @@ struct AwesomeDisplay {
     screens: Arc<Mutex<screen_manager::ScreenManager>>,
     config_manager: Arc<RwLock<config_manager::ConfigManager>>,
     companion_brightness_debouncers: IndexMap<String, Mutex<EventDebouncer<BrightnessEvent>>>,
+    metric_service: Arc<MetricService>,
+    font: FontArc,
+    // candidates of the last location search, one of them can be added to the saved locations
+    location_results: Vec<weather::location::Result>,
+    location_search_status: String,
+    scrobbler_export_status: String,
 }
 
 #[derive(Debug, Clone)]
@@ -208,10 +238,19 @@ enum Message {
     SaveConfig,
     FontLoaded(Result<(), iced::font::Error>),
     BrightnessChanged(f32, String),
+    BrightnessModeChanged(String),
+    BrightnessOffsetChanged(f32, String),
     ScreenStatusChanged(bool, String),
     KeyboardEventOccurred(iced::keyboard::Key, u32),
     WindowEventOccurred(iced::window::Event),
     ConfigValueChanged(String, String, ConfigParam),
+    AcknowledgeAlerts,
+    SearchLocation,
+    LocationsFound(std::result::Result<Vec<weather::location::Result>, String>),
+    AddLocation(usize),
+    MediaControl(media::MediaCommand),
+    ExportScrobblerLog,
+    ScrobblerLogExported(String),
 }
 
 impl AwesomeDisplay {
@@ -220,6 +259,7 @@ impl AwesomeDisplay {
         let symbols = FontArc::try_from_slice(SYMBOL_BYTES).unwrap();
         let config_manager =
             std::sync::Arc::new(RwLock::new(config_manager::ConfigManager::new(None)));
+        let metric_service = Arc::new(MetricService::new(config_manager.clone()));
         let mut screens: Vec<Box<dyn screens::BasicScreen>> = Vec::new();
 
         screens.push(Box::new(
@@ -228,8 +268,15 @@ impl AwesomeDisplay {
                 String::from("system_info_screen"),
                 font.clone(),
                 config_manager.clone(),
+                &metric_service,
             ),
         ));
+        screens.push(Box::new(screens::process_screen::ProcessScreen::new(
+            String::from("Processes"),
+            String::from("process_screen"),
+            font.clone(),
+            config_manager.clone(),
+        )));
         screens.push(Box::new(screens::media_info_screen::MediaInfoScreen::new(
             String::from("Media Info"),
             String::from("media_info_screen"),
@@ -237,6 +284,20 @@ impl AwesomeDisplay {
             symbols.clone(),
             config_manager.clone(),
         )));
+        screens.push(Box::new(
+            screens::listening_stats_screen::ListeningStatsScreen::new(
+                String::from("Listening Stats"),
+                String::from("listening_stats_screen"),
+                font.clone(),
+                config_manager.clone(),
+            ),
+        ));
+        screens.push(Box::new(screens::visualizer_screen::VisualizerScreen::new(
+            String::from("Visualizer"),
+            String::from("visualizer_screen"),
+            font.clone(),
+            config_manager.clone(),
+        )));
         screens.push(Box::new(screens::weather_screen::WeatherScreen::new(
             String::from("Weather Info"),
             String::from("weather_screen"),
@@ -244,6 +305,24 @@ impl AwesomeDisplay {
             symbols.clone(),
             config_manager.clone(),
         )));
+        screens.push(Box::new(screens::climate_screen::ClimateScreen::new(
+            String::from("Indoor Climate"),
+            String::from("climate_screen"),
+            font.clone(),
+            config_manager.clone(),
+        )));
+        screens.push(Box::new(screens::clock_screen::ClockScreen::new(
+            String::from("Clock"),
+            String::from("clock_screen"),
+            font.clone(),
+            config_manager.clone(),
+        )));
+        screens.push(Box::new(screens::agenda_screen::AgendaScreen::new(
+            String::from("Agenda"),
+            String::from("agenda_screen"),
+            font.clone(),
+            config_manager.clone(),
+        )));
 
         // look for plugins - windows only right now
         for entry in glob("./*.dll").expect("Failed to read glob pattern") {
@@ -287,6 +366,11 @@ impl AwesomeDisplay {
             screens: Arc::new(Mutex::new(screen_manager::ScreenManager::new(screens))),
             config_manager,
             companion_brightness_debouncers: debouncers,
+            metric_service,
+            font,
+            location_results: Vec::new(),
+            location_search_status: String::new(),
+            scrobbler_export_status: String::new(),
         };
 
         // global key press listener
@@ -296,8 +380,27 @@ impl AwesomeDisplay {
         for (key, device) in DEVICES.iter() {
             device.set_brightness(this.config_manager.read().unwrap().get_brightness(key));
 
-            device.start_background_workers()
+            let media_buttons = matches!(
+                this.config_manager.read().unwrap().get_device_value(key, "media_buttons"),
+                Some(ConfigParam::String(value)) if value == "on"
+            );
+            device.start_background_workers(key, media_buttons)
         }
+        sensors::start_host_sources(sensors::file::host_sources(
+            &this.config_manager.read().unwrap().get_host_sensors(),
+        ));
+        brightness::start_auto_brightness(this.config_manager.clone());
+        media::controller::start_media_controller(
+            this.config_manager.clone(),
+            "media_info_screen".into(),
+        );
+        let api_port = this
+            .config_manager
+            .read()
+            .unwrap()
+            .get_integer_value("media_info_screen", "api_port")
+            .unwrap_or(media::api::DEFAULT_API_PORT);
+        media::api::start_local_api(u16::try_from(api_port).unwrap_or_default());
         (
             this,
             iced::font::load(SYMBOL_BYTES).map(Message::FontLoaded),
@@ -305,8 +408,9 @@ impl AwesomeDisplay {
     }
 
     fn subscription(&self) -> iced::Subscription<Message> {
-        let tick = time::every(std::time::Duration::from_millis(250))
-            .map(|_| Message::UpdateCurrentScreen);
+        // animated screens like the visualizer are redrawn more often
+        let interval = self.screens.lock().unwrap().refresh_interval();
+        let tick = time::every(interval).map(|_| Message::UpdateCurrentScreen);
 
         fn handle_hotkey(event: iced::keyboard::Event) -> Option<Message> {
             match event {
@@ -364,6 +468,7 @@ impl AwesomeDisplay {
     }
     fn update(&mut self, message: Message) -> Task<Message> {
         let mut screen_manager = self.screens.lock().unwrap();
+        let mut task = Task::none();
         match message {
             Message::SaveConfig => {
                 self.config_manager.write().unwrap().save();
@@ -379,15 +484,27 @@ impl AwesomeDisplay {
                 screen_manager.update_current_screen();
             }
             Message::UpdateCurrentScreen => {
+                let alerts = self.metric_service.active_alerts();
+                if alerts
+                    .iter()
+                    .any(|alert| alert.rule.action == AlertAction::SwitchScreen)
+                {
+                    screen_manager.raise_screen("system_info_screen");
+                }
                 if *LAST_KEY.lock().unwrap() {
                     *LAST_KEY.lock().unwrap() = false;
                     let val = *LAST_KEY_VALUE.lock().unwrap();
-                    if val == 174 || val == 175 {
-                        // 1 is "volume mode"
-                        screen_manager.set_screen_for_short("media_info_screen".into(), 1);
-                    } else if [173, 176, 177, 178, 179].contains(&val) {
-                        // 0 is "normal mode"
-                        screen_manager.set_screen_for_short("media_info_screen".into(), 0);
+                    let hotkeys = self.config_manager.read().unwrap().get_hotkeys();
+                    if let Some(request) = hotkey_request(&hotkeys, val) {
+                        screen_manager.show_screen_mode(
+                            &request.key,
+                            &request.mode,
+                            request.duration,
+                        );
+                    } else if val == 180 && !alerts.is_empty() {
+                        // pause acknowledges pending alerts
+                        self.metric_service.acknowledge_alerts();
+                        screen_manager.release_raised_screen();
                     } else if val == 180 {
                         screen_manager.next_screen()
                     }
@@ -443,11 +560,63 @@ impl AwesomeDisplay {
                 }
             }
 
+            // the background thread applies the automatic brightness
+            Message::BrightnessModeChanged(key) => {
+                let mut config_manager = self.config_manager.write().unwrap();
+                let mode = BrightnessSettings::from_config(&config_manager, &key).mode;
+                config_manager.set_device_value(
+                    &key,
+                    "brightness_mode",
+                    ConfigParam::String(mode.next().key().into()),
+                );
+            }
+            Message::BrightnessOffsetChanged(offset, key) => {
+                self.config_manager.write().unwrap().set_device_value(
+                    &key,
+                    "brightness_offset",
+                    ConfigParam::Float(offset),
+                );
+            }
             Message::ScreenStatusChanged(status, screen) => {
                 if screen_manager.screen_deactivatable(&screen) {
                     screen_manager.set_status_for_screen(&screen, status);
                 }
             }
+            Message::MediaControl(command) => {
+                media::send_command(command);
+            }
+            Message::ExportScrobblerLog => {
+                self.scrobbler_export_status = "Exporting...".into();
+                // the history grows with every play, so it is read off the gui thread
+                task = Task::perform(
+                    tokio::task::spawn_blocking(|| {
+                        let plays = media::history::load_plays(Path::new(
+                            media::history::PLAY_HISTORY_PATH,
+                        ));
+                        match media::scrobble::export_scrobbler_log(
+                            &plays,
+                            Path::new(media::scrobble::SCROBBLER_LOG_PATH),
+                            Path::new(media::scrobble::SCROBBLER_EXPORTED_PATH),
+                        ) {
+                            Ok(0) => "No new plays to export".to_string(),
+                            Ok(plays) => format!("Exported {} plays", plays),
+                            Err(e) => format!("Export failed: {}", e),
+                        }
+                    }),
+                    |result| {
+                        Message::ScrobblerLogExported(
+                            result.unwrap_or_else(|e| format!("Export failed: {}", e)),
+                        )
+                    },
+                );
+            }
+            Message::ScrobblerLogExported(status) => {
+                self.scrobbler_export_status = status;
+            }
+            Message::AcknowledgeAlerts => {
+                self.metric_service.acknowledge_alerts();
+                screen_manager.release_raised_screen();
+            }
             Message::ConfigValueChanged(screen, key, value) => {
                 self.config_manager
                     .write()
@@ -462,6 +631,53 @@ impl AwesomeDisplay {
 
                 screen_manager.update_screen_config(&key, serialized_screen_config.clone())
             }
+            Message::SearchLocation => {
+                let city = self
+                    .config_manager
+                    .read()
+                    .unwrap()
+                    .get_string_value("weather_screen", "weather_location")
+                    .unwrap_or_default();
+                if !city.trim().is_empty() {
+                    self.location_search_status = "Searching...".into();
+                    task = Task::perform(weather::location::search_locations(city, 10), |result| {
+                        Message::LocationsFound(result.map_err(|e| e.to_string()))
+                    });
+                }
+            }
+            Message::LocationsFound(result) => match result {
+                Ok(results) => {
+                    self.location_search_status = if results.is_empty() {
+                        "No location found".into()
+                    } else {
+                        String::new()
+                    };
+                    self.location_results = results;
+                }
+                Err(e) => {
+                    self.location_search_status = format!("Search failed: {}", e);
+                    self.location_results.clear();
+                }
+            },
+            Message::AddLocation(index) => {
+                if let Some(location) = self.location_results.get(index) {
+                    let mut config_manager = self.config_manager.write().unwrap();
+                    let mut locations = weather::places::parse_locations(
+                        &config_manager
+                            .get_string_value("weather_screen", "locations")
+                            .unwrap_or_default(),
+                    );
+                    locations.push(weather::places::SavedLocation::from_geocoding(location));
+                    config_manager.set_value(
+                        "weather_screen".into(),
+                        "locations".into(),
+                        ConfigParam::String(weather::places::format_locations(&locations)),
+                    );
+                    self.location_search_status =
+                        format!("Added {}", weather::places::describe(location));
+                    self.location_results.clear();
+                }
+            }
             _ => (),
         }
 
@@ -477,7 +693,7 @@ impl AwesomeDisplay {
             return iced::window::latest().and_then(iced::window::close);
         }
 
-        Task::none()
+        task
     }
 
     fn title(&self) -> String {
@@ -519,6 +735,30 @@ impl AwesomeDisplay {
             }
         }
 
+        // flash pending alerts on top of the current screen, every other half second
+        let alerts = self.metric_service.active_alerts();
+        let flashing_alert = alerts
+            .iter()
+            .find(|alert| alert.rule.action == AlertAction::Flash);
+        if let Some(alert) = flashing_alert {
+            let flash_on = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|time| time.as_millis() / 500 % 2 == 0)
+                .unwrap_or(true);
+            if flash_on {
+                for (device_name, buffer) in screen_bytes.iter_mut() {
+                    let device = DEVICES.get(*device_name).unwrap();
+                    *buffer = draw_warning_frame(
+                        buffer.clone(),
+                        device.screen_width(),
+                        device.screen_height(),
+                        &alert.message(),
+                        &self.font,
+                    );
+                }
+            }
+        }
+
         for (device_name, buffer) in screen_bytes.iter() {
             if !buffer.is_empty() {
                 DEVICES
@@ -548,28 +788,92 @@ impl AwesomeDisplay {
             .width(Length::Fixed(200f32))
             .into(),
         ];
+        column_parts.append(&mut media_controls());
+        // plays of the media screen in the format of scrobbling clients
+        column_parts.push(
+            iced::widget::button(
+                Text::new("Export scrobbler log")
+                    .center()
+                    .style(|_theme| crate::style::text()),
+            )
+            .on_press(Message::ExportScrobblerLog)
+            .width(Length::Fixed(200f32))
+            .into(),
+        );
+        if !self.scrobbler_export_status.is_empty() {
+            column_parts.push(
+                iced::widget::text(self.scrobbler_export_status.clone())
+                    .width(Length::Fixed(200f32))
+                    .into(),
+            );
+        }
+
+        if !alerts.is_empty() {
+            column_parts.push(
+                iced::widget::button(
+                    Text::new(format!("Acknowledge {} alert(s)", alerts.len()))
+                        .center()
+                        .style(|_theme| crate::style::text()),
+                )
+                .on_press(Message::AcknowledgeAlerts)
+                .width(Length::Fixed(200f32))
+                .into(),
+            );
+        }
 
         for key in DEVICES.keys() {
-            let text: iced::Element<Message, Theme, iced::Renderer> = iced::widget::text(format!(
-                "{} brightness: {}%",
-                key.to_uppercase(),
-                self.config_manager.read().unwrap().get_brightness(key)
-            ))
-            .center()
-            .width(Length::Fixed(220f32))
-            .into();
-            let slider: iced::Element<Message, Theme, iced::Renderer> = iced::widget::Slider::new(
-                20.0..=100.0,
-                self.config_manager.read().unwrap().get_brightness(key) as f32,
-                |slider_value| -> Message {
-                    Message::BrightnessChanged(slider_value, key.to_string())
-                },
+            let settings =
+                BrightnessSettings::from_config(&self.config_manager.read().unwrap(), key);
+            let mode_button: iced::Element<Message, Theme, iced::Renderer> = iced::widget::button(
+                Text::new(format!("Brightness: {}", settings.mode.label()))
+                    .center()
+                    .style(|_theme| crate::style::text()),
             )
+            .on_press(Message::BrightnessModeChanged(key.to_string()))
             .width(Length::Fixed(200f32))
-            .step(1.0)
             .into();
 
+            // while the brightness is automatic, the slider shifts it
+            let (text, slider) = if settings.mode == BrightnessMode::Manual {
+                (
+                    format!(
+                        "{} brightness: {}%",
+                        key.to_uppercase(),
+                        self.config_manager.read().unwrap().get_brightness(key)
+                    ),
+                    iced::widget::Slider::new(
+                        MIN_BRIGHTNESS as f32..=MAX_BRIGHTNESS as f32,
+                        self.config_manager.read().unwrap().get_brightness(key) as f32,
+                        |slider_value| -> Message {
+                            Message::BrightnessChanged(slider_value, key.to_string())
+                        },
+                    ),
+                )
+            } else {
+                (
+                    format!(
+                        "{} brightness offset: {:+}%",
+                        key.to_uppercase(),
+                        settings.offset
+                    ),
+                    iced::widget::Slider::new(
+                        -MAX_BRIGHTNESS_OFFSET..=MAX_BRIGHTNESS_OFFSET,
+                        settings.offset,
+                        |slider_value| -> Message {
+                            Message::BrightnessOffsetChanged(slider_value, key.to_string())
+                        },
+                    ),
+                )
+            };
+            let text: iced::Element<Message, Theme, iced::Renderer> = iced::widget::text(text)
+                .center()
+                .width(Length::Fixed(220f32))
+                .into();
+            let slider: iced::Element<Message, Theme, iced::Renderer> =
+                slider.width(Length::Fixed(200f32)).step(1.0).into();
+
             column_parts.push(text);
+            column_parts.push(mode_button);
             column_parts.push(slider);
         }
 
@@ -615,6 +919,35 @@ impl AwesomeDisplay {
             .style(|_theme, _status| crate::style::text_field())
             .width(Length::Fixed(200f32))
             .into(),
+            // ambiguous names are resolved by picking one of the candidates
+            iced::widget::button(
+                Text::new("Search location")
+                    .center()
+                    .style(|_theme| crate::style::text()),
+            )
+            .width(Length::Fixed(200f32))
+            .on_press(Message::SearchLocation)
+            .into(),
+        ];
+        if !self.location_search_status.is_empty() {
+            left_column_after_screens.push(
+                iced::widget::text(self.location_search_status.clone())
+                    .width(Length::Fixed(200f32))
+                    .into(),
+            );
+        }
+        for (index, location) in self.location_results.iter().enumerate() {
+            left_column_after_screens.push(
+                iced::widget::button(
+                    Text::new(format!("+ {}", weather::places::describe(location)))
+                        .style(|_theme| crate::style::text()),
+                )
+                .width(Length::Fixed(200f32))
+                .on_press(Message::AddLocation(index))
+                .into(),
+            );
+        }
+        left_column_after_screens.append(&mut vec![
             iced::widget::button(
                 Text::new("Save config")
                     .center()
@@ -625,7 +958,7 @@ impl AwesomeDisplay {
             .into(),
             iced::widget::Row::with_children(vec![Space::new().height(10).into()]).into(),
             iced::widget::Row::with_children(vec![iced::widget::text("Devices").into()]).into(),
-        ];
+        ]);
 
         for key in DEVICES.keys() {
             let row: iced::Element<Message, Theme, iced::Renderer> =
@@ -0,0 +1,147 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    thread,
+    time::Duration,
+};
+
+use super::{send_command, MediaCommand};
+use crate::screen_manager::{request_screen, ScreenRequest};
+
+// the api is off by default, other programs on the computer could control the player otherwise
+pub const DEFAULT_API_PORT: u32 = 0;
+
+#[derive(Debug, PartialEq)]
+enum ApiRequest {
+    Media(MediaCommand),
+    Screen(ScreenRequest),
+}
+
+// a minimal http api on localhost, e.g. "curl -X POST http://127.0.0.1:<port>/media/next".
+// "/screens/media_info_screen/volume?seconds=5" shows a mode of a screen.
+pub fn start_local_api(port: u16) {
+    if port == 0 {
+        return;
+    }
+    let listener = match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("Could not start the local api on port {}: {}", port, error);
+            return;
+        }
+    };
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Err(error) = handle_connection(stream) {
+                eprintln!("Local api request failed: {}", error);
+            }
+        }
+    });
+}
+
+fn handle_connection(mut stream: TcpStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let status = match route(&request_line) {
+        Ok(ApiRequest::Media(command)) => {
+            send_command(command);
+            "204 No Content"
+        }
+        Ok(ApiRequest::Screen(request)) => {
+            request_screen(request);
+            "204 No Content"
+        }
+        Err(status) => status,
+    };
+    stream.write_all(format!("HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status).as_bytes())
+}
+
+// "<key>/<mode>" with an optional duration like "?seconds=5"
+fn parse_screen_request(path: &str) -> Option<ScreenRequest> {
+    let (path, query) = path.split_once('?').unwrap_or((path, ""));
+    let (key, mode) = path.split_once('/')?;
+    if key.is_empty() || mode.is_empty() || mode.contains('/') {
+        return None;
+    }
+    let duration = match query {
+        "" => None,
+        query => Some(Duration::from_secs(
+            query.strip_prefix("seconds=")?.parse::<u64>().ok()?,
+        )),
+    };
+    Some(ScreenRequest {
+        key: key.into(),
+        mode: mode.into(),
+        duration,
+    })
+}
+
+// the request of a request line like "POST /media/seek:-10 HTTP/1.1", the status line otherwise
+fn route(request_line: &str) -> Result<ApiRequest, &'static str> {
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method, path),
+        _ => return Err("400 Bad Request"),
+    };
+    let request = if let Some(command) = path.strip_prefix("/media/") {
+        MediaCommand::parse(command).map(ApiRequest::Media)
+    } else if let Some(screen) = path.strip_prefix("/screens/") {
+        parse_screen_request(screen).map(ApiRequest::Screen)
+    } else {
+        None
+    }
+    .ok_or("404 Not Found")?;
+    if method != "POST" {
+        return Err("405 Method Not Allowed");
+    }
+    Ok(request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route() {
+        assert_eq!(
+            route("POST /media/next HTTP/1.1\r\n"),
+            Ok(ApiRequest::Media(MediaCommand::Next))
+        );
+        assert_eq!(
+            route("POST /media/seek:-10 HTTP/1.1"),
+            Ok(ApiRequest::Media(MediaCommand::Seek(-10)))
+        );
+        assert_eq!(
+            route("GET /media/play_pause HTTP/1.1"),
+            Err("405 Method Not Allowed")
+        );
+        assert_eq!(route("POST /media/rewind HTTP/1.1"), Err("404 Not Found"));
+        assert_eq!(route("POST /next HTTP/1.1"), Err("404 Not Found"));
+        assert_eq!(route(""), Err("400 Bad Request"));
+
+        assert_eq!(
+            route("POST /screens/media_info_screen/volume HTTP/1.1"),
+            Ok(ApiRequest::Screen(ScreenRequest::new(
+                "media_info_screen",
+                "volume"
+            )))
+        );
+        assert_eq!(
+            route("POST /screens/clock_screen/normal?seconds=5 HTTP/1.1"),
+            Ok(ApiRequest::Screen(ScreenRequest {
+                key: "clock_screen".into(),
+                mode: "normal".into(),
+                duration: Some(Duration::from_secs(5)),
+            }))
+        );
+        assert_eq!(
+            route("POST /screens/clock_screen HTTP/1.1"),
+            Err("404 Not Found")
+        );
+        assert_eq!(
+            route("POST /screens/clock_screen/normal?minutes=5 HTTP/1.1"),
+            Err("404 Not Found")
+        );
+    }
+}
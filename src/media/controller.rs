@@ -0,0 +1,47 @@
+use std::{
+    sync::{Arc, RwLock},
+    thread,
+};
+
+use super::{
+    mpd::{MpdBackend, DEFAULT_MPD_ADDRESS},
+    mpris::MprisBackend,
+    select_backend, show_media_screen,
+    winamp::WinampBackend,
+    MediaBackend, MediaError, DEFAULT_BACKEND,
+};
+use crate::{config_manager::ConfigManager, MEDIA_COMMANDS};
+
+// sends the queued commands to the configured player. the settings are read for every
+// command, so changing them in the app takes effect immediately.
+pub fn start_media_controller(config_manager: Arc<RwLock<ConfigManager>>, key: String) {
+    thread::spawn(move || {
+        for command in MEDIA_COMMANDS.1.iter() {
+            let (preferred, address) = {
+                let config_manager = config_manager.read().unwrap();
+                (
+                    config_manager
+                        .get_string_value(&key, "backend")
+                        .unwrap_or(DEFAULT_BACKEND.into()),
+                    config_manager
+                        .get_string_value(&key, "mpd_address")
+                        .unwrap_or(DEFAULT_MPD_ADDRESS.into()),
+                )
+            };
+            // winamp is checked first, it is the player the media screen shows
+            let mut backends: Vec<Box<dyn MediaBackend>> = vec![
+                Box::new(WinampBackend),
+                Box::new(MprisBackend),
+                Box::new(MpdBackend::new(&address)),
+            ];
+            let result = match select_backend(&mut backends, &preferred) {
+                Some(backend) => backend.send(command),
+                None => Err(MediaError::NoPlayer),
+            };
+            match result {
+                Ok(()) => show_media_screen(&key, command),
+                Err(error) => eprintln!("Media command {} failed: {}", command, error),
+            }
+        }
+    });
+}
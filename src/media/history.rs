@@ -0,0 +1,203 @@
+use chrono::{DateTime, Datelike, Days, TimeZone, Utc};
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+    time::Duration,
+};
+
+pub const PLAY_HISTORY_PATH: &str = "./play_history.tsv";
+const HEADER: &str = "time\tartist\talbum\ttitle\ttrack\tlength\tlistened";
+
+// a track that was listened to long enough to count
+#[derive(Clone, Debug, PartialEq)]
+pub struct Play {
+    // when the track was started
+    pub time: DateTime<Utc>,
+    pub artist: String,
+    pub album: String,
+    pub title: String,
+    pub track_number: Option<u16>,
+    pub length: Option<Duration>,
+    // the time the track was actually playing
+    pub listened: Duration,
+}
+
+// tags may contain tabs and line breaks, which would break the line format
+pub fn sanitize(value: &str) -> String {
+    value.replace(['\t', '\r', '\n'], " ").trim().to_string()
+}
+
+fn to_line(play: &Play) -> String {
+    format!(
+        "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+        play.time.to_rfc3339(),
+        sanitize(&play.artist),
+        sanitize(&play.album),
+        sanitize(&play.title),
+        play.track_number
+            .map(|number| number.to_string())
+            .unwrap_or_default(),
+        play.length
+            .map(|length| length.as_secs().to_string())
+            .unwrap_or_default(),
+        play.listened.as_secs()
+    )
+}
+
+fn from_line(line: &str) -> Option<Play> {
+    let fields: Vec<&str> = line.trim_end_matches(['\r', '\n']).split('\t').collect();
+    if fields.len() != 7 {
+        return None;
+    }
+    let time = DateTime::parse_from_rfc3339(fields[0]).ok()?;
+    Some(Play {
+        time: time.with_timezone(&Utc),
+        artist: fields[1].to_string(),
+        album: fields[2].to_string(),
+        title: fields[3].to_string(),
+        track_number: fields[4].parse().ok(),
+        length: fields[5].parse().ok().map(Duration::from_secs),
+        listened: Duration::from_secs(fields[6].parse().ok()?),
+    })
+}
+
+// the recorded plays of the tab separated history file, oldest first
+pub fn load_plays(path: &Path) -> Vec<Play> {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    let mut plays: Vec<Play> = content.lines().filter_map(from_line).collect();
+    plays.sort_by_key(|play| play.time);
+    plays
+}
+
+// appends the play to the history file, the plays before are not read
+pub fn record_play(path: &Path, play: &Play) {
+    let new_file = !path.exists();
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| {
+            if new_file {
+                writeln!(file, "{}", HEADER)?;
+            }
+            writeln!(file, "{}", to_line(play))
+        });
+    if let Err(e) = result {
+        eprintln!("Error: {:?}", e);
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ListeningStats {
+    // the most played artists of the week and their plays, most played first
+    pub top_artists: Vec<(String, usize)>,
+    pub week_plays: usize,
+    pub today_plays: usize,
+    pub today_listening_time: Duration,
+}
+
+// the week starts on monday, days are those of the time zone of now
+pub fn listening_stats<Tz: TimeZone>(
+    plays: &[Play],
+    now: DateTime<Tz>,
+    count: usize,
+) -> ListeningStats {
+    let today = now.date_naive();
+    let week_start = today
+        .checked_sub_days(Days::new(now.weekday().num_days_from_monday() as u64))
+        .unwrap_or(today);
+
+    let mut stats = ListeningStats::default();
+    let mut artists: HashMap<&str, usize> = HashMap::new();
+    for play in plays {
+        let date = play.time.with_timezone(&now.timezone()).date_naive();
+        if date < week_start || date > today {
+            continue;
+        }
+        stats.week_plays += 1;
+        if !play.artist.is_empty() {
+            *artists.entry(play.artist.as_str()).or_default() += 1;
+        }
+        if date == today {
+            stats.today_plays += 1;
+            stats.today_listening_time += play.listened;
+        }
+    }
+
+    let mut top_artists: Vec<(String, usize)> = artists
+        .into_iter()
+        .map(|(artist, plays)| (artist.to_string(), plays))
+        .collect();
+    top_artists.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    top_artists.truncate(count);
+    stats.top_artists = top_artists;
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_dir::TestDir;
+
+    fn play(time: &str, artist: &str, length: Option<u64>, listened: u64) -> Play {
+        Play {
+            time: DateTime::parse_from_rfc3339(time)
+                .unwrap()
+                .with_timezone(&Utc),
+            artist: artist.into(),
+            album: "Album\twith tab".into(),
+            title: "Title".into(),
+            track_number: Some(4),
+            length: length.map(Duration::from_secs),
+            listened: Duration::from_secs(listened),
+        }
+    }
+
+    #[test]
+    fn test_history_is_appended() {
+        let dir = TestDir::new("play_history");
+        let path = dir.join("play_history.tsv");
+
+        record_play(&path, &play("2024-05-08T20:04:00Z", "Other", None, 240));
+        record_play(
+            &path,
+            &play("2024-05-08T20:00:00Z", "Artist", Some(200), 100),
+        );
+        fs::write(&path, fs::read_to_string(&path).unwrap() + "broken line\n").unwrap();
+
+        let plays = load_plays(&path);
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with(HEADER));
+        assert_eq!(plays.len(), 2);
+        assert_eq!(plays[0].album, "Album with tab");
+        assert_eq!(plays[0].length, Some(Duration::from_secs(200)));
+        assert_eq!(plays[0].listened, Duration::from_secs(100));
+        assert_eq!(plays[1].length, None);
+        assert_eq!(plays[1].track_number, Some(4));
+    }
+
+    #[test]
+    fn test_listening_stats() {
+        // a wednesday
+        let now = DateTime::parse_from_rfc3339("2024-05-08T21:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let plays = vec![
+            // the sunday before does not count
+            play("2024-05-05T12:00:00Z", "Old", Some(100), 50),
+            play("2024-05-06T00:10:00Z", "Other", Some(100), 50),
+            play("2024-05-07T10:00:00Z", "Artist", Some(100), 50),
+            // the track was skipped after it counted
+            play("2024-05-08T10:00:00Z", "Artist", Some(600), 240),
+            play("2024-05-08T11:00:00Z", "", None, 250),
+        ];
+        let stats = listening_stats(&plays, now, 1);
+        assert_eq!(stats.week_plays, 4);
+        assert_eq!(stats.today_plays, 2);
+        assert_eq!(stats.today_listening_time, Duration::from_secs(240 + 250));
+        assert_eq!(stats.top_artists, vec![("Artist".to_string(), 2)]);
+        assert_eq!(listening_stats(&plays, now, 5).top_artists.len(), 2);
+    }
+}
@@ -0,0 +1,228 @@
+pub mod api;
+pub mod controller;
+pub mod history;
+pub mod mpd;
+pub mod mpris;
+pub mod scrobble;
+pub mod winamp;
+
+use std::fmt;
+
+use crate::{
+    screen_manager::{request_screen, ScreenRequest},
+    screens::NORMAL_MODE,
+    MEDIA_COMMANDS,
+};
+
+// the jump of the seek buttons and hotkeys in seconds
+pub const SEEK_STEP: i32 = 10;
+// the volume step of the players in percent
+pub const VOLUME_STEP: u8 = 5;
+// the mode of the media screen, that shows the volume instead of the progress
+pub const VOLUME_MODE: &str = "volume";
+// "auto" picks the first running player
+pub const DEFAULT_BACKEND: &str = "auto";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MediaCommand {
+    PlayPause,
+    Stop,
+    Next,
+    Previous,
+    // relative to the current position, in seconds
+    Seek(i32),
+    VolumeUp,
+    VolumeDown,
+}
+
+impl MediaCommand {
+    // the names of the local api, e.g. "next" or "seek:-10"
+    pub fn parse(name: &str) -> Option<MediaCommand> {
+        match name.trim().to_lowercase().as_str() {
+            "play_pause" => Some(MediaCommand::PlayPause),
+            "stop" => Some(MediaCommand::Stop),
+            "next" => Some(MediaCommand::Next),
+            "previous" => Some(MediaCommand::Previous),
+            "volume_up" => Some(MediaCommand::VolumeUp),
+            "volume_down" => Some(MediaCommand::VolumeDown),
+            name => name
+                .strip_prefix("seek:")
+                .and_then(|seconds| seconds.parse::<i32>().ok())
+                .map(MediaCommand::Seek),
+        }
+    }
+
+    // the codes the physical buttons of the devices report, 0 is no press
+    pub fn from_button(code: u8) -> Option<MediaCommand> {
+        match code {
+            1 => Some(MediaCommand::PlayPause),
+            2 => Some(MediaCommand::Stop),
+            3 => Some(MediaCommand::Next),
+            4 => Some(MediaCommand::Previous),
+            5 => Some(MediaCommand::VolumeUp),
+            6 => Some(MediaCommand::VolumeDown),
+            7 => Some(MediaCommand::Seek(SEEK_STEP)),
+            8 => Some(MediaCommand::Seek(-SEEK_STEP)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MediaCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MediaCommand::PlayPause => write!(f, "play_pause"),
+            MediaCommand::Stop => write!(f, "stop"),
+            MediaCommand::Next => write!(f, "next"),
+            MediaCommand::Previous => write!(f, "previous"),
+            MediaCommand::Seek(seconds) => write!(f, "seek:{:+}", seconds),
+            MediaCommand::VolumeUp => write!(f, "volume_up"),
+            MediaCommand::VolumeDown => write!(f, "volume_down"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MediaError {
+    // no backend with a running player
+    NoPlayer,
+    // the player of the backend can not be reached
+    Unavailable(String),
+    // the player rejected the command
+    Failed(String),
+}
+
+impl fmt::Display for MediaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MediaError::NoPlayer => write!(f, "no media player is running"),
+            MediaError::Unavailable(reason) => write!(f, "media player unavailable: {}", reason),
+            MediaError::Failed(reason) => write!(f, "media command failed: {}", reason),
+        }
+    }
+}
+
+pub trait MediaBackend {
+    // the name of the backend setting, e.g. "winamp"
+    fn name(&self) -> &'static str;
+    // whether the player of the backend is running
+    fn is_available(&self) -> bool;
+    fn send(&mut self, command: MediaCommand) -> Result<(), MediaError>;
+}
+
+// the configured backend, or the first one with a running player for "auto"
+pub fn select_backend<'a>(
+    backends: &'a mut [Box<dyn MediaBackend>],
+    preferred: &str,
+) -> Option<&'a mut Box<dyn MediaBackend>> {
+    let preferred = preferred.trim().to_lowercase();
+    if preferred.is_empty() || preferred == DEFAULT_BACKEND {
+        return backends.iter_mut().find(|backend| backend.is_available());
+    }
+    backends
+        .iter_mut()
+        .find(|backend| backend.name() == preferred)
+}
+
+// queues the command for the controller thread, so buttons and hotkeys never wait for a player
+pub fn send_command(command: MediaCommand) {
+    MEDIA_COMMANDS.0.try_send(command).unwrap_or_default();
+}
+
+// shows the media screen for a few seconds like the media keys do, with the volume after
+// volume commands
+fn show_media_screen(key: &str, command: MediaCommand) {
+    let mode = match command {
+        MediaCommand::VolumeUp | MediaCommand::VolumeDown => VOLUME_MODE,
+        _ => NORMAL_MODE,
+    };
+    request_screen(ScreenRequest::new(key, mode));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeBackend {
+        name: &'static str,
+        running: bool,
+    }
+
+    impl MediaBackend for FakeBackend {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn is_available(&self) -> bool {
+            self.running
+        }
+
+        fn send(&mut self, _command: MediaCommand) -> Result<(), MediaError> {
+            Ok(())
+        }
+    }
+
+    fn backends() -> Vec<Box<dyn MediaBackend>> {
+        vec![
+            Box::new(FakeBackend {
+                name: "winamp",
+                running: false,
+            }),
+            Box::new(FakeBackend {
+                name: "mpd",
+                running: true,
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(MediaCommand::parse("next"), Some(MediaCommand::Next));
+        assert_eq!(
+            MediaCommand::parse(" Play_Pause "),
+            Some(MediaCommand::PlayPause)
+        );
+        assert_eq!(
+            MediaCommand::parse("seek:-10"),
+            Some(MediaCommand::Seek(-10))
+        );
+        assert_eq!(MediaCommand::parse("seek:+5"), Some(MediaCommand::Seek(5)));
+        assert_eq!(MediaCommand::parse("seek:"), None);
+        assert_eq!(MediaCommand::from_button(3), Some(MediaCommand::Next));
+        assert_eq!(
+            MediaCommand::from_button(8),
+            Some(MediaCommand::Seek(-SEEK_STEP))
+        );
+        assert_eq!(MediaCommand::from_button(0), None);
+        assert_eq!(MediaCommand::parse("rewind"), None);
+        for command in [
+            MediaCommand::PlayPause,
+            MediaCommand::Stop,
+            MediaCommand::Previous,
+            MediaCommand::Seek(-30),
+            MediaCommand::VolumeUp,
+            MediaCommand::VolumeDown,
+        ] {
+            assert_eq!(MediaCommand::parse(&command.to_string()), Some(command));
+        }
+    }
+
+    #[test]
+    fn test_select_backend() {
+        let mut backends = backends();
+        assert_eq!(
+            select_backend(&mut backends, "auto").map(|backend| backend.name()),
+            Some("mpd")
+        );
+        assert_eq!(
+            select_backend(&mut backends, "Winamp").map(|backend| backend.name()),
+            Some("winamp")
+        );
+        assert!(select_backend(&mut backends, "vlc").is_none());
+        let mut stopped: Vec<Box<dyn MediaBackend>> = vec![Box::new(FakeBackend {
+            name: "winamp",
+            running: false,
+        })];
+        assert!(select_backend(&mut stopped, "").is_none());
+    }
+}
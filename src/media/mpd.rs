@@ -0,0 +1,200 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use super::{MediaBackend, MediaCommand, MediaError, VOLUME_STEP};
+
+pub const DEFAULT_MPD_ADDRESS: &str = "127.0.0.1:6600";
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+// music player daemon, controlled through its text protocol
+pub struct MpdBackend {
+    address: String,
+}
+
+impl MpdBackend {
+    pub fn new(address: &str) -> MpdBackend {
+        MpdBackend {
+            address: address.to_string(),
+        }
+    }
+
+    fn connect(&self) -> Result<MpdConnection, MediaError> {
+        let unavailable = |error: std::io::Error| MediaError::Unavailable(error.to_string());
+        let address = self
+            .address
+            .to_socket_addrs()
+            .map_err(unavailable)?
+            .next()
+            .ok_or_else(|| MediaError::Unavailable(format!("unknown host {}", self.address)))?;
+        let stream = TcpStream::connect_timeout(&address, TIMEOUT).map_err(unavailable)?;
+        stream
+            .set_read_timeout(Some(TIMEOUT))
+            .map_err(unavailable)?;
+        let mut connection = MpdConnection {
+            reader: BufReader::new(stream.try_clone().map_err(unavailable)?),
+            stream,
+        };
+        // the server greets with "OK MPD <version>"
+        let greeting = connection.read_line()?;
+        if !greeting.starts_with("OK MPD") {
+            return Err(MediaError::Unavailable(format!(
+                "unexpected greeting {}",
+                greeting
+            )));
+        }
+        Ok(connection)
+    }
+}
+
+impl MediaBackend for MpdBackend {
+    fn name(&self) -> &'static str {
+        "mpd"
+    }
+
+    fn is_available(&self) -> bool {
+        self.connect().is_ok()
+    }
+
+    fn send(&mut self, command: MediaCommand) -> Result<(), MediaError> {
+        let mut connection = self.connect()?;
+        let request = match command {
+            MediaCommand::PlayPause => {
+                match connection.status()?.get("state").map(String::as_str) {
+                    Some("play") => "pause 1".to_string(),
+                    Some("pause") => "pause 0".to_string(),
+                    _ => "play".to_string(),
+                }
+            }
+            MediaCommand::Stop => "stop".to_string(),
+            MediaCommand::Next => "next".to_string(),
+            MediaCommand::Previous => "previous".to_string(),
+            MediaCommand::Seek(seconds) => format!("seekcur {:+}", seconds),
+            MediaCommand::VolumeUp | MediaCommand::VolumeDown => {
+                // -1 if the output has no mixer
+                let volume = connection
+                    .status()?
+                    .get("volume")
+                    .and_then(|volume| volume.parse::<i32>().ok())
+                    .filter(|volume| *volume >= 0)
+                    .ok_or_else(|| MediaError::Failed("volume can not be changed".into()))?;
+                let step = if command == MediaCommand::VolumeUp {
+                    VOLUME_STEP as i32
+                } else {
+                    -(VOLUME_STEP as i32)
+                };
+                format!("setvol {}", (volume + step).clamp(0, 100))
+            }
+        };
+        connection.request(&request).map(|_| ())
+    }
+}
+
+struct MpdConnection {
+    reader: BufReader<TcpStream>,
+    stream: TcpStream,
+}
+
+impl MpdConnection {
+    fn read_line(&mut self) -> Result<String, MediaError> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => Err(MediaError::Unavailable("connection closed".into())),
+            Ok(_) => Ok(line.trim_end().to_string()),
+            Err(error) => Err(MediaError::Unavailable(error.to_string())),
+        }
+    }
+
+    // the "key: value" lines of the answer, errors are answered with "ACK [code@index] message"
+    fn request(&mut self, command: &str) -> Result<HashMap<String, String>, MediaError> {
+        self.stream
+            .write_all(format!("{}\n", command).as_bytes())
+            .map_err(|error| MediaError::Unavailable(error.to_string()))?;
+        let mut values = HashMap::new();
+        loop {
+            let line = self.read_line()?;
+            if line == "OK" {
+                return Ok(values);
+            }
+            if let Some(error) = line.strip_prefix("ACK ") {
+                return Err(MediaError::Failed(error.to_string()));
+            }
+            if let Some((key, value)) = line.split_once(": ") {
+                values.insert(key.to_string(), value.to_string());
+            }
+        }
+    }
+
+    fn status(&mut self) -> Result<HashMap<String, String>, MediaError> {
+        self.request("status")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    // answers every command like mpd and returns the received commands
+    fn fake_server(status: &'static str) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"OK MPD 0.23.5\n").unwrap();
+            let reader = BufReader::new(stream.try_clone().unwrap());
+            let mut commands = Vec::new();
+            for line in reader.lines() {
+                let line = line.unwrap();
+                let answer = match line.as_str() {
+                    "status" => format!("{}\nOK\n", status),
+                    "seekcur +600" => "ACK [2@0] {seekcur} Bad song index\n".to_string(),
+                    _ => "OK\n".to_string(),
+                };
+                commands.push(line);
+                stream.write_all(answer.as_bytes()).unwrap();
+            }
+            commands
+        });
+        (address, handle)
+    }
+
+    fn send(status: &'static str, command: MediaCommand) -> (Result<(), MediaError>, Vec<String>) {
+        let (address, handle) = fake_server(status);
+        let result = MpdBackend::new(&address).send(command);
+        (result, handle.join().unwrap())
+    }
+
+    #[test]
+    fn test_commands() {
+        let playing = "volume: 98\nstate: play";
+        assert_eq!(
+            send(playing, MediaCommand::PlayPause),
+            (Ok(()), vec!["status".to_string(), "pause 1".to_string()])
+        );
+        assert_eq!(
+            send("state: stop", MediaCommand::PlayPause).1,
+            vec!["status", "play"]
+        );
+        assert_eq!(
+            send(playing, MediaCommand::VolumeUp).1,
+            vec!["status", "setvol 100"]
+        );
+        assert_eq!(
+            send(playing, MediaCommand::Seek(-10)).1,
+            vec!["seekcur -10"]
+        );
+        assert_eq!(
+            send("volume: -1", MediaCommand::VolumeDown).0,
+            Err(MediaError::Failed("volume can not be changed".into()))
+        );
+        assert_eq!(
+            send(playing, MediaCommand::Seek(600)).0,
+            Err(MediaError::Failed("[2@0] {seekcur} Bad song index".into()))
+        );
+    }
+}
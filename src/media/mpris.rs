@@ -0,0 +1,92 @@
+use std::{io, process::Command};
+
+use super::{MediaBackend, MediaCommand, MediaError, VOLUME_STEP};
+
+// mpris players, the methods are called through playerctl, which has to be installed
+pub struct MprisBackend;
+
+// a missing playerctl looks like a missing player otherwise
+fn spawn_error(error: io::Error) -> MediaError {
+    if error.kind() == io::ErrorKind::NotFound {
+        return MediaError::Unavailable(
+            "playerctl was not found, it is needed to control mpris players".into(),
+        );
+    }
+    MediaError::Unavailable(error.to_string())
+}
+
+// playerctl addresses the most recently active player
+fn arguments(command: MediaCommand) -> Vec<String> {
+    match command {
+        MediaCommand::PlayPause => vec!["play-pause".into()],
+        MediaCommand::Stop => vec!["stop".into()],
+        MediaCommand::Next => vec!["next".into()],
+        MediaCommand::Previous => vec!["previous".into()],
+        MediaCommand::Seek(seconds) => vec![
+            "position".into(),
+            format!(
+                "{}{}",
+                seconds.unsigned_abs(),
+                if seconds < 0 { "-" } else { "+" }
+            ),
+        ],
+        MediaCommand::VolumeUp => vec!["volume".into(), format!("{:.2}+", volume_step())],
+        MediaCommand::VolumeDown => vec!["volume".into(), format!("{:.2}-", volume_step())],
+    }
+}
+
+// mpris volumes range from 0 to 1
+fn volume_step() -> f32 {
+    VOLUME_STEP as f32 / 100.0
+}
+
+impl MediaBackend for MprisBackend {
+    fn name(&self) -> &'static str {
+        "mpris"
+    }
+
+    // playerctl fails without a player
+    fn is_available(&self) -> bool {
+        Command::new("playerctl")
+            .arg("status")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+
+    fn send(&mut self, command: MediaCommand) -> Result<(), MediaError> {
+        let output = Command::new("playerctl")
+            .args(arguments(command))
+            .output()
+            .map_err(spawn_error)?;
+        if !output.status.success() {
+            return Err(MediaError::Failed(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arguments() {
+        assert_eq!(arguments(MediaCommand::PlayPause), vec!["play-pause"]);
+        assert_eq!(arguments(MediaCommand::Seek(-10)), vec!["position", "10-"]);
+        assert_eq!(arguments(MediaCommand::Seek(10)), vec!["position", "10+"]);
+        assert_eq!(arguments(MediaCommand::VolumeDown), vec!["volume", "0.05-"]);
+    }
+
+    #[test]
+    fn test_missing_playerctl() {
+        let error = spawn_error(io::Error::from(io::ErrorKind::NotFound));
+        assert!(error.to_string().contains("playerctl was not found"));
+        assert_eq!(
+            spawn_error(io::Error::other("denied")),
+            MediaError::Unavailable("denied".into())
+        );
+    }
+}
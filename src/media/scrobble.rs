@@ -0,0 +1,313 @@
+use chrono::{DateTime, Utc};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+    time::Duration,
+};
+
+use super::history::{sanitize, Play};
+
+// a track counts once half of it or four minutes have been played
+pub const MAX_PLAY_THRESHOLD: Duration = Duration::from_secs(240);
+// longer gaps between two polls, e.g. after hibernation, are not counted as listening
+const MAX_POLL_GAP: Duration = Duration::from_secs(2);
+// jumping back to the start of the track is a replay, not a seek
+const REPLAY_POSITION: Duration = Duration::from_secs(10);
+
+pub const SCROBBLER_LOG_PATH: &str = "./.scrobbler.log";
+// the time of the last exported play. scrobbling clients delete the log after submitting it,
+// so the log itself can not tell which plays were exported already.
+pub const SCROBBLER_EXPORTED_PATH: &str = "./.scrobbler_exported";
+
+// the current track of the player
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NowPlaying {
+    // identifies the track, e.g. by its path and caption
+    pub id: String,
+    pub artist: String,
+    pub album: String,
+    pub title: String,
+    pub track_number: Option<u16>,
+    pub length: Option<Duration>,
+}
+
+impl NowPlaying {
+    fn threshold(&self) -> Duration {
+        match self.length {
+            Some(length) if !length.is_zero() => (length / 2).min(MAX_PLAY_THRESHOLD),
+            _ => MAX_PLAY_THRESHOLD,
+        }
+    }
+}
+
+// counts the time the current track is actually playing, pauses and seeks are not counted
+#[derive(Default)]
+pub struct PlayTracker {
+    current: Option<NowPlaying>,
+    started: Option<DateTime<Utc>>,
+    last_update: Option<DateTime<Utc>>,
+    last_position: Duration,
+    listened: Duration,
+}
+
+impl PlayTracker {
+    // called for every poll of the player, returns the previous track once it ended and counts.
+    // it is returned at the end, so the play contains the whole time it was listened to.
+    pub fn update(
+        &mut self,
+        track: Option<&NowPlaying>,
+        playing: bool,
+        position: Duration,
+        now: DateTime<Utc>,
+    ) -> Option<Play> {
+        let track = match track {
+            Some(track) if !track.title.is_empty() => track,
+            _ => return self.finish(),
+        };
+        let replayed =
+            position < REPLAY_POSITION && self.last_position >= position + REPLAY_POSITION;
+        let mut play = None;
+        if self.current.as_ref().map(|current| &current.id) != Some(&track.id) || replayed {
+            play = self.finish();
+            self.current = Some(track.clone());
+            self.started = Some(now);
+            self.last_update = Some(now);
+        }
+
+        if playing {
+            let elapsed = self
+                .last_update
+                .and_then(|last_update| (now - last_update).to_std().ok())
+                .unwrap_or_default();
+            self.listened += elapsed.min(MAX_POLL_GAP);
+        }
+        self.last_update = Some(now);
+        self.last_position = position;
+        play
+    }
+
+    // ends the current track, which is a play if it was listened to long enough
+    fn finish(&mut self) -> Option<Play> {
+        let track = self.current.take()?;
+        let listened = std::mem::take(&mut self.listened);
+        if listened < track.threshold() {
+            return None;
+        }
+        Some(Play {
+            time: self.started.unwrap_or_else(Utc::now),
+            artist: track.artist,
+            album: track.album,
+            title: track.title,
+            track_number: track.track_number,
+            length: track.length.filter(|length| !length.is_zero()),
+            listened,
+        })
+    }
+}
+
+// the header of the audioscrobbler portable player log, which scrobbling clients can submit
+fn scrobbler_log_header() -> String {
+    format!(
+        "#AUDIOSCROBBLER/1.1\n#TZ/UTC\n#CLIENT/Awesome Info Display {}\n",
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+// the lines of the plays in the audioscrobbler portable player log
+fn scrobbler_log_lines(plays: &[Play]) -> String {
+    let mut log = String::new();
+    for play in plays {
+        // artist, album, title, track number, length, rating, timestamp and musicbrainz id
+        log.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\tL\t{}\t\n",
+            sanitize(&play.artist),
+            sanitize(&play.album),
+            sanitize(&play.title),
+            play.track_number
+                .map(|number| number.to_string())
+                .unwrap_or_default(),
+            play.length
+                .map(|length| length.as_secs())
+                .unwrap_or_default(),
+            play.time.timestamp()
+        ));
+    }
+    log
+}
+
+// appends the plays after the last export to the log, returns the number of exported plays
+pub fn export_scrobbler_log(
+    plays: &[Play],
+    log_path: &Path,
+    exported_path: &Path,
+) -> std::io::Result<usize> {
+    let last_exported = fs::read_to_string(exported_path)
+        .ok()
+        .and_then(|content| content.trim().parse::<i64>().ok());
+    let new_plays: Vec<Play> = plays
+        .iter()
+        .filter(|play| last_exported.is_none_or(|last| play.time.timestamp() > last))
+        .cloned()
+        .collect();
+    let Some(last_play) = new_plays.iter().map(|play| play.time.timestamp()).max() else {
+        return Ok(0);
+    };
+
+    let new_log = fs::metadata(log_path).map_or(true, |metadata| metadata.len() == 0);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)?;
+    if new_log {
+        file.write_all(scrobbler_log_header().as_bytes())?;
+    }
+    file.write_all(scrobbler_log_lines(&new_plays).as_bytes())?;
+    fs::write(exported_path, last_play.to_string())?;
+    Ok(new_plays.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_dir::TestDir;
+
+    fn track(id: &str, length: Option<u64>) -> NowPlaying {
+        NowPlaying {
+            id: id.into(),
+            artist: "Artist".into(),
+            album: "Album".into(),
+            title: "Title".into(),
+            track_number: Some(2),
+            length: length.map(Duration::from_secs),
+        }
+    }
+
+    // polls every 200 ms for the given seconds and closes the player, returns the play
+    fn poll(
+        tracker: &mut PlayTracker,
+        track: &NowPlaying,
+        playing: bool,
+        start: DateTime<Utc>,
+        seconds: i64,
+    ) -> Option<Play> {
+        for tick in 0..seconds * 5 {
+            let elapsed = chrono::Duration::milliseconds(tick * 200);
+            let position = Duration::from_millis(tick as u64 * 200);
+            assert_eq!(
+                tracker.update(Some(track), playing, position, start + elapsed),
+                None
+            );
+        }
+        let end = start + chrono::Duration::seconds(seconds);
+        tracker.update(None, false, Duration::ZERO, end)
+    }
+
+    fn play(timestamp: i64) -> Play {
+        Play {
+            time: DateTime::from_timestamp(timestamp, 0).unwrap(),
+            artist: "Artist".into(),
+            album: String::new(),
+            title: "Title".into(),
+            track_number: None,
+            length: Some(Duration::from_secs(187)),
+            listened: Duration::from_secs(187),
+        }
+    }
+
+    #[test]
+    fn test_tracks_count_after_half_or_four_minutes() {
+        let start = Utc::now();
+        let short = track("short", Some(100));
+        let mut tracker = PlayTracker::default();
+        assert_eq!(poll(&mut tracker, &short, true, start, 49), None);
+
+        let mut tracker = PlayTracker::default();
+        assert!(poll(&mut tracker, &short, true, start, 60).is_some());
+        // pauses do not count
+        let mut tracker = PlayTracker::default();
+        assert_eq!(poll(&mut tracker, &short, false, start, 100), None);
+
+        // long tracks and streams without length count after four minutes
+        let mut tracker = PlayTracker::default();
+        let stream = track("stream", None);
+        assert_eq!(poll(&mut tracker, &stream, true, start, 239), None);
+        // the play contains the whole time it was listened to
+        let mut tracker = PlayTracker::default();
+        let play = poll(&mut tracker, &track("long", Some(3600)), true, start, 600).unwrap();
+        assert_eq!(play.listened, Duration::from_millis(599_800));
+        assert_eq!(play.time, start);
+    }
+
+    #[test]
+    fn test_gaps_and_replays() {
+        let start = Utc::now();
+        let song = track("song", Some(60));
+        let mut tracker = PlayTracker::default();
+        tracker.update(Some(&song), true, Duration::ZERO, start);
+        // an hour without polls is no listening
+        let later = start + chrono::Duration::hours(1);
+        assert_eq!(
+            tracker.update(Some(&song), true, Duration::from_secs(1), later),
+            None
+        );
+
+        assert!(poll(&mut tracker, &song, true, later, 40).is_some());
+        // the second run of the same track counts again
+        assert!(poll(&mut tracker, &song, true, later, 40).is_some());
+
+        // jumping back to the start ends the play of the first run
+        let mut tracker = PlayTracker::default();
+        for tick in 0..200 {
+            let elapsed = Duration::from_millis(tick * 200);
+            tracker.update(Some(&song), true, elapsed, later + elapsed);
+        }
+        let replay = later + chrono::Duration::seconds(40);
+        let play = tracker.update(Some(&song), true, Duration::ZERO, replay);
+        assert_eq!(play.unwrap().listened, Duration::from_millis(39_800));
+    }
+
+    #[test]
+    fn test_scrobbler_log() {
+        let dir = TestDir::new("scrobbler_log");
+        let log_path = dir.join(".scrobbler.log");
+        let exported_path = dir.join(".scrobbler_exported");
+
+        let plays = vec![play(1715198400), play(1715198700)];
+        assert_eq!(
+            export_scrobbler_log(&plays, &log_path, &exported_path).unwrap(),
+            2
+        );
+        let log = fs::read_to_string(&log_path).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines[0], "#AUDIOSCROBBLER/1.1");
+        assert_eq!(lines[1], "#TZ/UTC");
+        assert_eq!(lines[3], "Artist\t\tTitle\t\t187\tL\t1715198400\t");
+        assert_eq!(lines.len(), 5);
+
+        // exported plays are not exported twice
+        assert_eq!(
+            export_scrobbler_log(&plays, &log_path, &exported_path).unwrap(),
+            0
+        );
+        let plays = vec![play(1715198400), play(1715198700), play(1715199000)];
+        assert_eq!(
+            export_scrobbler_log(&plays, &log_path, &exported_path).unwrap(),
+            1
+        );
+        let log = fs::read_to_string(&log_path).unwrap();
+        assert_eq!(log.lines().count(), 6);
+        assert_eq!(log.matches("#AUDIOSCROBBLER").count(), 1);
+
+        // the log is deleted once it was submitted
+        fs::remove_file(&log_path).unwrap();
+        let plays = vec![play(1715198400), play(1715199300)];
+        assert_eq!(
+            export_scrobbler_log(&plays, &log_path, &exported_path).unwrap(),
+            1
+        );
+        let log = fs::read_to_string(&log_path).unwrap();
+        assert_eq!(log.lines().count(), 4);
+        assert!(log.starts_with("#AUDIOSCROBBLER/1.1"));
+    }
+}
@@ -0,0 +1,94 @@
+use winsafe::{co, msg::WndMsg, HWND};
+
+use super::{MediaBackend, MediaCommand, MediaError, VOLUME_STEP};
+
+// WM_COMMAND ids of the winamp main window
+const PREVIOUS: usize = 40044;
+const PLAY: usize = 40045;
+// toggles between playing and paused
+const PAUSE: usize = 40046;
+const STOP: usize = 40047;
+const NEXT: usize = 40048;
+
+// WM_USER ids of the winamp api
+const IPC_ISPLAYING: isize = 104;
+const IPC_GETOUTPUTTIME: isize = 105;
+const IPC_JUMPTOTIME: isize = 106;
+const IPC_SETVOLUME: isize = 122;
+// IPC_SETVOLUME with this value returns the volume instead
+const QUERY_VOLUME: isize = -666;
+
+pub struct WinampBackend;
+
+fn winamp_window() -> Option<HWND> {
+    HWND::FindWindow(Some(winsafe::AtomStr::from_str("Winamp v1.x")), None)
+        .ok()
+        .flatten()
+}
+
+fn send_user(window: &HWND, wparam: usize, lparam: isize) -> isize {
+    unsafe {
+        window.SendMessage(WndMsg {
+            msg_id: co::WM::USER,
+            wparam,
+            lparam,
+        })
+    }
+}
+
+fn send_wm_command(window: &HWND, id: usize) {
+    unsafe {
+        window.SendMessage(WndMsg {
+            msg_id: co::WM::COMMAND,
+            wparam: id,
+            lparam: 0,
+        });
+    }
+}
+
+impl MediaBackend for WinampBackend {
+    fn name(&self) -> &'static str {
+        "winamp"
+    }
+
+    fn is_available(&self) -> bool {
+        winamp_window().is_some()
+    }
+
+    fn send(&mut self, command: MediaCommand) -> Result<(), MediaError> {
+        let window = winamp_window().ok_or(MediaError::NoPlayer)?;
+        match command {
+            // 1 == playing, 3 == paused, anything else == stopped
+            MediaCommand::PlayPause => match send_user(&window, 0, IPC_ISPLAYING) {
+                1 | 3 => send_wm_command(&window, PAUSE),
+                _ => send_wm_command(&window, PLAY),
+            },
+            MediaCommand::Stop => send_wm_command(&window, STOP),
+            MediaCommand::Next => send_wm_command(&window, NEXT),
+            MediaCommand::Previous => send_wm_command(&window, PREVIOUS),
+            MediaCommand::Seek(seconds) => {
+                let position = send_user(&window, 0, IPC_GETOUTPUTTIME);
+                if position < 0 {
+                    return Err(MediaError::Failed("nothing is playing".into()));
+                }
+                let target = (position + seconds as isize * 1000).max(0);
+                // -1 if not playing, 1 if the target is past the end of the track
+                if send_user(&window, target as usize, IPC_JUMPTOTIME) == -1 {
+                    return Err(MediaError::Failed("nothing is playing".into()));
+                }
+            }
+            MediaCommand::VolumeUp | MediaCommand::VolumeDown => {
+                // the volume ranges from 0 to 255
+                let volume = send_user(&window, QUERY_VOLUME as usize, IPC_SETVOLUME);
+                let step = (VOLUME_STEP as isize * 255 + 50) / 100;
+                let volume = if command == MediaCommand::VolumeUp {
+                    volume + step
+                } else {
+                    volume - step
+                };
+                send_user(&window, volume.clamp(0, 255) as usize, IPC_SETVOLUME);
+            }
+        }
+        Ok(())
+    }
+}
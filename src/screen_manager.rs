@@ -1,7 +1,97 @@
+use std::collections::VecDeque;
 use std::time::Duration;
 use std::time::Instant;
 
 use exchange_format::ExchangeableConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::media::VOLUME_MODE;
+use crate::screens::NORMAL_MODE;
+use crate::SCREEN_REQUESTS;
+
+const SHORT_SWITCH_DURATION: Duration = Duration::from_secs(3);
+pub const PRIORITY_SWITCH_DURATION: Duration = Duration::from_secs(30);
+
+// a screen and one of its modes, requested by keys and the local api
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScreenRequest {
+    pub key: String,
+    pub mode: String,
+    // the duration of the mode, if it should not be the configured one
+    pub duration: Option<Duration>,
+}
+
+impl ScreenRequest {
+    pub fn new(key: &str, mode: &str) -> ScreenRequest {
+        ScreenRequest {
+            key: key.into(),
+            mode: mode.into(),
+            duration: None,
+        }
+    }
+}
+
+// queues the request for the next update of the screens
+pub fn request_screen(request: ScreenRequest) {
+    SCREEN_REQUESTS.0.try_send(request).unwrap_or_default();
+}
+
+// the names of the media keys in the hotkey config, by their virtual key codes
+const MEDIA_KEYS: [(u32, &str); 7] = [
+    (173, "volume_mute"),
+    (174, "volume_down"),
+    (175, "volume_up"),
+    (176, "next_track"),
+    (177, "previous_track"),
+    (178, "stop"),
+    (179, "play_pause"),
+];
+
+// a media key that shows a screen in one of its modes, e.g.
+// {"key": "volume_up", "screen": "media_info_screen", "mode": "volume", "seconds": 5}
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Hotkey {
+    pub key: String,
+    pub screen: String,
+    pub mode: String,
+    // the duration of the mode, if it should not be the configured one
+    #[serde(default)]
+    pub seconds: Option<u64>,
+}
+
+impl Hotkey {
+    // without config, the volume keys show the volume of the media screen and the others the track
+    fn default_for(key: &str) -> Hotkey {
+        let mode = match key {
+            "volume_down" | "volume_up" => VOLUME_MODE,
+            _ => NORMAL_MODE,
+        };
+        Hotkey {
+            key: key.into(),
+            screen: "media_info_screen".into(),
+            mode: mode.into(),
+            seconds: None,
+        }
+    }
+
+    fn request(&self) -> ScreenRequest {
+        ScreenRequest {
+            key: self.screen.clone(),
+            mode: self.mode.clone(),
+            duration: self.seconds.map(Duration::from_secs),
+        }
+    }
+}
+
+// the request of a pressed media key, configured hotkeys take precedence over the defaults
+pub fn hotkey_request(hotkeys: &[Hotkey], key_code: u32) -> Option<ScreenRequest> {
+    let (_, key) = MEDIA_KEYS.iter().find(|(code, _)| *code == key_code)?;
+    let request = match hotkeys.iter().find(|hotkey| hotkey.key == *key) {
+        Some(hotkey) => hotkey.request(),
+        None => Hotkey::default_for(key).request(),
+    };
+    Some(request)
+}
 
 pub struct ScreenManager {
     screens: Vec<Box<dyn super::screens::BasicScreen>>,
@@ -9,6 +99,10 @@ pub struct ScreenManager {
     timeout: Option<std::time::Instant>,
     last_screen: usize,
     switch_in_progress: bool,
+    switch_duration: Duration,
+    raised: bool,
+    // screens that asked to be shown, in the order of their requests
+    priority_queue: VecDeque<usize>,
 }
 
 impl ScreenManager {
@@ -19,6 +113,9 @@ impl ScreenManager {
             timeout: Some(Instant::now()),
             last_screen: 0,
             switch_in_progress: false,
+            switch_duration: SHORT_SWITCH_DURATION,
+            raised: false,
+            priority_queue: VecDeque::new(),
         };
 
         if !this.screens[this.current].enabled() {
@@ -35,9 +132,8 @@ impl ScreenManager {
 
     pub fn current_screen(&mut self) -> &mut Box<dyn super::screens::BasicScreen> {
         if self.screens.get(self.current).is_some() {
-            let seconds = Duration::from_secs(3);
             if self.switch_in_progress
-                && self.timeout.unwrap_or(Instant::now()).elapsed() >= seconds
+                && self.timeout.unwrap_or(Instant::now()).elapsed() >= self.switch_duration
             {
                 self.screens[self.current].update();
                 self.current = self.last_screen;
@@ -51,9 +147,16 @@ impl ScreenManager {
         panic!("No current screen!");
     }
 
+    // how often the current screen wants to be redrawn. unlike current_screen,
+    // the screen is neither started nor switched back.
+    pub fn refresh_interval(&self) -> Duration {
+        self.screens[self.current].refresh_interval()
+    }
+
     pub fn next_screen(&mut self) {
         self.current_screen().stop();
         self.switch_in_progress = false;
+        self.raised = false;
         self.find_next_enabled_screen();
         self.current_screen().start();
     }
@@ -61,32 +164,105 @@ impl ScreenManager {
     pub fn previous_screen(&mut self) {
         self.current_screen().stop();
         self.switch_in_progress = false;
+        self.raised = false;
         self.find_previous_enabled_screen();
         self.current_screen().start();
     }
 
     pub fn update_current_screen(&mut self) {
+        while let Ok(request) = SCREEN_REQUESTS.1.try_recv() {
+            self.show_screen_mode(&request.key, &request.mode, request.duration);
+        }
+        self.handle_priority_requests();
         self.current_screen().update();
     }
 
-    pub fn set_screen_for_short(&mut self, key: String, mode: u32) {
-        let index: usize = match self.screens.iter_mut().position(|r| *r.key() == key) {
+    // screens may ask to be shown for a while, e.g. the agenda before a meeting.
+    // requests at the same time are shown one after the other.
+    fn handle_priority_requests(&mut self) {
+        for (index, screen) in self.screens.iter_mut().enumerate() {
+            if screen.priority_request() && !self.priority_queue.contains(&index) {
+                self.priority_queue.push_back(index);
+            }
+        }
+
+        // the next one waits until the current switch is over
+        if self.switch_in_progress || self.raised {
+            return;
+        }
+        if let Some(index) = self.priority_queue.pop_front() {
+            let key = self.screens[index].key();
+            self.show_screen_for(&key, NORMAL_MODE, PRIORITY_SWITCH_DURATION);
+        }
+    }
+
+    // shows the mode for its configured duration, the normal mode for a few seconds
+    pub fn show_screen_mode(&mut self, key: &str, mode: &str, duration: Option<Duration>) -> bool {
+        let duration = match duration {
+            Some(duration) => duration,
+            None => self
+                .screens
+                .iter_mut()
+                .find_map(|r| (r.key() == key).then(|| r.mode_duration(mode)))
+                .flatten()
+                .unwrap_or(SHORT_SWITCH_DURATION),
+        };
+        self.show_screen_for(key, mode, duration)
+    }
+
+    // shows the screen in the mode for the given duration and switches back to the previous
+    // screen afterwards. returns false, if the screen or its mode can not be shown.
+    pub fn show_screen_for(&mut self, key: &str, mode: &str, duration: Duration) -> bool {
+        let index: usize = match self.screens.iter_mut().position(|r| r.key() == key) {
             Some(idx) => idx,
-            None => return,
+            None => return false,
         };
 
-        if !self.screens[index].enabled() {
-            return;
+        if !self.screens[index].enabled() || self.raised {
+            return false;
+        }
+        if !self.screens[index].set_mode(mode, duration) {
+            return false;
         }
         self.timeout = Some(Instant::now());
+        self.switch_duration = duration;
         if !self.switch_in_progress {
             self.current_screen().stop();
             self.last_screen = self.current;
         }
         self.current = index;
-        self.current_screen().set_mode(mode); // right now, volume mode for 3 seconds for media screen
         self.current_screen().start();
         self.switch_in_progress = true;
+        true
+    }
+
+    // shows the screen until it is released again, e.g. while an alert is pending
+    pub fn raise_screen(&mut self, key: &str) {
+        let index: usize = match self.screens.iter_mut().position(|r| r.key() == key) {
+            Some(idx) => idx,
+            None => return,
+        };
+        if self.raised {
+            return;
+        }
+        if !self.switch_in_progress {
+            self.last_screen = self.current;
+        }
+        self.screens[self.current].stop();
+        self.switch_in_progress = false;
+        self.raised = true;
+        self.current = index;
+        self.screens[self.current].start();
+    }
+
+    pub fn release_raised_screen(&mut self) {
+        if !self.raised {
+            return;
+        }
+        self.screens[self.current].stop();
+        self.raised = false;
+        self.current = self.last_screen;
+        self.screens[self.current].start();
     }
 
     fn find_previous_enabled_screen(&mut self) {
@@ -157,7 +333,7 @@ impl ScreenManager {
 
 #[cfg(test)]
 mod tests {
-    use crate::screens::{BasicScreen, Screen};
+    use crate::screens::{BasicScreen, Screen, ScreenMode};
 
     use super::*;
     use exchange_format::ExchangeableConfig;
@@ -183,8 +359,6 @@ mod tests {
 
         fn update(&mut self) {}
 
-        fn set_mode(&mut self, _mode: u32) {}
-
         fn set_status(&mut self, _status: bool) {}
 
         fn description(&mut self) -> String {
@@ -199,6 +373,38 @@ mod tests {
         }
     }
 
+    // asks once to be shown
+    struct PriorityMockScreen {
+        key: String,
+        requested: bool,
+        screen: Screen,
+    }
+
+    impl super::super::screens::BasicScreen for PriorityMockScreen {
+        fn key(&mut self) -> String {
+            self.key.clone()
+        }
+
+        fn enabled(&mut self) -> bool {
+            true
+        }
+
+        fn start(&mut self) {}
+
+        fn stop(&mut self) {}
+
+        fn update(&mut self) {}
+
+        fn priority_request(&mut self) -> bool {
+            std::mem::take(&mut self.requested)
+        }
+    }
+    impl super::super::screens::Screenable for PriorityMockScreen {
+        fn get_screen(&mut self) -> &mut Screen {
+            &mut self.screen
+        }
+    }
+
     #[test]
     fn test_create_screen_manager_with_enabled_screen() {
         let screens: Vec<Box<dyn BasicScreen>> = vec![
@@ -306,4 +512,160 @@ mod tests {
         screen_manager.previous_screen();
         assert_eq!(screen_manager.current, 0);
     }
+
+    #[test]
+    fn test_raise_and_release_screen() {
+        let screens: Vec<Box<dyn BasicScreen>> = vec![
+            Box::new(MockScreen {
+                key: String::from("screen1"),
+                enabled: true,
+                screen: Screen::default(),
+            }),
+            Box::new(MockScreen {
+                key: String::from("screen2"),
+                enabled: true,
+                screen: Screen::default(),
+            }),
+            Box::new(MockScreen {
+                key: String::from("screen3"),
+                enabled: true,
+                screen: Screen::default(),
+            }),
+        ];
+
+        let mut screen_manager = ScreenManager::new(screens);
+        screen_manager.next_screen();
+        screen_manager.raise_screen("screen3");
+        assert_eq!(screen_manager.current, 2);
+
+        // short switches do not replace a raised screen
+        assert!(!screen_manager.show_screen_mode("screen1", NORMAL_MODE, None));
+        assert_eq!(screen_manager.current, 2);
+
+        screen_manager.release_raised_screen();
+        assert_eq!(screen_manager.current, 1);
+    }
+
+    #[test]
+    fn test_priority_request_switches_screen() {
+        let screens: Vec<Box<dyn BasicScreen>> = vec![
+            Box::new(MockScreen {
+                key: String::from("screen1"),
+                enabled: true,
+                screen: Screen::default(),
+            }),
+            Box::new(PriorityMockScreen {
+                key: String::from("priority1"),
+                requested: true,
+                screen: Screen::default(),
+            }),
+            Box::new(PriorityMockScreen {
+                key: String::from("priority2"),
+                requested: true,
+                screen: Screen::default(),
+            }),
+        ];
+
+        let mut screen_manager = ScreenManager::new(screens);
+        screen_manager.update_current_screen();
+        assert_eq!(screen_manager.current, 1);
+        assert_eq!(screen_manager.switch_duration, PRIORITY_SWITCH_DURATION);
+
+        // the second request waits for the first one
+        screen_manager.update_current_screen();
+        assert_eq!(screen_manager.current, 1);
+        screen_manager.timeout = Some(Instant::now() - PRIORITY_SWITCH_DURATION);
+        screen_manager.update_current_screen();
+        screen_manager.update_current_screen();
+        assert_eq!(screen_manager.current, 2);
+
+        // back to the previous screen, once the time is up
+        screen_manager.timeout = Some(Instant::now() - PRIORITY_SWITCH_DURATION);
+        screen_manager.update_current_screen();
+        assert_eq!(screen_manager.current, 0);
+    }
+
+    #[test]
+    fn test_show_screen_mode() {
+        let pages = Screen {
+            modes: vec![ScreenMode::new("details", Duration::from_secs(10))],
+            ..Default::default()
+        };
+        let screens: Vec<Box<dyn BasicScreen>> = vec![
+            Box::new(MockScreen {
+                key: String::from("screen1"),
+                enabled: true,
+                screen: Screen::default(),
+            }),
+            Box::new(MockScreen {
+                key: String::from("screen2"),
+                enabled: true,
+                screen: pages,
+            }),
+        ];
+
+        let mut screen_manager = ScreenManager::new(screens);
+
+        // unknown modes and screens are ignored
+        assert!(!screen_manager.show_screen_mode("screen2", "volume", None));
+        assert!(!screen_manager.show_screen_mode("screen3", NORMAL_MODE, None));
+        assert_eq!(screen_manager.current, 0);
+
+        // the declared duration
+        assert!(screen_manager.show_screen_mode("screen2", "details", None));
+        assert_eq!(screen_manager.current, 1);
+        assert_eq!(screen_manager.switch_duration, Duration::from_secs(10));
+        assert!(screen_manager
+            .current_screen()
+            .get_screen()
+            .in_mode("details"));
+
+        // an explicit duration, the normal mode ends the details
+        assert!(screen_manager.show_screen_mode(
+            "screen2",
+            NORMAL_MODE,
+            Some(Duration::from_secs(5))
+        ));
+        assert_eq!(screen_manager.switch_duration, Duration::from_secs(5));
+        assert!(screen_manager
+            .current_screen()
+            .get_screen()
+            .in_mode(NORMAL_MODE));
+
+        // the mode falls back to normal, once its time is up
+        assert!(screen_manager.show_screen_for("screen2", "details", Duration::ZERO));
+        assert!(screen_manager.screens[1].get_screen().in_mode(NORMAL_MODE));
+    }
+
+    #[test]
+    fn test_hotkey_request() {
+        // the media keys show the media screen by default
+        assert_eq!(
+            hotkey_request(&[], 175),
+            Some(ScreenRequest::new("media_info_screen", VOLUME_MODE))
+        );
+        assert_eq!(
+            hotkey_request(&[], 179),
+            Some(ScreenRequest::new("media_info_screen", NORMAL_MODE))
+        );
+        assert_eq!(hotkey_request(&[], 180), None);
+
+        // configured keys select a mode of any screen by name
+        let hotkeys: Vec<Hotkey> = serde_json::from_str(
+            r#"[{"key": "next_track", "screen": "plugin", "mode": "lyrics", "seconds": 5}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            hotkey_request(&hotkeys, 176),
+            Some(ScreenRequest {
+                key: "plugin".into(),
+                mode: "lyrics".into(),
+                duration: Some(Duration::from_secs(5)),
+            })
+        );
+        assert_eq!(
+            hotkey_request(&hotkeys, 174),
+            Some(ScreenRequest::new("media_info_screen", VOLUME_MODE))
+        );
+    }
 }
@@ -0,0 +1,386 @@
+use crate::{
+    calendar::{format_until, load_events, occurrences, Occurrence},
+    config_manager::{integer_value, string_value, ConfigManager},
+    helpers::{drawing::draw_text, layout::Layout},
+    screens::{BasicScreen, Screen, Screenable},
+    DEVICES,
+};
+use ab_glyph::FontArc;
+use chrono::{DateTime, Local, Utc};
+use crossbeam_channel::{bounded, Receiver};
+use exchange_format::{ConfigParam, ExchangeableConfig};
+use image::{ImageBuffer, Rgb, RgbImage};
+use imageproc::drawing::{draw_line_segment_mut, text_size};
+use indexmap::IndexMap;
+use std::{
+    collections::HashSet,
+    sync::{atomic::AtomicBool, atomic::Ordering, Arc, RwLock},
+    thread,
+    time::{Duration, Instant},
+};
+
+const DEFAULT_ALERT_MINUTES: u32 = 5;
+const DEFAULT_DAYS: u32 = 7;
+const RELOAD_INTERVAL: Duration = Duration::from_secs(60);
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+pub struct AgendaScreen {
+    screen: Screen,
+    receiver: Receiver<Arc<Vec<Occurrence>>>,
+    occurrences: Arc<Vec<Occurrence>>,
+    // set by the background thread, when an event is about to start
+    priority: Arc<AtomicBool>,
+    last_minute: i64,
+}
+
+impl Screenable for AgendaScreen {
+    fn get_screen(&mut self) -> &mut Screen {
+        &mut self.screen
+    }
+}
+
+impl BasicScreen for AgendaScreen {
+    fn update(&mut self) {
+        let mut redraw = false;
+        if let Ok(occurrences) = self.receiver.try_recv() {
+            self.occurrences = occurrences;
+            redraw = true;
+        }
+
+        // "in 12 min" has to be updated, even if the calendar did not change
+        let minute = Utc::now().timestamp() / 60;
+        if minute != self.last_minute {
+            self.last_minute = minute;
+            redraw = true;
+        }
+
+        if redraw {
+            self.draw_screen();
+        }
+    }
+
+    fn priority_request(&mut self) -> bool {
+        self.priority.swap(false, Ordering::AcqRel)
+    }
+}
+
+impl AgendaScreen {
+    #[allow(clippy::too_many_arguments)]
+    fn draw_text(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+        text: &str,
+        x: i32,
+        y: i32,
+        size: f32,
+        color: Rgb<u8>,
+    ) {
+        draw_text(
+            image,
+            &self.screen.font,
+            text,
+            layout.x(x),
+            layout.y(y),
+            layout.scale(size),
+            color,
+        );
+    }
+
+    // in the coordinates of the reference resolution
+    fn text_width(&self, layout: &Layout, text: &str, size: f32) -> i32 {
+        layout.reference_width(text_size(layout.scale(size), &self.screen.font, text).0)
+    }
+
+    // cuts the text, so that it fits into the given width
+    fn fit_text(&self, layout: &Layout, text: &str, size: f32, max_width: i32) -> String {
+        if self.text_width(layout, text, size) <= max_width {
+            return text.to_string();
+        }
+        let mut chars: Vec<char> = text.chars().collect();
+        while !chars.is_empty() {
+            chars.pop();
+            let shortened = format!("{}...", chars.iter().collect::<String>().trim_end());
+            if self.text_width(layout, &shortened, size) <= max_width {
+                return shortened;
+            }
+        }
+        String::new()
+    }
+
+    // "09:30", "Tue 09:30" or "Tue" for all day events, that do not start today
+    fn start_label(occurrence: &Occurrence, now: &DateTime<Local>) -> String {
+        let start = occurrence.start.with_timezone(&Local);
+        let today = start.date_naive() == now.date_naive();
+        match (occurrence.all_day, today) {
+            (true, true) => "Today".into(),
+            (true, false) => start.format("%a").to_string(),
+            (false, true) => start.format("%H:%M").to_string(),
+            (false, false) => start.format("%a %H:%M").to_string(),
+        }
+    }
+
+    // the next event with a start time, that is still ahead or currently running
+    fn next_up(&self) -> Option<&Occurrence> {
+        let now = Utc::now();
+        self.occurrences
+            .iter()
+            .find(|occurrence| !occurrence.all_day && occurrence.end > now)
+    }
+
+    // finished events are dropped, the data of the background thread may be a few seconds old
+    fn remaining(&self, next: Option<&Occurrence>) -> Vec<&Occurrence> {
+        let now = Utc::now();
+        self.occurrences
+            .iter()
+            .filter(|occurrence| occurrence.end > now && Some(*occurrence) != next)
+            .collect()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_list(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+        occurrences: &[&Occurrence],
+        (width, height): (i32, i32),
+        top: i32,
+        font_size: f32,
+        row_height: i32,
+    ) {
+        let white = Rgb([255u8, 255u8, 255u8]);
+        let gray = Rgb([160u8, 160u8, 160u8]);
+        let now = Local::now();
+        let rows = ((height - top) / row_height).max(0) as usize;
+        let label_width = self.text_width(layout, "Mon 00:00 ", font_size);
+
+        for (index, occurrence) in occurrences.iter().take(rows).enumerate() {
+            let y = top + index as i32 * row_height;
+            let label = Self::start_label(occurrence, &now);
+            self.draw_text(image, layout, &label, 2, y, font_size, gray);
+            let summary = self.fit_text(
+                layout,
+                &occurrence.summary,
+                font_size,
+                width - label_width - 4,
+            );
+            self.draw_text(
+                image,
+                layout,
+                &summary,
+                label_width + 2,
+                y,
+                font_size,
+                white,
+            );
+        }
+    }
+
+    // laid out for 256x64
+    fn draw_compact(&self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, layout: &Layout) {
+        let white = Rgb([255u8, 255u8, 255u8]);
+        let width = 256;
+        let now = Utc::now();
+        let today = Local::now().date_naive();
+
+        let next = self.next_up();
+        let top = match next {
+            Some(next) => {
+                let until = format!("Next up {}", format_until(now, next.start, today));
+                let until_width = self.text_width(layout, &until, 16.0);
+                self.draw_text(
+                    image,
+                    layout,
+                    &until,
+                    width - until_width - 2,
+                    0,
+                    16.0,
+                    white,
+                );
+                let summary = self.fit_text(layout, &next.summary, 16.0, width - until_width - 8);
+                self.draw_text(image, layout, &summary, 2, 0, 16.0, white);
+                18
+            }
+            None => 0,
+        };
+
+        let rest = self.remaining(next);
+        if rest.is_empty() && next.is_none() {
+            self.draw_text(image, layout, "No upcoming events", 2, 0, 16.0, white);
+            return;
+        }
+        self.draw_list(image, layout, &rest, (width, 64), top, 14.0, 15);
+    }
+
+    // laid out for 320x170
+    fn draw_full(&self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, layout: &Layout) {
+        let white = Rgb([255u8, 255u8, 255u8]);
+        let gray = Rgb([160u8, 160u8, 160u8]);
+        let accent = Rgb([90u8, 180u8, 250u8]);
+        let width = 320;
+        let now = Utc::now();
+        let today = Local::now().date_naive();
+
+        self.draw_text(image, layout, "Agenda", 4, 2, 22.0, white);
+        self.draw_separator(image, layout, 27);
+
+        let next = self.next_up();
+        let top = match next {
+            Some(next) => {
+                let start = next.start.with_timezone(&Local).format("%H:%M");
+                let end = next.end.with_timezone(&Local).format("%H:%M");
+                let until = format!(
+                    "Next up {} ({} - {})",
+                    format_until(now, next.start, today),
+                    start,
+                    end
+                );
+                self.draw_text(image, layout, &until, 4, 32, 18.0, accent);
+                let summary = self.fit_text(layout, &next.summary, 24.0, width - 8);
+                self.draw_text(image, layout, &summary, 4, 52, 24.0, white);
+                if let Some(location) = &next.location {
+                    let location = self.fit_text(layout, location, 16.0, width - 8);
+                    self.draw_text(image, layout, &location, 4, 78, 16.0, gray);
+                }
+                self.draw_separator(image, layout, 98);
+                102
+            }
+            None => 32,
+        };
+
+        let rest = self.remaining(next);
+        if rest.is_empty() && next.is_none() {
+            self.draw_text(image, layout, "No upcoming events", 4, top, 20.0, white);
+            return;
+        }
+        self.draw_list(image, layout, &rest, (width, 170), top, 18.0, 22);
+    }
+
+    fn draw_separator(&self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, layout: &Layout, y: i32) {
+        let y = layout.y(y) as f32;
+        draw_line_segment_mut(
+            image,
+            (0.0, y),
+            (layout.width() as f32, y),
+            Rgb([90, 90, 90]),
+        );
+    }
+
+    fn draw_screen(&mut self) {
+        for (key, device) in DEVICES.iter() {
+            let layout = Layout::for_display(device.screen_width(), device.screen_height());
+            let mut image = RgbImage::new(layout.width(), layout.height());
+            if layout.compact() {
+                self.draw_compact(&mut image, &layout);
+            } else {
+                self.draw_full(&mut image, &layout);
+            }
+            self.screen
+                .device_screen_bytes
+                .insert(key.clone(), image.into_vec());
+        }
+    }
+
+    pub fn new(
+        description: String,
+        key: String,
+        font: FontArc,
+        config_manager: Arc<RwLock<ConfigManager>>,
+    ) -> AgendaScreen {
+        let (tx, rx) = bounded::<Arc<Vec<Occurrence>>>(1);
+        let priority = Arc::new(AtomicBool::new(false));
+
+        let mut params = IndexMap::new();
+        params.insert("calendar_paths".into(), ConfigParam::String(String::new()));
+        params.insert(
+            "alert_minutes".into(),
+            ConfigParam::Integer(DEFAULT_ALERT_MINUTES),
+        );
+        params.insert("days".into(), ConfigParam::Integer(DEFAULT_DAYS));
+
+        let screen = Screen {
+            description,
+            key: key.clone(),
+            font,
+            config_manager: config_manager.clone(),
+            config_layout: ExchangeableConfig { params },
+            // the thread keeps running while the screen is inactive, to be able to request the display
+            handle: Some(thread::spawn({
+                let priority = priority.clone();
+                move || {
+                    let sender = tx;
+                    let mut paths = String::new();
+                    let mut days = 0;
+                    // the recurrences are expanded once per reload, the range ends a reload
+                    // interval later, so that it still covers the configured days until then
+                    let mut expanded: Vec<Occurrence> = Vec::new();
+                    let mut last_load: Option<Instant> = None;
+                    // events, that already requested the display
+                    let mut announced: HashSet<(String, DateTime<Utc>)> = HashSet::new();
+
+                    loop {
+                        let current_paths =
+                            string_value(&config_manager, &key, "calendar_paths", "");
+                        let current_days =
+                            integer_value(&config_manager, &key, "days", DEFAULT_DAYS).max(1);
+                        let now = Utc::now();
+                        if current_paths != paths
+                            || current_days != days
+                            || !matches!(last_load, Some(last) if last.elapsed() < RELOAD_INTERVAL)
+                        {
+                            let events = load_events(&current_paths);
+                            let until = now
+                                + chrono::Duration::days(current_days as i64)
+                                + chrono::Duration::from_std(RELOAD_INTERVAL).unwrap_or_default();
+                            expanded = occurrences(&events, now, until);
+                            paths = current_paths;
+                            days = current_days;
+                            last_load = Some(Instant::now());
+                        }
+
+                        let alert_minutes = integer_value(
+                            &config_manager,
+                            &key,
+                            "alert_minutes",
+                            DEFAULT_ALERT_MINUTES,
+                        );
+                        let until = now + chrono::Duration::days(days as i64);
+                        let upcoming: Vec<Occurrence> = expanded
+                            .iter()
+                            .filter(|occurrence| occurrence.end > now && occurrence.start < until)
+                            .cloned()
+                            .collect();
+
+                        // 0 disables the switch to the agenda
+                        let alert_before = chrono::Duration::minutes(alert_minutes as i64);
+                        announced.retain(|(_, start)| *start > now);
+                        for occurrence in upcoming.iter().filter(|occurrence| {
+                            !occurrence.all_day
+                                && occurrence.start > now
+                                && occurrence.start - now <= alert_before
+                        }) {
+                            if announced.insert((occurrence.summary.clone(), occurrence.start)) {
+                                priority.store(true, Ordering::Release);
+                            }
+                        }
+
+                        let _ = sender.try_send(Arc::new(upcoming));
+                        thread::sleep(CHECK_INTERVAL);
+                    }
+                }
+            })),
+            ..Default::default()
+        };
+
+        let mut this = AgendaScreen {
+            screen,
+            receiver: rx,
+            occurrences: Arc::new(Vec::new()),
+            priority,
+            last_minute: 0,
+        };
+
+        this.draw_screen(); // initial draw
+        this
+    }
+}
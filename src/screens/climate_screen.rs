@@ -0,0 +1,442 @@
+use crate::{
+    climate::{
+        advice, celsius_to_fahrenheit,
+        history::{ClimateHistory, HISTORY_PATH},
+        trend, ClimateReading, ComfortThresholds, Trend, DEFAULT_HUMIDITY_MAX,
+        DEFAULT_HUMIDITY_MIN, DEFAULT_TEMPERATURE_MAX, DEFAULT_TEMPERATURE_MIN,
+    },
+    config_manager::{integer_value, string_value, ConfigManager},
+    helpers::{
+        drawing::draw_text,
+        graph::{draw_graph, Graph, GraphStyle},
+        layout::Layout,
+    },
+    screens::{BasicScreen, Screen, Screenable},
+    weather::weather::UnitSystem,
+    DEVICES, SENSORS,
+};
+use ab_glyph::FontArc;
+use chrono::Utc;
+use crossbeam_channel::{bounded, Receiver};
+use exchange_format::{ConfigParam, ExchangeableConfig};
+use image::{ImageBuffer, Rgb, RgbImage};
+use imageproc::drawing::{draw_line_segment_mut, text_size};
+use indexmap::IndexMap;
+use std::{
+    path::Path,
+    sync::{Arc, RwLock},
+    thread,
+    time::Duration,
+};
+
+const DEFAULT_HISTORY_HOURS: u32 = 24;
+// the sensor thread of the device reads every two seconds
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// changes within an hour, that are shown as a trend
+const TEMPERATURE_TREND: f64 = 0.5;
+const HUMIDITY_TREND: f64 = 3.0;
+const PRESSURE_TREND: f64 = 1.0;
+
+#[derive(Default)]
+struct ClimateSnapshot {
+    reading: Option<ClimateReading>,
+    // the readings of the configured history hours, one per minute
+    history: Vec<ClimateReading>,
+}
+
+pub struct ClimateScreen {
+    screen: Screen,
+    receiver: Receiver<Arc<ClimateSnapshot>>,
+    snapshot: Arc<ClimateSnapshot>,
+}
+
+impl Screenable for ClimateScreen {
+    fn get_screen(&mut self) -> &mut Screen {
+        &mut self.screen
+    }
+}
+
+impl BasicScreen for ClimateScreen {
+    fn update(&mut self) {
+        if let Ok(snapshot) = self.receiver.try_recv() {
+            self.snapshot = snapshot;
+            self.draw_screen();
+        }
+    }
+}
+
+impl ClimateScreen {
+    // the text at a position of the reference resolution
+    fn draw_text(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+        text: &str,
+        (x, y): (i32, i32),
+        size: f32,
+        color: Rgb<u8>,
+    ) {
+        draw_text(
+            image,
+            &self.screen.font,
+            text,
+            layout.x(x),
+            layout.y(y),
+            layout.scale(size),
+            color,
+        );
+    }
+
+    fn unit_system(&self) -> UnitSystem {
+        UnitSystem::from_key(&string_value(
+            &self.screen.config_manager,
+            &self.screen.key,
+            "unit_system",
+            "",
+        ))
+    }
+
+    fn thresholds(&self) -> ComfortThresholds {
+        let config_manager = &self.screen.config_manager;
+        let key = &self.screen.key;
+        ComfortThresholds {
+            humidity_min: integer_value(config_manager, key, "humidity_min", DEFAULT_HUMIDITY_MIN)
+                as f64,
+            humidity_max: integer_value(config_manager, key, "humidity_max", DEFAULT_HUMIDITY_MAX)
+                as f64,
+            temperature_min: integer_value(
+                config_manager,
+                key,
+                "temperature_min",
+                DEFAULT_TEMPERATURE_MIN,
+            ) as f64,
+            temperature_max: integer_value(
+                config_manager,
+                key,
+                "temperature_max",
+                DEFAULT_TEMPERATURE_MAX,
+            ) as f64,
+        }
+    }
+
+    // the current reading, unless the sensor stopped sending
+    fn current_reading(&self) -> Option<ClimateReading> {
+        self.snapshot
+            .reading
+            .filter(|reading| reading.is_recent(Utc::now()))
+    }
+
+    fn format_temperature(temperature: f64, unit_system: UnitSystem) -> String {
+        let temperature = match unit_system {
+            UnitSystem::Metric => temperature,
+            UnitSystem::Imperial => celsius_to_fahrenheit(temperature),
+        };
+        format!("{:.1}{}", temperature, unit_system.temperature_label())
+    }
+
+    fn arrow(trend: Option<Trend>) -> &'static str {
+        trend.map(|trend| trend.arrow()).unwrap_or_default()
+    }
+
+    // "Ventilate, Too warm" or "Comfortable"
+    fn advice_line(&self, reading: &ClimateReading) -> (String, Rgb<u8>) {
+        let advice = advice(reading, &self.thresholds());
+        if advice.is_empty() {
+            return ("Comfortable".into(), Rgb([80u8, 200u8, 120u8]));
+        }
+        (
+            advice
+                .iter()
+                .map(|advice| advice.message())
+                .collect::<Vec<&str>>()
+                .join(", "),
+            Rgb([250u8, 160u8, 40u8]),
+        )
+    }
+
+    fn draw_history(
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        values: &[f64],
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        color: Rgb<u8>,
+    ) {
+        let graph = Graph {
+            x,
+            y,
+            width,
+            height,
+            style: GraphStyle::Area,
+            color,
+            range: None,
+            min_max_markers: false,
+        };
+        let capacity = values.len().max(width as usize);
+        draw_graph(image, &graph, values, capacity);
+    }
+
+    fn draw_compact(&self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, layout: &Layout) {
+        let white = Rgb([255u8, 255u8, 255u8]);
+        let gray = Rgb([160u8, 160u8, 160u8]);
+        let reading = match self.current_reading() {
+            Some(reading) => reading,
+            None => {
+                self.draw_text(image, layout, "No sensor data", (2, 0), 16.0, white);
+                return;
+            }
+        };
+        let history = &self.snapshot.history;
+        let unit_system = self.unit_system();
+
+        let temperature = format!(
+            "{} {}",
+            Self::format_temperature(reading.temperature, unit_system),
+            Self::arrow(trend(
+                history,
+                &reading,
+                |reading| Some(reading.temperature),
+                TEMPERATURE_TREND
+            ))
+        );
+        self.draw_text(image, layout, &temperature, (2, 0), 28.0, white);
+
+        let humidity = format!(
+            "{:.0}% {}",
+            reading.humidity,
+            Self::arrow(trend(
+                history,
+                &reading,
+                |reading| Some(reading.humidity),
+                HUMIDITY_TREND
+            ))
+        );
+        self.draw_text(image, layout, &humidity, (2, 30), 16.0, gray);
+
+        let (advice, color) = self.advice_line(&reading);
+        self.draw_text(image, layout, &advice, (2, 48), 14.0, color);
+
+        // the temperature of the last hours on the right side
+        let values: Vec<f64> = history.iter().map(|reading| reading.temperature).collect();
+        let (temperature_width, _) = text_size(layout.scale(28.0), &self.screen.font, &temperature);
+        let left = layout.x(130).max(temperature_width as i32 + layout.x(8));
+        let width = (layout.width() as i32 - left - layout.x(2)).max(1) as u32;
+        Self::draw_history(
+            image,
+            &values,
+            left,
+            layout.y(4),
+            width,
+            layout.height().saturating_sub(layout.h(8)).max(1),
+            Rgb([250u8, 120u8, 60u8]),
+        );
+    }
+
+    fn draw_full(&self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, layout: &Layout) {
+        let white = Rgb([255u8, 255u8, 255u8]);
+        let gray = Rgb([160u8, 160u8, 160u8]);
+        let width = layout.width() as i32;
+
+        self.draw_text(image, layout, "Indoor climate", (4, 2), 22.0, white);
+        let line_y = layout.y(27) as f32;
+        draw_line_segment_mut(
+            image,
+            (0.0, line_y),
+            (width as f32, line_y),
+            Rgb([90, 90, 90]),
+        );
+
+        let reading = match self.current_reading() {
+            Some(reading) => reading,
+            None => {
+                self.draw_text(image, layout, "No sensor data", (4, 32), 20.0, white);
+                return;
+            }
+        };
+        let history = &self.snapshot.history;
+        let unit_system = self.unit_system();
+
+        let rows = [
+            (
+                "Temperature",
+                Some(Self::format_temperature(reading.temperature, unit_system)),
+                trend(
+                    history,
+                    &reading,
+                    |reading| Some(reading.temperature),
+                    TEMPERATURE_TREND,
+                ),
+            ),
+            (
+                "Humidity",
+                Some(format!("{:.0}%", reading.humidity)),
+                trend(
+                    history,
+                    &reading,
+                    |reading| Some(reading.humidity),
+                    HUMIDITY_TREND,
+                ),
+            ),
+            (
+                "Pressure",
+                reading
+                    .pressure
+                    .map(|pressure| format!("{:.0} hPa", pressure)),
+                trend(
+                    history,
+                    &reading,
+                    |reading| reading.pressure,
+                    PRESSURE_TREND,
+                ),
+            ),
+        ];
+        let mut y = 32;
+        for (label, value, trend) in rows.iter() {
+            // sensors without pressure skip the row
+            if let Some(value) = value {
+                self.draw_text(image, layout, label, (4, y), 18.0, gray);
+                self.draw_text(
+                    image,
+                    layout,
+                    &format!("{} {}", value, Self::arrow(*trend)),
+                    (110, y),
+                    18.0,
+                    white,
+                );
+                y += 22;
+            }
+        }
+
+        // temperature and humidity of the last hours side by side
+        let top = layout.y(y + 4);
+        let graph_height = (layout.height() as i32 - top - layout.y(26)).max(1) as u32;
+        let graph_width = (width / 2 - layout.x(8)).max(1) as u32;
+        let temperatures: Vec<f64> = history.iter().map(|reading| reading.temperature).collect();
+        let humidities: Vec<f64> = history.iter().map(|reading| reading.humidity).collect();
+        Self::draw_history(
+            image,
+            &temperatures,
+            layout.x(4),
+            top,
+            graph_width,
+            graph_height,
+            Rgb([250u8, 120u8, 60u8]),
+        );
+        Self::draw_history(
+            image,
+            &humidities,
+            width / 2 + layout.x(4),
+            top,
+            graph_width,
+            graph_height,
+            Rgb([90u8, 180u8, 250u8]),
+        );
+
+        let (advice, color) = self.advice_line(&reading);
+        draw_text(
+            image,
+            &self.screen.font,
+            &advice,
+            layout.x(4),
+            layout.height() as i32 - layout.y(22),
+            layout.scale(18.0),
+            color,
+        );
+    }
+
+    fn draw_screen(&mut self) {
+        for (key, device) in DEVICES.iter() {
+            let layout = Layout::for_display(device.screen_width(), device.screen_height());
+            let mut image = RgbImage::new(layout.width(), layout.height());
+            if layout.compact() {
+                self.draw_compact(&mut image, &layout);
+            } else {
+                self.draw_full(&mut image, &layout);
+            }
+            self.screen
+                .device_screen_bytes
+                .insert(key.clone(), image.into_vec());
+        }
+    }
+
+    pub fn new(
+        description: String,
+        key: String,
+        font: FontArc,
+        config_manager: Arc<RwLock<ConfigManager>>,
+    ) -> ClimateScreen {
+        let (tx, rx) = bounded::<Arc<ClimateSnapshot>>(1);
+
+        let mut params = IndexMap::new();
+        params.insert("unit_system".into(), ConfigParam::String("metric".into()));
+        params.insert(
+            "humidity_min".into(),
+            ConfigParam::Integer(DEFAULT_HUMIDITY_MIN),
+        );
+        params.insert(
+            "humidity_max".into(),
+            ConfigParam::Integer(DEFAULT_HUMIDITY_MAX),
+        );
+        params.insert(
+            "temperature_min".into(),
+            ConfigParam::Integer(DEFAULT_TEMPERATURE_MIN),
+        );
+        params.insert(
+            "temperature_max".into(),
+            ConfigParam::Integer(DEFAULT_TEMPERATURE_MAX),
+        );
+        params.insert(
+            "history_hours".into(),
+            ConfigParam::Integer(DEFAULT_HISTORY_HOURS),
+        );
+
+        let screen = Screen {
+            description,
+            key: key.clone(),
+            font,
+            config_manager: config_manager.clone(),
+            config_layout: ExchangeableConfig { params },
+            // the thread keeps running while the screen is inactive, so the history has no gaps
+            handle: Some(thread::spawn(move || {
+                let sender = tx;
+                let mut history = ClimateHistory::load(Path::new(HISTORY_PATH), Utc::now());
+                let mut last_reading: Option<ClimateReading> = None;
+
+                loop {
+                    let reading = ClimateReading::from_sensors(&SENSORS.read().unwrap());
+                    if let Some(reading) = reading {
+                        if last_reading != Some(reading) {
+                            history.record(&reading);
+                            last_reading = Some(reading);
+                        }
+                    }
+
+                    let hours = integer_value(
+                        &config_manager,
+                        &key,
+                        "history_hours",
+                        DEFAULT_HISTORY_HOURS,
+                    );
+                    let since = Utc::now() - chrono::Duration::hours(hours.max(1) as i64);
+                    let _ = sender.try_send(Arc::new(ClimateSnapshot {
+                        reading,
+                        history: history.since(since),
+                    }));
+                    thread::sleep(POLL_INTERVAL);
+                }
+            })),
+            ..Default::default()
+        };
+
+        let mut this = ClimateScreen {
+            screen,
+            receiver: rx,
+            snapshot: Arc::new(ClimateSnapshot::default()),
+        };
+
+        this.draw_screen(); // initial draw
+        this
+    }
+}
@@ -0,0 +1,403 @@
+use crate::{
+    config_manager::{string_value, ConfigManager},
+    helpers::{
+        clock::{hand_end, parse_countdowns, parse_time_zones, upcoming, WorldTime},
+        drawing::{draw_centered, draw_right_aligned, draw_text},
+        layout::Layout,
+        paging::page_duration,
+        text_manipulation::{format_duration, DurationFormat},
+    },
+    screens::{BasicScreen, Screen, Screenable},
+    DEVICES,
+};
+use ab_glyph::FontArc;
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, TimeZone, Timelike};
+use exchange_format::{ConfigParam, ExchangeableConfig};
+use image::{ImageBuffer, Rgb, RgbImage};
+use imageproc::drawing::{draw_filled_circle_mut, draw_hollow_circle_mut, draw_line_segment_mut};
+use indexmap::IndexMap;
+use std::{
+    sync::{Arc, RwLock},
+    time::Instant,
+};
+
+const DEFAULT_FACE: &str = "digital";
+const DEFAULT_PAGE_DURATION: u32 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Page {
+    Clock,
+    WorldTime,
+    Countdown,
+}
+
+pub struct ClockScreen {
+    screen: Screen,
+    last_second: u32,
+    page: usize,
+    page_started: Instant,
+}
+
+impl Screenable for ClockScreen {
+    fn get_screen(&mut self) -> &mut Screen {
+        &mut self.screen
+    }
+}
+
+impl BasicScreen for ClockScreen {
+    fn update(&mut self) {
+        let mut redraw = false;
+        let pages = self.pages();
+        if self.page_started.elapsed()
+            >= page_duration(
+                &self.screen.config_manager,
+                &self.screen.key,
+                DEFAULT_PAGE_DURATION,
+            )
+        {
+            self.page = (self.page + 1) % pages.len();
+            self.page_started = Instant::now();
+            redraw = true;
+        }
+
+        // no background thread needed, the clock is redrawn once per second
+        let second = Local::now().second();
+        if second != self.last_second {
+            self.last_second = second;
+            redraw = true;
+        }
+
+        if redraw {
+            self.draw_screen();
+        }
+    }
+}
+
+impl ClockScreen {
+    fn string_value(&self, key: &str, default: &str) -> String {
+        string_value(&self.screen.config_manager, &self.screen.key, key, default)
+    }
+
+    fn time_zones(&self) -> Vec<WorldTime> {
+        parse_time_zones(&self.string_value("time_zones", ""))
+    }
+
+    // pages without content are skipped
+    fn pages(&self) -> Vec<Page> {
+        let mut pages = vec![Page::Clock];
+        if !self.time_zones().is_empty() {
+            pages.push(Page::WorldTime);
+        }
+        if !parse_countdowns(&self.string_value("countdowns", "")).is_empty() {
+            pages.push(Page::Countdown);
+        }
+        pages
+    }
+
+    fn draw_text(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+        text: &str,
+        (x, y): (i32, i32),
+        size: f32,
+        color: Rgb<u8>,
+    ) {
+        draw_text(
+            image,
+            &self.screen.font,
+            text,
+            layout.x(x),
+            layout.y(y),
+            layout.scale(size),
+            color,
+        );
+    }
+
+    fn draw_centered(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+        text: &str,
+        (center, y): (i32, i32),
+        size: f32,
+        color: Rgb<u8>,
+    ) {
+        draw_centered(
+            image,
+            &self.screen.font,
+            text,
+            layout.x(center),
+            layout.y(y),
+            layout.scale(size),
+            color,
+        );
+    }
+
+    // the right position is relative to the right border
+    fn draw_right_aligned(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+        text: &str,
+        (margin, y): (i32, i32),
+        size: f32,
+        color: Rgb<u8>,
+    ) {
+        draw_right_aligned(
+            image,
+            &self.screen.font,
+            text,
+            layout.width() as i32 - layout.x(margin),
+            layout.y(y),
+            layout.scale(size),
+            color,
+        );
+    }
+
+    // the title and the line below of the regular layout
+    fn draw_header(&self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, layout: &Layout, title: &str) {
+        self.draw_text(image, layout, title, (4, 2), 22.0, Rgb([255, 255, 255]));
+        let line_y = layout.y(27) as f32;
+        draw_line_segment_mut(
+            image,
+            (0.0, line_y),
+            (layout.width() as f32, line_y),
+            Rgb([90, 90, 90]),
+        );
+    }
+
+    fn draw_analog_face(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        now: &NaiveDateTime,
+        center: (i32, i32),
+        radius: i32,
+        colored: bool,
+    ) {
+        let white = Rgb([255u8, 255u8, 255u8]);
+        let center_f = (center.0 as f32, center.1 as f32);
+        let radius_f = radius as f32;
+        draw_hollow_circle_mut(image, center, radius, white);
+        for hour in 0..12 {
+            let fraction = hour as f32 / 12.0;
+            let inner = if hour % 3 == 0 { 0.8 } else { 0.9 };
+            draw_line_segment_mut(
+                image,
+                hand_end(center_f, radius_f * inner, fraction),
+                hand_end(center_f, radius_f, fraction),
+                white,
+            );
+        }
+
+        let seconds = now.second() as f32;
+        let minutes = now.minute() as f32 + seconds / 60.0;
+        let hours = (now.hour() % 12) as f32 + minutes / 60.0;
+        draw_line_segment_mut(
+            image,
+            center_f,
+            hand_end(center_f, radius_f * 0.5, hours / 12.0),
+            white,
+        );
+        draw_line_segment_mut(
+            image,
+            center_f,
+            hand_end(center_f, radius_f * 0.8, minutes / 60.0),
+            white,
+        );
+        draw_line_segment_mut(
+            image,
+            center_f,
+            hand_end(center_f, radius_f * 0.9, seconds / 60.0),
+            if colored {
+                Rgb([230, 70, 60])
+            } else {
+                Rgb([160, 160, 160])
+            },
+        );
+        draw_filled_circle_mut(image, center, (radius / 16).max(1), white);
+    }
+
+    fn draw_clock(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+        now: &DateTime<Local>,
+    ) {
+        let white = Rgb([255u8, 255u8, 255u8]);
+        let gray = Rgb([160u8, 160u8, 160u8]);
+        let analog = self
+            .string_value("face", DEFAULT_FACE)
+            .trim()
+            .to_lowercase()
+            == "analog";
+        let date = now.format("%a %d.%m.%Y").to_string();
+        let week = format!("Week {}", now.iso_week().week());
+        let time = now.format("%H:%M:%S").to_string();
+
+        // the coordinates are those of the reference resolutions, 256x64 and 320x170
+        match (analog, layout.compact()) {
+            (true, true) => {
+                let center = (layout.x(32), layout.y(32));
+                self.draw_analog_face(image, &now.naive_local(), center, layout.length(30), false);
+                let short_time = now.format("%H:%M").to_string();
+                self.draw_text(image, layout, &short_time, (76, 0), 30.0, white);
+                self.draw_text(image, layout, &date, (76, 32), 14.0, white);
+                self.draw_text(image, layout, &week, (76, 48), 14.0, gray);
+            }
+            (true, false) => {
+                let radius = layout.length(79);
+                let center = (layout.x(85), layout.y(85));
+                self.draw_analog_face(image, &now.naive_local(), center, radius, true);
+                self.draw_centered(image, layout, &time, (242, 30), 36.0, white);
+                self.draw_centered(image, layout, &date, (242, 82), 18.0, white);
+                self.draw_centered(image, layout, &week, (242, 108), 18.0, gray);
+            }
+            (false, true) => {
+                self.draw_centered(image, layout, &time, (128, 0), 40.0, white);
+                self.draw_text(image, layout, &date, (2, 46), 14.0, white);
+                self.draw_right_aligned(image, layout, &week, (2, 46), 14.0, gray);
+            }
+            (false, false) => {
+                self.draw_centered(image, layout, &time, (160, 20), 72.0, white);
+                self.draw_centered(image, layout, &date, (160, 104), 24.0, white);
+                self.draw_centered(image, layout, &week, (160, 136), 20.0, gray);
+            }
+        }
+    }
+
+    fn draw_world_time(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+        now: &DateTime<Local>,
+    ) {
+        let white = Rgb([255u8, 255u8, 255u8]);
+        let gray = Rgb([160u8, 160u8, 160u8]);
+        let time_zones = self.time_zones();
+        let today = now.date_naive();
+
+        if layout.compact() {
+            // up to three zones side by side
+            let columns = time_zones.len().min(3) as i32;
+            let column_width = 256 / columns.max(1);
+            for (index, world_time) in time_zones.iter().take(3).enumerate() {
+                let time = world_time.time_zone.from_utc_datetime(&now.naive_utc());
+                let center = index as i32 * column_width + column_width / 2;
+                self.draw_centered(image, layout, &world_time.label, (center, 0), 14.0, gray);
+                let time = time.format("%H:%M").to_string();
+                self.draw_centered(image, layout, &time, (center, 20), 28.0, white);
+            }
+            return;
+        }
+
+        self.draw_header(image, layout, "World time");
+        for (index, world_time) in time_zones.iter().take(4).enumerate() {
+            let time = world_time.time_zone.from_utc_datetime(&now.naive_utc());
+            let y = 32 + index as i32 * 34;
+            // mark zones, that are already in tomorrow or still in yesterday
+            let day_offset = (time.date_naive() - today).num_days();
+            let label = match day_offset {
+                0 => world_time.label.clone(),
+                offset => format!("{} ({:+}d)", world_time.label, offset),
+            };
+            self.draw_text(image, layout, &label, (4, y + 6), 20.0, white);
+            let time = time.format("%H:%M").to_string();
+            self.draw_right_aligned(image, layout, &time, (4, y), 30.0, white);
+        }
+    }
+
+    fn draw_countdowns(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+        now: &DateTime<Local>,
+    ) {
+        let white = Rgb([255u8, 255u8, 255u8]);
+        let countdowns = upcoming(
+            &parse_countdowns(&self.string_value("countdowns", "")),
+            now.naive_local(),
+        );
+        let (top, font_size, row_height, rows) = if layout.compact() {
+            (0, 14.0, 16, 4)
+        } else {
+            self.draw_header(image, layout, "Countdown");
+            (32, 20.0, 27, 5)
+        };
+
+        if countdowns.is_empty() {
+            self.draw_text(
+                image,
+                layout,
+                "No upcoming events",
+                (2, top),
+                font_size,
+                white,
+            );
+            return;
+        }
+        for (index, (countdown, remaining)) in countdowns.iter().take(rows).enumerate() {
+            let y = top + index as i32 * row_height;
+            self.draw_text(image, layout, &countdown.label, (2, y), font_size, white);
+            self.draw_right_aligned(
+                image,
+                layout,
+                &format_duration(*remaining, DurationFormat::Clock),
+                (2, y),
+                font_size,
+                white,
+            );
+        }
+    }
+
+    fn draw_screen(&mut self) {
+        let now = Local::now();
+        let pages = self.pages();
+        let page = pages[self.page % pages.len()];
+        for (key, device) in DEVICES.iter() {
+            let layout = Layout::for_display(device.screen_width(), device.screen_height());
+            let mut image = RgbImage::new(layout.width(), layout.height());
+            match page {
+                Page::Clock => self.draw_clock(&mut image, &layout, &now),
+                Page::WorldTime => self.draw_world_time(&mut image, &layout, &now),
+                Page::Countdown => self.draw_countdowns(&mut image, &layout, &now),
+            }
+            self.screen
+                .device_screen_bytes
+                .insert(key.clone(), image.into_vec());
+        }
+    }
+
+    pub fn new(
+        description: String,
+        key: String,
+        font: FontArc,
+        config_manager: Arc<RwLock<ConfigManager>>,
+    ) -> ClockScreen {
+        let mut params = IndexMap::new();
+        params.insert("face".into(), ConfigParam::String(DEFAULT_FACE.into()));
+        params.insert("time_zones".into(), ConfigParam::String(String::new()));
+        params.insert("countdowns".into(), ConfigParam::String(String::new()));
+        params.insert(
+            "page_duration".into(),
+            ConfigParam::Integer(DEFAULT_PAGE_DURATION),
+        );
+
+        let mut this = ClockScreen {
+            screen: Screen {
+                description,
+                key,
+                font,
+                config_manager,
+                config_layout: ExchangeableConfig { params },
+                ..Default::default()
+            },
+            last_second: 60,
+            page: 0,
+            page_started: Instant::now(),
+        };
+
+        this.draw_screen(); // initial draw
+        this
+    }
+}
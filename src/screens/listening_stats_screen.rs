@@ -0,0 +1,217 @@
+use crate::{
+    config_manager::{integer_value, ConfigManager},
+    helpers::{
+        drawing::{draw_right_aligned, draw_text},
+        layout::Layout,
+        text_manipulation::{format_duration, DurationFormat},
+    },
+    media::history::{listening_stats, load_plays, ListeningStats, PLAY_HISTORY_PATH},
+    screens::{BasicScreen, Screen, Screenable},
+    DEVICES,
+};
+use ab_glyph::FontArc;
+use chrono::Local;
+use crossbeam_channel::{bounded, Receiver};
+use exchange_format::{ConfigParam, ExchangeableConfig};
+use image::{ImageBuffer, Rgb, RgbImage};
+use imageproc::{
+    drawing::{draw_filled_rect_mut, draw_line_segment_mut},
+    rect::Rect,
+};
+use indexmap::IndexMap;
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    thread,
+    time::Duration,
+};
+
+const DEFAULT_TOP_ARTISTS: u32 = 5;
+// the media screen appends the plays to the history file, which is read again after this
+const RELOAD_INTERVAL: Duration = Duration::from_secs(30);
+
+pub struct ListeningStatsScreen {
+    screen: Screen,
+    receiver: Receiver<Arc<ListeningStats>>,
+    stats: Arc<ListeningStats>,
+}
+
+impl Screenable for ListeningStatsScreen {
+    fn get_screen(&mut self) -> &mut Screen {
+        &mut self.screen
+    }
+}
+
+impl BasicScreen for ListeningStatsScreen {
+    fn update(&mut self) {
+        if let Ok(stats) = self.receiver.try_recv() {
+            self.stats = stats;
+            self.draw_screen();
+        }
+    }
+}
+
+impl ListeningStatsScreen {
+    // the text at a position of the reference resolution
+    fn draw_text(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+        text: &str,
+        (x, y): (i32, i32),
+        size: f32,
+        color: Rgb<u8>,
+    ) {
+        draw_text(
+            image,
+            &self.screen.font,
+            text,
+            layout.x(x),
+            layout.y(y),
+            layout.scale(size),
+            color,
+        );
+    }
+
+    fn today_line(&self) -> String {
+        format!(
+            "Today {} \u{00B7} {} plays",
+            format_duration(self.stats.today_listening_time, DurationFormat::Minutes),
+            self.stats.today_plays
+        )
+    }
+
+    fn draw_compact(&self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, layout: &Layout) {
+        let white = Rgb([255u8, 255u8, 255u8]);
+        let gray = Rgb([160u8, 160u8, 160u8]);
+        self.draw_text(image, layout, &self.today_line(), (2, 0), 18.0, white);
+        if self.stats.top_artists.is_empty() {
+            self.draw_text(image, layout, "No plays this week", (2, 22), 14.0, gray);
+            return;
+        }
+        for (index, (artist, plays)) in self.stats.top_artists.iter().take(3).enumerate() {
+            self.draw_text(
+                image,
+                layout,
+                &format!("{}. {} ({})", index + 1, artist, plays),
+                (2, 20 + index as i32 * 14),
+                14.0,
+                gray,
+            );
+        }
+    }
+
+    fn draw_full(&self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, layout: &Layout) {
+        let white = Rgb([255u8, 255u8, 255u8]);
+        let gray = Rgb([160u8, 160u8, 160u8]);
+        let accent = Rgb([90u8, 180u8, 250u8]);
+        let width = layout.width() as f32;
+
+        self.draw_text(image, layout, "Listening stats", (4, 2), 22.0, white);
+        let line_y = layout.y(27) as f32;
+        draw_line_segment_mut(image, (0.0, line_y), (width, line_y), Rgb([90, 90, 90]));
+        self.draw_text(image, layout, &self.today_line(), (4, 32), 18.0, accent);
+        self.draw_text(
+            image,
+            layout,
+            &format!("Top artists this week ({} plays)", self.stats.week_plays),
+            (4, 56),
+            16.0,
+            gray,
+        );
+
+        if self.stats.top_artists.is_empty() {
+            self.draw_text(image, layout, "No plays this week", (4, 78), 18.0, white);
+            return;
+        }
+        // bars relative to the most played artist behind the names,
+        // five rows fit below the header of the 320x170 reference
+        let most_plays = self.stats.top_artists[0].1.max(1);
+        for (index, (artist, plays)) in self.stats.top_artists.iter().take(5).enumerate() {
+            let y = 78 + index as i32 * 18;
+            let bar_width = (312 * plays / most_plays).max(1) as u32;
+            draw_filled_rect_mut(
+                image,
+                Rect::at(layout.x(4), layout.y(y)).of_size(layout.w(bar_width), layout.h(16)),
+                Rgb([40u8, 60u8, 90u8]),
+            );
+            self.draw_text(image, layout, artist, (8, y), 16.0, white);
+            draw_right_aligned(
+                image,
+                &self.screen.font,
+                &plays.to_string(),
+                layout.x(312),
+                layout.y(y),
+                layout.scale(16.0),
+                white,
+            );
+        }
+    }
+
+    fn draw_screen(&mut self) {
+        for (key, device) in DEVICES.iter() {
+            let layout = Layout::for_display(device.screen_width(), device.screen_height());
+            let mut image = RgbImage::new(layout.width(), layout.height());
+            if layout.compact() {
+                self.draw_compact(&mut image, &layout);
+            } else {
+                self.draw_full(&mut image, &layout);
+            }
+            self.screen
+                .device_screen_bytes
+                .insert(key.clone(), image.into_vec());
+        }
+    }
+
+    pub fn new(
+        description: String,
+        key: String,
+        font: FontArc,
+        config_manager: Arc<RwLock<ConfigManager>>,
+    ) -> ListeningStatsScreen {
+        let (tx, rx) = bounded::<Arc<ListeningStats>>(1);
+        let active = Arc::new(AtomicBool::new(false));
+
+        let mut params = IndexMap::new();
+        params.insert(
+            "top_artists".into(),
+            ConfigParam::Integer(DEFAULT_TOP_ARTISTS),
+        );
+
+        let screen = Screen {
+            description,
+            key: key.clone(),
+            font,
+            active: active.clone(),
+            config_manager: config_manager.clone(),
+            config_layout: ExchangeableConfig { params },
+            handle: Some(thread::spawn(move || {
+                let sender = tx;
+                loop {
+                    while !active.load(Ordering::Acquire) {
+                        thread::park();
+                    }
+                    let count =
+                        integer_value(&config_manager, &key, "top_artists", DEFAULT_TOP_ARTISTS);
+                    let plays = load_plays(Path::new(PLAY_HISTORY_PATH));
+                    let stats = listening_stats(&plays, Local::now(), count as usize);
+                    let _ = sender.try_send(Arc::new(stats));
+                    thread::park_timeout(RELOAD_INTERVAL);
+                }
+            })),
+            ..Default::default()
+        };
+
+        let mut this = ListeningStatsScreen {
+            screen,
+            receiver: rx,
+            stats: Arc::new(ListeningStats::default()),
+        };
+
+        this.draw_screen(); // initial draw
+        this
+    }
+}
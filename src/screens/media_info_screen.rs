@@ -2,37 +2,75 @@ extern crate winapi;
 use crate::{
     config_manager::ConfigManager,
     helpers::{
-        current_cover::{extract_cover_image, extract_current_cover_path},
-        text_manipulation::rotate,
+        accent_color::accent_color,
+        cover_cache::{cover_key, cover_patterns, CoverArt, CoverCache, DEFAULT_COVER_PATTERNS},
+        current_cover::{
+            extract_current_cover_path, extract_lyrics, extract_track_metadata, load_cover,
+        },
+        layout::Layout,
+        lyrics::Lyrics,
+        marquee::{Marquee, MarqueeArea, DEFAULT_SCROLL_PAUSE, DEFAULT_SCROLL_SPEED},
+        text_manipulation::{format_duration, DurationFormat},
+        track_metadata::{parse_window_title, TrackMetadata, DEFAULT_DETAILS},
     },
-    screens::{BasicScreen, Screen, Screenable},
-    ESP32, TEENSY,
+    media::{
+        api::DEFAULT_API_PORT,
+        history::{record_play, PLAY_HISTORY_PATH},
+        mpd::DEFAULT_MPD_ADDRESS,
+        scrobble::{NowPlaying, PlayTracker},
+        DEFAULT_BACKEND, VOLUME_MODE,
+    },
+    screens::{BasicScreen, Screen, ScreenMode, Screenable},
+    DEVICES, ESP32,
 };
 use ab_glyph::{FontArc, PxScale};
 use crossbeam_channel::{bounded, Receiver, Sender};
-use image::{EncodableLayout, ImageBuffer, Rgb, RgbImage};
+use exchange_format::{ConfigParam, ExchangeableConfig};
+use image::{ImageBuffer, Rgb, RgbImage};
 use imageproc::drawing::{
-    draw_filled_rect_mut, draw_hollow_rect_mut, draw_line_segment_mut, draw_text_mut,
+    draw_filled_rect_mut, draw_hollow_rect_mut, draw_line_segment_mut, draw_text_mut, text_size,
 };
 use imageproc::rect::Rect;
-use regex;
+use indexmap::IndexMap;
 
 use std::path::Path;
 use std::{
     sync::{atomic::AtomicBool, atomic::Ordering, Arc, RwLock},
     thread,
-    time::{Duration, Instant},
+    time::Duration,
 };
-use unicode_segmentation::UnicodeSegmentation;
 use winapi::shared::minwindef::LPARAM;
 use winsafe::{co, msg::WndMsg};
+
+// the player is polled less often while the screen is hidden and only plays are recorded
+const HIDDEN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// the volume replaces the progress for a while, when it is changed
+const VOLUME_MODE_DURATION: Duration = Duration::from_secs(3);
+
+// scrolling texts are redrawn more often than the player is polled, so they move in small steps
+const SCROLL_REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+
+// used, if the cover has no prominent color
+const DEFAULT_ACCENT: Rgb<u8> = Rgb([90u8, 180u8, 250u8]);
+
 pub struct MediaInfoScreen {
     screen: Screen,
     receiver: Receiver<MusicPlayerInfo>,
     symbols: FontArc,
-    title_x: u32,
-    artist_x: u32,
+    artist_marquee: Marquee,
+    title_marquee: Marquee,
+    // album, year and the other configured tags on the companion display
+    details_marquee: Marquee,
+    // the current line of the lyrics page
+    lyrics_marquee: Marquee,
+    show_lyrics: bool,
+    show_volume: bool,
+    // set, if a text did not fit into its area on the last redraw
+    scrolling: bool,
     music_player_info: MusicPlayerInfo,
+    // the key of the cover, the scaled cover of the companion display and its accent color
+    companion_cover: Option<(Option<String>, RgbImage, Rgb<u8>)>,
 }
 
 #[derive(Clone, Default, Debug)]
@@ -45,13 +83,19 @@ struct MusicPlayerInfo {
     player_active: bool,
     system_volume: f32,
     mute: i32,
-    cover: Vec<u8>,
-    filepath: String,
+    cover: Option<Arc<CoverArt>>,
+    lyrics: Option<Arc<Lyrics>>,
+    metadata: TrackMetadata,
 }
 #[derive(Clone, Default)]
 pub struct CoverManager {
     pub last_path: String,
-    pub current_cover: Vec<u8>,
+    pub current_cover: Option<Arc<CoverArt>>,
+    pub cache: CoverCache,
+    // tags and lyrics are only read again, if the track changes
+    pub track_path: String,
+    pub metadata: Option<TrackMetadata>,
+    pub lyrics: Option<Arc<Lyrics>>,
 }
 
 impl Screenable for MediaInfoScreen {
@@ -66,164 +110,256 @@ impl BasicScreen for MediaInfoScreen {
         match music_player_info {
             Ok(music_player_info) => {
                 self.draw_screen(&music_player_info);
-                self.draw_companion_screen(&music_player_info);
                 self.music_player_info = music_player_info;
             }
-            Err(_) => {}
+            Err(_) => {
+                if self.scrolling {
+                    let music_player_info = self.music_player_info.clone();
+                    self.draw_screen(&music_player_info);
+                }
+            }
         }
     }
+
+    fn refresh_interval(&self) -> Duration {
+        if self.scrolling {
+            SCROLL_REFRESH_INTERVAL
+        } else {
+            Duration::from_millis(250)
+        }
+    }
+}
+
+// the line of the lyrics at the playback position. the line of lyrics without timestamps
+// is estimated from the progress of the track.
+fn lyrics_line(lyrics: &Lyrics, music_player_info: &MusicPlayerInfo) -> Option<usize> {
+    let position = Duration::from_millis(music_player_info.current_track_position.max(0) as u64);
+    if lyrics.synced {
+        return lyrics.current_line(position);
+    }
+    if lyrics.lines.is_empty() || music_player_info.track_length <= 0 {
+        return None;
+    }
+    let progress = position.as_secs_f64() / music_player_info.track_length as f64;
+    Some(((progress * lyrics.lines.len() as f64) as usize).min(lyrics.lines.len() - 1))
+}
+
+// the track for the play history, streams keep their path and change the caption
+fn now_playing(music_player_info: &MusicPlayerInfo, path: &str) -> Option<NowPlaying> {
+    if !music_player_info.player_active {
+        return None;
+    }
+    Some(NowPlaying {
+        id: format!(
+            "{}\t{}\t{}",
+            path, music_player_info.artist, music_player_info.title
+        ),
+        artist: music_player_info.artist.clone(),
+        album: music_player_info.metadata.album.clone(),
+        title: music_player_info.title.clone(),
+        track_number: music_player_info.metadata.track_number,
+        length: (music_player_info.track_length > 0)
+            .then(|| Duration::from_secs(music_player_info.track_length as u64)),
+    })
+}
+
+fn record_plays(config_manager: &Arc<RwLock<ConfigManager>>, key: &str) -> bool {
+    config_manager
+        .read()
+        .unwrap()
+        .get_string_value(key, "record_plays")
+        .is_none_or(|value| value != "off")
+}
+
+fn play_state_symbol(playback_status: isize) -> &'static str {
+    match playback_status {
+        1 => "\u{f04B}",
+        3 => "\u{f04C}",
+        _ => "\u{f04D}",
+    }
 }
 
 impl MediaInfoScreen {
-    fn draw_intro(&mut self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, scale: PxScale) {
+    fn draw_intro(&mut self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, layout: &Layout) {
         draw_text_mut(
             image,
             Rgb([255u8, 255u8, 255u8]),
-            77,
-            4,
-            scale,
+            layout.x(77),
+            layout.y(4),
+            layout.scale(16.0),
             &self.screen.font,
             "Media Screen",
         );
         draw_text_mut(
             image,
             Rgb([255u8, 255u8, 255u8]),
-            65,
-            32,
-            scale,
+            layout.x(65),
+            layout.y(32),
+            layout.scale(16.0),
             &self.screen.font,
             "Winamp inactive",
         );
     }
-    fn draw_artist(
-        &mut self,
-        artist: &String,
-        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
-        scale: PxScale,
-    ) {
-        let mut position_artist = 0;
-        let artist_len = artist.graphemes(true).count();
-        let mut start = 0usize;
-        if artist_len * 17 < 480 {
-            position_artist = (1 + ((256 - (artist_len as u32 * 17) / 2) / 2)) - 2;
-        } else {
-            start = self.artist_x as usize;
-            if self.artist_x == artist_len as u32 + 2u32 {
-                self.artist_x = 0;
-            } else {
-                self.artist_x += 1;
-            }
-        }
 
-        draw_text_mut(
+    fn draw_artist(&mut self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, layout: &Layout) {
+        self.scrolling |= self.artist_marquee.draw(
             image,
+            &self.screen.font,
+            layout.scale(16.0),
             Rgb([255u8, 255u8, 255u8]),
-            position_artist as i32,
-            0,
-            scale,
+            MarqueeArea {
+                x: 0,
+                y: layout.y(0),
+                width: layout.width() as i32,
+                centered: true,
+            },
+        );
+    }
+
+    fn draw_title(&mut self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, layout: &Layout) {
+        self.scrolling |= self.title_marquee.draw(
+            image,
             &self.screen.font,
-            &rotate(
-                &[artist, "   "].join(""),
-                crate::helpers::text_manipulation::Direction::Left,
-                start,
-            ),
+            layout.scale(16.0),
+            Rgb([255u8, 255u8, 255u8]),
+            MarqueeArea {
+                x: 0,
+                y: layout.y(16),
+                width: layout.width() as i32,
+                centered: true,
+            },
         );
     }
 
-    fn draw_title(
+    // the lyrics page replaces the player info, while no volume is shown
+    fn visible_lyrics(&self, music_player_info: &MusicPlayerInfo) -> Option<Arc<Lyrics>> {
+        if !self.show_volume && self.show_lyrics {
+            music_player_info.lyrics.clone()
+        } else {
+            None
+        }
+    }
+
+    fn draw_centered_text(
+        &self,
+        text: &str,
+        y: i32,
+        scale: PxScale,
+        color: Rgb<u8>,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+    ) {
+        let text_width = text_size(scale, &self.screen.font, text).0 as i32;
+        let x = ((image.width() as i32 - text_width) / 2).max(0);
+        draw_text_mut(image, color, x, y, scale, &self.screen.font, text);
+    }
+
+    // the title, the current line and the next line
+    fn draw_lyrics(
         &mut self,
-        title: &String,
+        lyrics: &Lyrics,
+        music_player_info: &MusicPlayerInfo,
         image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
-        scale: PxScale,
+        layout: &Layout,
     ) {
-        let title_len = title.graphemes(true).count();
-        let mut position_title = 0;
-        let mut start = 0;
-        if title_len * 17 < 480 {
-            position_title = (1 + ((256 - (title_len as u32 * 17) / 2) / 2)) - 2;
+        let width = layout.width() as i32;
+        self.scrolling |= self.title_marquee.draw(
+            image,
+            &self.screen.font,
+            layout.scale(12.0),
+            Rgb([150u8, 150u8, 150u8]),
+            MarqueeArea {
+                x: 0,
+                y: layout.y(0),
+                width,
+                centered: true,
+            },
+        );
+        let color = if lyrics.synced {
+            Rgb([255u8, 255u8, 255u8])
         } else {
-            start = self.title_x as usize;
-
-            if self.title_x == title_len as u32 + 2u32 {
-                self.title_x = 0;
-            } else {
-                self.title_x += 1;
-            }
-        }
-
-        draw_text_mut(
+            Rgb([200u8, 200u8, 200u8])
+        };
+        self.scrolling |= self.lyrics_marquee.draw(
             image,
-            Rgb([255u8, 255u8, 255u8]),
-            position_title as i32,
-            16,
-            scale,
             &self.screen.font,
-            &rotate(
-                &[title, "   "].join(""),
-                crate::helpers::text_manipulation::Direction::Left,
-                start,
-            ),
+            layout.scale(18.0),
+            color,
+            MarqueeArea {
+                x: 0,
+                y: layout.y(20),
+                width,
+                centered: true,
+            },
         );
+        let next = lyrics_line(lyrics, music_player_info).map_or(0, |line| line + 1);
+        if let Some(line) = lyrics.lines.get(next) {
+            self.draw_centered_text(
+                &line.text,
+                layout.y(44),
+                layout.scale(14.0),
+                Rgb([120u8, 120u8, 120u8]),
+                image,
+            );
+        }
     }
 
     fn draw_play_button(
         &mut self,
         playback_status: isize,
         image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
     ) {
-        let play_button = &String::from("\u{f04B}");
-        let pause_button = &String::from("\u{f04C}");
-        let stop_button = &String::from("\u{f04D}");
-
-        let mut button = stop_button;
-        if playback_status == 1 {
-            button = play_button;
-        }
-
-        if playback_status == 3 {
-            button = pause_button;
-        }
+        let button = play_state_symbol(playback_status);
         draw_text_mut(
             image,
             Rgb([255u8, 255u8, 255u8]),
-            4,
-            37,
-            PxScale { x: 10.0, y: 10.0 },
+            layout.x(4),
+            layout.y(37),
+            layout.scale(10.0),
             &self.symbols,
             button,
         );
     }
 
-    fn draw_elapsed(&mut self, length: isize, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>) {
-        let length = length / 1000;
-        let seconds = length % 60;
-        let minutes = (length / 60) % 60;
-        let hours = (length / 60) / 60;
-        let elapsed = format!("{:0>2}:{:0>2}:{:0>2}", hours, minutes, seconds);
+    fn draw_elapsed(
+        &mut self,
+        length: isize,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+    ) {
+        let elapsed = format_duration(
+            Duration::from_millis(length.max(0) as u64),
+            DurationFormat::Clock,
+        );
         let elapsed = format!("{: <12}", elapsed);
         draw_text_mut(
             image,
             Rgb([255u8, 255u8, 255u8]),
-            16,
-            36,
-            PxScale { x: 14.0, y: 14.0 },
+            layout.x(16),
+            layout.y(36),
+            layout.scale(14.0),
             &self.screen.font,
             &elapsed,
         );
     }
 
-    fn draw_total(&mut self, length: isize, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>) {
-        let seconds = length % 60;
-        let minutes = (length / 60) % 60;
-        let hours = (length / 60) / 60;
-        let total = format!("{:0>2}:{:0>2}:{:0>2}", hours, minutes, seconds);
+    fn draw_total(
+        &mut self,
+        length: isize,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+    ) {
+        let total = format_duration(
+            Duration::from_secs(length.max(0) as u64),
+            DurationFormat::Clock,
+        );
         let total = format!("{: >12}", total);
         draw_text_mut(
             image,
             Rgb([255u8, 255u8, 255u8]),
-            166,
-            36,
-            PxScale { x: 14.0, y: 14.0 },
+            layout.x(166),
+            layout.y(36),
+            layout.scale(14.0),
             &self.screen.font,
             &total,
         );
@@ -234,91 +370,79 @@ impl MediaInfoScreen {
         current_track_position: isize,
         track_length: isize,
         image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
     ) {
-        let indicator_position_x_min = 16.0;
-        let indicator_position_x_max = 232.0;
+        let indicator_position_x_min = layout.x(16) as f64;
+        let indicator_position_x_max = layout.x(232) as f64;
+        let bar_y = layout.y(50);
+        let bar_height = layout.h(7);
 
         let progress = (current_track_position as f64 / 1000.0) / (track_length as f64);
         let position = indicator_position_x_min + (progress * indicator_position_x_max);
         draw_hollow_rect_mut(
             image,
-            Rect::at(16, 50).of_size(238, 7),
+            Rect::at(layout.x(16), bar_y).of_size(layout.w(238), bar_height),
             Rgb([255u8, 255u8, 255u8]),
         );
 
         draw_filled_rect_mut(
             image,
-            Rect::at(position as i32, 50).of_size(6, 7),
+            Rect::at(position as i32, bar_y).of_size(layout.w(6), bar_height),
             Rgb([255u8, 255u8, 255u8]),
         );
 
-        let start = 16;
+        let start = layout.x(16);
         let end = position as i32;
-        let segment_length = 6;
+        let segment_length = layout.w(6) as i32;
         let line_length = (end - start) + segment_length;
         let segments = line_length / segment_length;
+        let line_y = (bar_y + bar_height as i32 / 2) as f32;
 
         for n in 0..segments {
             let formula = start as f32 + (n as f32 * segment_length as f32);
             draw_line_segment_mut(
                 image,
-                (formula, 53.0),
-                (formula + (segment_length / 2) as f32, 53.0),
+                (formula, line_y),
+                (formula + (segment_length / 2) as f32, line_y),
                 Rgb([255u8, 255u8, 255u8]),
             );
         }
     }
 
-    fn draw_mute_speaker(&mut self, mute: i32, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>) {
+    fn draw_mute_speaker(
+        &mut self,
+        mute: i32,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+    ) {
         let mute_speaker = &String::from("\u{f6a9}");
         if mute == 1 {
             draw_text_mut(
                 image,
                 Rgb([255u8, 255u8, 255u8]),
-                118,
-                38,
-                PxScale { x: 10.0, y: 10.0 },
+                layout.x(118),
+                layout.y(38),
+                layout.scale(10.0),
                 &self.symbols,
                 mute_speaker,
             );
         }
     }
 
-    fn draw_cover(&mut self, music_player_info: &MusicPlayerInfo) -> Vec<u8> {
-        if !music_player_info.player_active {
-            return vec![0; 320 * 170 * 3];
-        }
-        let mut dyn_image_base = image::DynamicImage::new_rgb8(320, 170);
-        // TODO: replace me with real cover
-        let mut cover = RgbImage::new(170, 170);
-
-        if music_player_info.cover.len() == cover.len() {
-            cover.copy_from_slice(music_player_info.cover.as_bytes());
-        }
-
-        let dyn_image_cover = image::DynamicImage::ImageRgb8(cover);
-
-        draw_filled_rect_mut(
-            &mut dyn_image_base,
-            Rect::at(0, 0).of_size(320, 170),
-            image::Rgba([211u8, 211u8, 211u8, 255]),
-        );
-        image::imageops::overlay(&mut dyn_image_base, &dyn_image_cover, 75, 0);
-
-        return dyn_image_base.as_bytes().to_vec();
-    }
-
     fn draw_volume_bar(
         &mut self,
         system_volume: f32,
         playback_status: isize,
         image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
     ) {
-        let progress = (1.0 + (238.0 * system_volume)) as u32;
+        let bar_width = layout.w(238);
+        let bar_height = layout.h(6);
+        let progress = (1.0 + (bar_width as f32 * system_volume)) as u32;
 
         draw_hollow_rect_mut(
             image,
-            Rect::at(16, 50).of_size(238, 6),
+            Rect::at(layout.x(16), layout.y(50)).of_size(bar_width, bar_height),
             Rgb([255u8, 255u8, 255u8]),
         );
         let small_speaker = &String::from("\u{f027}");
@@ -327,9 +451,9 @@ impl MediaInfoScreen {
         draw_text_mut(
             image,
             Rgb([255u8, 255u8, 255u8]),
-            16,
-            38,
-            PxScale { x: 10.0, y: 10.0 },
+            layout.x(16),
+            layout.y(38),
+            layout.scale(10.0),
             &self.symbols,
             small_speaker,
         );
@@ -337,75 +461,466 @@ impl MediaInfoScreen {
         draw_text_mut(
             image,
             Rgb([255u8, 255u8, 255u8]),
-            240,
-            37,
-            PxScale { x: 10.0, y: 10.0 },
+            layout.x(240),
+            layout.y(37),
+            layout.scale(10.0),
             &self.symbols,
             big_speaker,
         );
         draw_filled_rect_mut(
             image,
-            Rect::at(16, 50).of_size(progress, 6),
+            Rect::at(layout.x(16), layout.y(50)).of_size(progress, bar_height),
             Rgb([255u8, 255u8, 255u8]),
         );
 
-        self.draw_play_button(playback_status, image);
+        self.draw_play_button(playback_status, image, layout);
     }
 
-    fn draw_companion_screen(&mut self, music_player_info: &MusicPlayerInfo) {
-        // draw companion image
-        if music_player_info.filepath != self.music_player_info.filepath
-            || (music_player_info.filepath.is_empty() && self.music_player_info.filepath.is_empty())
-        {
-            *self.screen.device_screen_bytes.get_mut(ESP32).unwrap() =
-                self.draw_cover(music_player_info);
+    // the cover of the player thread, scaled for the companion display. the cover and its
+    // accent color are only computed again, if the cover changes.
+    fn companion_cover(
+        &mut self,
+        music_player_info: &MusicPlayerInfo,
+        size: u32,
+    ) -> (RgbImage, Rgb<u8>) {
+        let key = music_player_info
+            .cover
+            .as_ref()
+            .map(|cover| cover.key.clone());
+        if let Some((last_key, cover, accent)) = &self.companion_cover {
+            if *last_key == key && cover.width() == size {
+                return (cover.clone(), *accent);
+            }
         }
+
+        let cover = match &music_player_info.cover {
+            Some(cover) => cover.thumbnail(size),
+            None => RgbImage::from_pixel(size, size, Rgb([211u8, 211u8, 211u8])),
+        };
+        let accent = accent_color(cover.as_raw()).unwrap_or(DEFAULT_ACCENT);
+        self.companion_cover = Some((key, cover.clone(), accent));
+        (cover, accent)
     }
 
-    fn draw_screen(&mut self, music_player_info: &MusicPlayerInfo) {
-        let mut image = RgbImage::new(256, 64);
-        let scale = PxScale { x: 16.0, y: 16.0 };
-        let seconds = Duration::from_secs(3);
-        if self.screen.mode_timeout.unwrap_or(Instant::now()).elapsed() >= seconds {
-            self.screen.mode = 0;
+    fn draw_companion_progress(
+        &self,
+        music_player_info: &MusicPlayerInfo,
+        accent: Rgb<u8>,
+        left: i32,
+        width: i32,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+    ) {
+        let gray = Rgb([170u8, 170u8, 170u8]);
+        draw_text_mut(
+            image,
+            accent,
+            left,
+            layout.y(80),
+            layout.scale(18.0),
+            &self.symbols,
+            play_state_symbol(music_player_info.playback_status),
+        );
+        if music_player_info.mute == 1 {
+            draw_text_mut(
+                image,
+                gray,
+                left + layout.x(28),
+                layout.y(80),
+                layout.scale(18.0),
+                &self.symbols,
+                "\u{f6a9}",
+            );
         }
 
-        if (music_player_info.artist != self.music_player_info.artist)
-            || (music_player_info.title != self.music_player_info.title)
-        {
-            self.title_x = 0;
-        }
+        let elapsed = format_duration(
+            Duration::from_millis(music_player_info.current_track_position.max(0) as u64),
+            DurationFormat::Track,
+        );
+        let total = format_duration(
+            Duration::from_secs(music_player_info.track_length.max(0) as u64),
+            DurationFormat::Track,
+        );
+        let scale = layout.scale(14.0);
+        draw_text_mut(
+            image,
+            gray,
+            left,
+            layout.y(108),
+            scale,
+            &self.screen.font,
+            &elapsed,
+        );
+        let total_width = text_size(scale, &self.screen.font, &total).0 as i32;
+        draw_text_mut(
+            image,
+            gray,
+            left + width - total_width,
+            layout.y(108),
+            scale,
+            &self.screen.font,
+            &total,
+        );
 
-        if music_player_info.artist != self.music_player_info.artist {
-            self.artist_x = 0;
+        let progress = if music_player_info.track_length > 0 {
+            (music_player_info.current_track_position as f64
+                / 1000.0
+                / music_player_info.track_length as f64)
+                .clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let bar_height = layout.h(6);
+        draw_filled_rect_mut(
+            image,
+            Rect::at(left, layout.y(128)).of_size(width.max(1) as u32, bar_height),
+            Rgb([60u8, 60u8, 60u8]),
+        );
+        let filled = (width as f64 * progress) as u32;
+        if filled > 0 {
+            draw_filled_rect_mut(
+                image,
+                Rect::at(left, layout.y(128)).of_size(filled, bar_height),
+                accent,
+            );
         }
+    }
 
-        if music_player_info.player_active {
-            self.draw_artist(&music_player_info.artist, &mut image, scale);
-            self.draw_title(&music_player_info.title, &mut image, scale);
-            self.draw_mute_speaker(music_player_info.mute, &mut image);
-
-            if self.screen.mode == 0 {
-                self.draw_play_button(music_player_info.playback_status, &mut image);
-                self.draw_elapsed(music_player_info.current_track_position, &mut image);
-                self.draw_total(music_player_info.track_length, &mut image);
-                self.draw_elapsed_bar(
-                    music_player_info.current_track_position,
-                    music_player_info.track_length,
-                    &mut image,
-                );
+    // shown for a few seconds after the volume changed, instead of the progress
+    fn draw_companion_volume(
+        &self,
+        music_player_info: &MusicPlayerInfo,
+        accent: Rgb<u8>,
+        left: i32,
+        width: i32,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+    ) {
+        let top = layout.y(76);
+        let height = layout.h(72);
+        draw_filled_rect_mut(
+            image,
+            Rect::at(left, top).of_size(width.max(1) as u32, height),
+            Rgb([20u8, 20u8, 20u8]),
+        );
+        draw_hollow_rect_mut(
+            image,
+            Rect::at(left, top).of_size(width.max(1) as u32, height),
+            accent,
+        );
+
+        let muted = music_player_info.mute == 1;
+        draw_text_mut(
+            image,
+            accent,
+            left + layout.x(8),
+            top + layout.y(10),
+            layout.scale(18.0),
+            &self.symbols,
+            if muted { "\u{f6a9}" } else { "\u{f028}" },
+        );
+        let volume = (music_player_info.system_volume * 100.0).round() as i32;
+        draw_text_mut(
+            image,
+            Rgb([255u8, 255u8, 255u8]),
+            left + layout.x(36),
+            top + layout.y(10),
+            layout.scale(18.0),
+            &self.screen.font,
+            &if muted {
+                String::from("Muted")
             } else {
-                // DRAW VOLUME BAR
-                self.draw_volume_bar(
-                    music_player_info.system_volume,
-                    music_player_info.playback_status,
-                    &mut image,
+                format!("Volume {}%", volume)
+            },
+        );
+
+        let bar_left = left + layout.x(8);
+        let bar_width = (width - layout.x(16)).max(1) as u32;
+        let bar_top = top + layout.y(46);
+        draw_hollow_rect_mut(
+            image,
+            Rect::at(bar_left, bar_top).of_size(bar_width, layout.h(10)),
+            Rgb([90u8, 90u8, 90u8]),
+        );
+        let filled = (bar_width as f32 * music_player_info.system_volume.clamp(0.0, 1.0)) as u32;
+        if filled > 0 {
+            draw_filled_rect_mut(
+                image,
+                Rect::at(bar_left, bar_top).of_size(filled, layout.h(10)),
+                if muted {
+                    Rgb([90u8, 90u8, 90u8])
+                } else {
+                    accent
+                },
+            );
+        }
+    }
+
+    // the lyrics page of the companion display: the title on top, the previous, the current
+    // and the next lines below, the current line is highlighted
+    fn draw_companion_lyrics(
+        &mut self,
+        lyrics: &Lyrics,
+        music_player_info: &MusicPlayerInfo,
+        accent: Rgb<u8>,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+    ) {
+        let gray = Rgb([150u8, 150u8, 150u8]);
+        let area = |y: i32| MarqueeArea {
+            x: layout.x(10),
+            y,
+            width: layout.width() as i32 - layout.x(20),
+            centered: true,
+        };
+        self.scrolling |= self.title_marquee.draw(
+            image,
+            &self.screen.font,
+            layout.scale(16.0),
+            accent,
+            area(layout.y(8)),
+        );
+
+        let line = lyrics_line(lyrics, music_player_info);
+        if let Some(previous) = line
+            .and_then(|line| line.checked_sub(1))
+            .and_then(|line| lyrics.lines.get(line))
+        {
+            self.draw_centered_text(
+                &previous.text,
+                layout.y(40),
+                layout.scale(18.0),
+                gray,
+                image,
+            );
+        }
+        let color = if lyrics.synced {
+            Rgb([255u8, 255u8, 255u8])
+        } else {
+            Rgb([200u8, 200u8, 200u8])
+        };
+        self.scrolling |= self.lyrics_marquee.draw(
+            image,
+            &self.screen.font,
+            layout.scale(24.0),
+            color,
+            area(layout.y(68)),
+        );
+        let next = line.map_or(0, |line| line + 1);
+        for (row, line) in lyrics.lines.iter().skip(next).take(2).enumerate() {
+            self.draw_centered_text(
+                &line.text,
+                layout.y(102 + row as i32 * 26),
+                layout.scale(18.0),
+                gray,
+                image,
+            );
+        }
+
+        if music_player_info.track_length > 0 {
+            let progress = (music_player_info.current_track_position as f64 / 1000.0)
+                / music_player_info.track_length as f64;
+            let progress_width = (layout.width() as f64 * progress.clamp(0.0, 1.0)) as u32;
+            if progress_width > 0 {
+                draw_filled_rect_mut(
+                    image,
+                    Rect::at(0, layout.height() as i32 - layout.h(4) as i32)
+                        .of_size(progress_width, layout.h(4)),
+                    accent,
                 );
             }
+        }
+    }
+
+    // the color layout of the companion display: the cover on the left side,
+    // artist, title, progress and volume on the right side
+    fn draw_companion(&mut self, music_player_info: &MusicPlayerInfo, layout: &Layout) -> Vec<u8> {
+        let mut image = RgbImage::new(layout.width(), layout.height());
+        if !music_player_info.player_active {
+            let white = Rgb([255u8, 255u8, 255u8]);
+            draw_text_mut(
+                &mut image,
+                white,
+                layout.x(104),
+                layout.y(56),
+                layout.scale(22.0),
+                &self.screen.font,
+                "Media Screen",
+            );
+            draw_text_mut(
+                &mut image,
+                Rgb([170u8, 170u8, 170u8]),
+                layout.x(102),
+                layout.y(88),
+                layout.scale(18.0),
+                &self.screen.font,
+                "Winamp inactive",
+            );
+            return image.into_vec();
+        }
+
+        let (cover, accent) = self.companion_cover(music_player_info, layout.h(150));
+        draw_filled_rect_mut(
+            &mut image,
+            Rect::at(0, 0).of_size(layout.width(), layout.height()),
+            Rgb(accent.0.map(|channel| channel / 6)),
+        );
+        if let Some(lyrics) = self.visible_lyrics(music_player_info) {
+            self.draw_companion_lyrics(&lyrics, music_player_info, accent, &mut image, layout);
+            return image.into_vec();
+        }
+        image::imageops::overlay(&mut image, &cover, layout.x(10) as i64, layout.y(10) as i64);
+
+        let left = layout.x(10) + cover.width() as i32 + layout.x(12);
+        let width = layout.width() as i32 - left - layout.x(10);
+        self.scrolling |= self.artist_marquee.draw(
+            &mut image,
+            &self.screen.font,
+            layout.scale(16.0),
+            Rgb([170u8, 170u8, 170u8]),
+            MarqueeArea {
+                x: left,
+                y: layout.y(12),
+                width,
+                centered: false,
+            },
+        );
+        self.scrolling |= self.title_marquee.draw(
+            &mut image,
+            &self.screen.font,
+            layout.scale(22.0),
+            Rgb([255u8, 255u8, 255u8]),
+            MarqueeArea {
+                x: left,
+                y: layout.y(34),
+                width,
+                centered: false,
+            },
+        );
+        self.scrolling |= self.details_marquee.draw(
+            &mut image,
+            &self.screen.font,
+            layout.scale(14.0),
+            Rgb([130u8, 130u8, 130u8]),
+            MarqueeArea {
+                x: left,
+                y: layout.y(60),
+                width,
+                centered: false,
+            },
+        );
+
+        if !self.show_volume {
+            self.draw_companion_progress(
+                music_player_info,
+                accent,
+                left,
+                width,
+                &mut image,
+                layout,
+            );
+        } else {
+            self.draw_companion_volume(music_player_info, accent, left, width, &mut image, layout);
+        }
+        image.into_vec()
+    }
+
+    fn draw_player_info(
+        &mut self,
+        music_player_info: &MusicPlayerInfo,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+    ) {
+        if !music_player_info.player_active {
+            self.draw_intro(image, layout);
+            return;
+        }
+        if let Some(lyrics) = self.visible_lyrics(music_player_info) {
+            self.draw_lyrics(&lyrics, music_player_info, image, layout);
+            return;
+        }
+
+        self.draw_artist(image, layout);
+        self.draw_title(image, layout);
+        self.draw_mute_speaker(music_player_info.mute, image, layout);
+
+        if !self.show_volume {
+            self.draw_play_button(music_player_info.playback_status, image, layout);
+            self.draw_elapsed(music_player_info.current_track_position, image, layout);
+            self.draw_total(music_player_info.track_length, image, layout);
+            self.draw_elapsed_bar(
+                music_player_info.current_track_position,
+                music_player_info.track_length,
+                image,
+                layout,
+            );
         } else {
-            self.draw_intro(&mut image, scale);
+            // DRAW VOLUME BAR
+            self.draw_volume_bar(
+                music_player_info.system_volume,
+                music_player_info.playback_status,
+                image,
+                layout,
+            );
+        }
+    }
+
+    fn draw_screen(&mut self, music_player_info: &MusicPlayerInfo) {
+        self.show_volume = self.screen.in_mode(VOLUME_MODE);
+
+        // the speed can be changed while the screen is shown
+        let config_manager = self.screen.config_manager.read().unwrap();
+        let speed = config_manager
+            .get_integer_value(&self.screen.key, "scroll_speed")
+            .unwrap_or(DEFAULT_SCROLL_SPEED);
+        let pause = config_manager
+            .get_integer_value(&self.screen.key, "scroll_pause")
+            .map(|seconds| Duration::from_secs(seconds as u64))
+            .unwrap_or(DEFAULT_SCROLL_PAUSE);
+        let details = config_manager
+            .get_string_value(&self.screen.key, "details")
+            .unwrap_or_else(|| DEFAULT_DETAILS.into());
+        self.show_lyrics = config_manager
+            .get_string_value(&self.screen.key, "lyrics")
+            .is_some_and(|lyrics| lyrics == "on");
+        drop(config_manager);
+        self.artist_marquee.set_speed(speed, pause);
+        self.title_marquee.set_speed(speed, pause);
+        self.lyrics_marquee.set_speed(speed, pause);
+        self.details_marquee.set_speed(speed, pause);
+
+        // the title starts from the beginning with a new artist, even if the title is the same
+        if music_player_info.artist != self.music_player_info.artist {
+            self.title_marquee.restart();
+        }
+        self.artist_marquee.set_text(&music_player_info.artist);
+        self.title_marquee.set_text(&music_player_info.title);
+        self.details_marquee
+            .set_text(&music_player_info.metadata.details(&details));
+        let lyrics_text = music_player_info
+            .lyrics
+            .as_ref()
+            .and_then(|lyrics| {
+                lyrics_line(lyrics, music_player_info).map(|line| lyrics.lines[line].text.clone())
+            })
+            .unwrap_or_default();
+        self.lyrics_marquee.set_text(&lyrics_text);
+
+        self.scrolling = false;
+        for (key, device) in DEVICES.iter() {
+            if key == ESP32 {
+                let layout = Layout::new(device.screen_width(), device.screen_height(), 320, 170);
+                let bytes = self.draw_companion(music_player_info, &layout);
+                self.screen.device_screen_bytes.insert(key.clone(), bytes);
+                continue;
+            }
+            let layout = Layout::new(device.screen_width(), device.screen_height(), 256, 64);
+            let mut image = RgbImage::new(layout.width(), layout.height());
+            self.draw_player_info(music_player_info, &mut image, &layout);
+            self.screen
+                .device_screen_bytes
+                .insert(key.clone(), image.into_vec());
         }
-        *self.screen.device_screen_bytes.get_mut(TEENSY).unwrap() = image.into_vec();
     }
 
     pub fn new(
@@ -418,54 +933,66 @@ impl MediaInfoScreen {
         let (tx, rx): (Sender<MusicPlayerInfo>, Receiver<MusicPlayerInfo>) = bounded(1);
         let active = Arc::new(AtomicBool::new(false));
 
+        let mut params = IndexMap::new();
+        params.insert(
+            "scroll_speed".into(),
+            ConfigParam::Integer(DEFAULT_SCROLL_SPEED),
+        );
+        params.insert(
+            "scroll_pause".into(),
+            ConfigParam::Integer(DEFAULT_SCROLL_PAUSE.as_secs() as u32),
+        );
+        // any of album, album_artist, track, year, genre and duration
+        params.insert(
+            "details".into(),
+            ConfigParam::String(DEFAULT_DETAILS.into()),
+        );
+        // folder images are looked up in this order, if the track has no embedded cover
+        params.insert(
+            "cover_patterns".into(),
+            ConfigParam::String(DEFAULT_COVER_PATTERNS.into()),
+        );
+        // "on" shows the lyrics of the track instead of the progress
+        params.insert("lyrics".into(), ConfigParam::String("off".into()));
+        // the player of the transport controls: auto, winamp, mpris or mpd
+        params.insert(
+            "backend".into(),
+            ConfigParam::String(DEFAULT_BACKEND.into()),
+        );
+        params.insert(
+            "mpd_address".into(),
+            ConfigParam::String(DEFAULT_MPD_ADDRESS.into()),
+        );
+        // port of the local api, 0 turns it off. read at startup.
+        params.insert("api_port".into(), ConfigParam::Integer(DEFAULT_API_PORT));
+        // "on" records the played tracks for the listening stats, even while the screen is hidden
+        params.insert("record_plays".into(), ConfigParam::String("on".into()));
+        // seconds the volume is shown after it was changed
+        params.insert(
+            format!("{}_duration", VOLUME_MODE),
+            ConfigParam::Integer(VOLUME_MODE_DURATION.as_secs() as u32),
+        );
+
         let mut this = MediaInfoScreen {
             screen: Screen {
                 description,
                 font,
-                config_manager,
-                key,
+                config_manager: config_manager.clone(),
+                config_layout: ExchangeableConfig { params },
+                modes: vec![ScreenMode::new(VOLUME_MODE, VOLUME_MODE_DURATION)],
+                key: key.clone(),
                 active: active.clone(),
                 handle: Some(thread::spawn(move || {
                     let sender = tx.to_owned();
                     let active = active;
-                    let match_correct_artist_and_title_format;
-                    let match_artist_and_title;
-                    let match_artist_or_title;
-
-                    match regex::Regex::new(r"\s(.*)-") {
-                        Ok(regex) => {
-                            match_correct_artist_and_title_format = regex;
-                        }
-                        Err(err) => {
-                            eprintln!("REGEX ERROR: {:?}", err);
-                            return;
-                        }
-                    }
-                    match regex::Regex::new(r"(.*?) - (.*)") {
-                        Ok(regex) => {
-                            match_artist_and_title = regex;
-                        }
-                        Err(err) => {
-                            eprintln!("REGEX ERROR: {:?}", err);
-                            return;
-                        }
-                    }
-
-                    match regex::Regex::new(r"\s(.*?)\s") {
-                        Ok(regex) => {
-                            match_artist_or_title = regex;
-                        }
-                        Err(err) => {
-                            eprintln!("REGEX ERROR: {:?}", err);
-                            return;
-                        }
-                    }
-
                     winsafe::CoInitializeEx(co::COINIT::APARTMENTTHREADED).unwrap();
                     let mut cover_manager = CoverManager::default();
+                    let mut play_tracker = PlayTracker::default();
                     loop {
-                        while !active.load(Ordering::Acquire) {
-                            thread::park();
+                        while !active.load(Ordering::Acquire)
+                            && !record_plays(&config_manager, &key)
+                        {
+                            thread::park_timeout(HIDDEN_POLL_INTERVAL);
                         }
                         let mut music_player_info: MusicPlayerInfo = Default::default();
 
@@ -528,28 +1055,34 @@ impl MediaInfoScreen {
 
                                         let path = extract_current_cover_path(&window);
 
+                                        if path != cover_manager.track_path {
+                                            cover_manager.metadata = extract_track_metadata(&path);
+                                            cover_manager.lyrics =
+                                                extract_lyrics(&path).map(Arc::new);
+                                            cover_manager.track_path = path.clone();
+                                        }
+                                        music_player_info.lyrics = cover_manager.lyrics.clone();
+
                                         if path != cover_manager.last_path {
-                                            let file_exists =
-                                                Path::new(std::ffi::OsStr::new(&path)).exists();
-                                            if file_exists {
-                                                music_player_info.filepath = path.clone();
-                                                match extract_cover_image(&path) {
-                                                    Some(cover) => {
-                                                        music_player_info.cover =
-                                                            cover.data.as_bytes().to_vec();
-                                                        cover_manager.last_path = path.clone();
-                                                        cover_manager.current_cover =
-                                                            cover.data.as_bytes().to_vec();
-                                                    }
-                                                    None => {}
-                                                }
-                                            } else {
-                                                music_player_info.cover = vec![211; 320 * 170 * 3];
-                                            }
-                                        } else {
-                                            music_player_info.cover =
-                                                cover_manager.current_cover.clone();
+                                            let cache_key =
+                                                cover_key(&path, cover_manager.metadata.as_ref());
+                                            let patterns = cover_patterns(
+                                                &config_manager
+                                                    .read()
+                                                    .unwrap()
+                                                    .get_string_value(&key, "cover_patterns")
+                                                    .unwrap_or_else(|| {
+                                                        DEFAULT_COVER_PATTERNS.into()
+                                                    }),
+                                            );
+                                            cover_manager.current_cover =
+                                                cover_manager.cache.get_or_load(&cache_key, || {
+                                                    load_cover(&path, &patterns)
+                                                });
+                                            cover_manager.last_path = path.clone();
                                         }
+                                        music_player_info.cover =
+                                            cover_manager.current_cover.clone();
 
                                         let buffer_length = title_length + 1;
                                         let mut buffer =
@@ -566,94 +1099,85 @@ impl MediaInfoScreen {
 
                                         let data = String::from_utf16_lossy(&buffer);
 
-                                        music_player_info.player_active = title_length > 0
-                                            && match_correct_artist_and_title_format
-                                                .is_match(&data);
-
-                                        let caps =
-                                            match_correct_artist_and_title_format.captures(&data);
-                                        match caps {
-                                            Some(caps) => {
-                                                let artist_and_title =
-                                                    caps.get(1).map_or("", |m| m.as_str()).trim();
-
-                                                let artist_and_title_caps = match_artist_and_title
-                                                    .captures(artist_and_title);
-                                                match artist_and_title_caps {
-                                                    Some(artist_and_title_caps) => {
-                                                        let artist = artist_and_title_caps
-                                                            .get(1)
-                                                            .map_or("", |m| m.as_str())
-                                                            .trim();
-                                                        let title = artist_and_title_caps
-                                                            .get(2)
-                                                            .map_or("", |m| m.as_str())
-                                                            .trim();
-
-                                                        music_player_info.artist =
-                                                            artist.to_string();
-                                                        music_player_info.title = title.to_string();
-                                                    }
-                                                    None => {
-                                                        // check, if only artist OR title are there
-                                                        let artist_or_title_caps =
-                                                            match_artist_or_title.captures(&data);
-
-                                                        match artist_or_title_caps {
-                                                            Some(artist_or_title_caps) => {
-                                                                let title = artist_or_title_caps
-                                                                    .get(1)
-                                                                    .map_or("", |m| m.as_str())
-                                                                    .trim();
-                                                                music_player_info.title =
-                                                                    title.to_string();
-                                                                music_player_info.artist =
-                                                                    "".into();
-                                                            }
-                                                            None => {
-                                                                music_player_info.player_active =
-                                                                    false;
-                                                                music_player_info.filepath.clear();
-                                                            }
-                                                        }
-                                                    }
+                                        // the tags are preferred, the caption is the fallback
+                                        // for streams and files without tags
+                                        let caption = parse_window_title(&data);
+                                        music_player_info.player_active =
+                                            title_length > 0 && caption.is_some();
+                                        match (caption, &cover_manager.metadata) {
+                                            (Some(_), Some(metadata)) => {
+                                                music_player_info.artist =
+                                                    metadata.display_artist().to_string();
+                                                music_player_info.title = metadata.title.clone();
+                                                music_player_info.metadata = metadata.clone();
+                                                if music_player_info.track_length <= 0 {
+                                                    music_player_info.track_length =
+                                                        metadata.duration.map_or(0, |duration| {
+                                                            duration.as_secs() as isize
+                                                        });
                                                 }
                                             }
-                                            None => {
+                                            (Some((artist, title)), None) => {
+                                                music_player_info.artist = artist;
+                                                music_player_info.title = title;
+                                            }
+                                            (None, _) => {
                                                 music_player_info.player_active = false;
-                                                music_player_info.filepath.clear();
                                             }
                                         }
                                     }
                                     None => {
                                         music_player_info.player_active = false;
-                                        music_player_info.filepath.clear();
                                     }
                                 }
                             }
                             Err(_) => {
                                 music_player_info.player_active = false;
-                                music_player_info.filepath.clear();
                             }
                         }
 
                         let volume_data = crate::helpers::master_volume::get_master_volume();
                         music_player_info.system_volume = volume_data.0;
                         music_player_info.mute = volume_data.1;
+
+                        if record_plays(&config_manager, &key) {
+                            let track = now_playing(&music_player_info, &cover_manager.track_path);
+                            let position = Duration::from_millis(
+                                music_player_info.current_track_position.max(0) as u64,
+                            );
+                            if let Some(play) = play_tracker.update(
+                                track.as_ref(),
+                                music_player_info.playback_status == 1,
+                                position,
+                                chrono::Utc::now(),
+                            ) {
+                                record_play(Path::new(PLAY_HISTORY_PATH), &play);
+                            }
+                        }
+
                         sender.try_send(music_player_info).unwrap_or_default();
-                        thread::sleep(Duration::from_millis(200));
+                        if active.load(Ordering::Acquire) {
+                            thread::sleep(Duration::from_millis(200));
+                        } else {
+                            thread::sleep(HIDDEN_POLL_INTERVAL);
+                        }
                     }
                 })),
                 ..Default::default()
             },
             music_player_info: Default::default(),
             symbols: FontArc::clone(&symbols),
-            title_x: 0,
-            artist_x: 0,
+            artist_marquee: Marquee::default(),
+            title_marquee: Marquee::default(),
+            details_marquee: Marquee::default(),
+            lyrics_marquee: Marquee::default(),
+            show_lyrics: false,
+            show_volume: false,
+            scrolling: false,
             receiver: rx,
+            companion_cover: None,
         };
         this.draw_screen(&Default::default());
-        this.draw_companion_screen(&Default::default());
         this
     }
 }
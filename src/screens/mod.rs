@@ -6,12 +6,38 @@ use exchange_format::ExchangeableConfig;
 use std::collections::HashMap;
 use std::sync::{atomic::AtomicBool, atomic::Ordering, Arc, RwLock};
 use std::thread::JoinHandle;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+pub mod agenda_screen;
+pub mod climate_screen;
+pub mod clock_screen;
+pub mod listening_stats_screen;
 pub mod media_info_screen;
 pub mod plugin_screen;
+pub mod process_screen;
 pub mod system_info_screen;
+pub mod visualizer_screen;
 pub mod weather_screen;
 
+// the mode every screen has, it is shown unless another mode was requested
+pub const NORMAL_MODE: &str = "normal";
+
+// a named page of a screen, e.g. the volume of the media screen
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScreenMode {
+    pub name: String,
+    // how long the mode is shown, unless the "<name>_duration" setting of the screen says otherwise
+    pub duration: Duration,
+}
+
+impl ScreenMode {
+    pub fn new(name: &str, duration: Duration) -> ScreenMode {
+        ScreenMode {
+            name: name.into(),
+            duration,
+        }
+    }
+}
+
 pub struct Screen {
     pub description: String,
     pub key: String,
@@ -20,8 +46,11 @@ pub struct Screen {
     pub symbols: FontArc,
     pub active: Arc<AtomicBool>,
     pub handle: Option<JoinHandle<()>>,
-    pub mode: u32,
-    pub mode_timeout: Option<Instant>,
+    // the named pages of the screen besides the normal one
+    pub modes: Vec<ScreenMode>,
+    pub mode: String,
+    // the screen returns to the normal mode afterwards
+    pub mode_until: Option<Instant>,
     pub config_manager: Arc<RwLock<ConfigManager>>,
     pub config_layout: ExchangeableConfig,
 }
@@ -48,14 +77,30 @@ impl Default for Screen {
 
             active: Arc::new(AtomicBool::new(false)),
             handle: None,
-            mode: 0,
-            mode_timeout: Some(Instant::now()),
+            modes: Vec::new(),
+            mode: NORMAL_MODE.into(),
+            mode_until: None,
             config_manager: Arc::new(RwLock::new(ConfigManager::new(None))),
             config_layout: ExchangeableConfig::default(),
         }
     }
 }
 
+impl Screen {
+    // the requested mode, or the normal mode once its time is up
+    pub fn current_mode(&mut self) -> &str {
+        if self.mode_until.is_some_and(|until| Instant::now() >= until) {
+            self.mode = NORMAL_MODE.into();
+            self.mode_until = None;
+        }
+        &self.mode
+    }
+
+    pub fn in_mode(&mut self, mode: &str) -> bool {
+        self.current_mode() == mode
+    }
+}
+
 pub trait Screenable {
     fn get_screen(&mut self) -> &mut Screen;
 }
@@ -101,10 +146,42 @@ pub trait BasicScreen: Screenable {
         self.get_screen().active.store(false, Ordering::Release)
     }
 
-    fn set_mode(&mut self, mode: u32) {
+    fn modes(&mut self) -> Vec<ScreenMode> {
+        self.get_screen().modes.clone()
+    }
+
+    // the configured duration of a mode in seconds, the declared one otherwise
+    fn mode_duration(&mut self, mode: &str) -> Option<Duration> {
+        let declared = self
+            .modes()
+            .into_iter()
+            .find(|declared| declared.name == mode)?;
+        let screen = self.get_screen();
+        let configured = screen
+            .config_manager
+            .read()
+            .unwrap()
+            .get_integer_value(&screen.key, &format!("{}_duration", mode));
+        Some(
+            configured
+                .map(|seconds| Duration::from_secs(seconds as u64))
+                .unwrap_or(declared.duration),
+        )
+    }
+
+    // shows the mode for the duration, returns false if the screen does not know the mode
+    fn set_mode(&mut self, mode: &str, duration: Duration) -> bool {
+        if mode != NORMAL_MODE && !self.modes().iter().any(|declared| declared.name == mode) {
+            return false;
+        }
         let screen = self.get_screen();
-        screen.mode_timeout = Some(Instant::now());
-        screen.mode = mode;
+        screen.mode = mode.into();
+        screen.mode_until = if mode == NORMAL_MODE {
+            None
+        } else {
+            Some(Instant::now() + duration)
+        };
+        true
     }
 
     fn enabled(&mut self) -> bool {
@@ -129,4 +206,14 @@ pub trait BasicScreen: Screenable {
     fn set_current_config(&mut self, _config: ExchangeableConfig) {
         // implement, if needed
     }
+
+    // how often the app redraws the screen and sends it to the devices
+    fn refresh_interval(&self) -> Duration {
+        Duration::from_millis(250)
+    }
+
+    // returns true once, if the screen wants to be shown for a while, e.g. before a meeting starts
+    fn priority_request(&mut self) -> bool {
+        false
+    }
 }
@@ -1,15 +1,26 @@
 use crate::config_manager::ConfigManager;
-use crate::screens::{BasicScreen, Screen, Screenable};
-use crate::DEVICES;
+use crate::helpers::marquee::{Marquee, MarqueeArea};
+use crate::screens::{BasicScreen, Screen, ScreenMode, Screenable, NORMAL_MODE};
+use crate::sensors::SensorValue;
+use crate::{DEVICES, SENSORS};
 use ab_glyph::{FontArc, PxScale};
 use exchange_format::*;
 use image::{EncodableLayout, GenericImage, ImageBuffer, Rgb, RgbImage};
-use imageproc::drawing::draw_text_mut;
 use libloading::Library;
+use serde::Deserialize;
 
+use std::collections::HashMap;
 use std::ffi::CString;
 use std::path::PathBuf;
 use std::sync::{atomic::AtomicBool, Arc, RwLock};
+use std::time::Duration;
+
+// a page of a plugin, e.g. {"name": "details", "duration": 10} with the duration in seconds
+#[derive(Deserialize)]
+struct PluginMode {
+    name: String,
+    duration: u64,
+}
 
 struct Lib {
     library: Library,
@@ -64,6 +75,43 @@ impl Lib {
         }
     }
 
+    // optional, the pages of the plugin besides the normal one as json
+    fn get_modes(&self) -> Vec<ScreenMode> {
+        let get_modes: std::result::Result<
+            libloading::Symbol<unsafe extern "C" fn() -> *mut i8>,
+            libloading::Error,
+        > = unsafe { self.library.get(b"get_modes") };
+
+        match get_modes {
+            Ok(get_modes) => {
+                // plugins without pages of their own may return null
+                let ptr = unsafe { get_modes() };
+                if ptr.is_null() {
+                    return Vec::new();
+                }
+                let json = unsafe { CString::from_raw(ptr).to_string_lossy().to_string() };
+                serde_json::from_str::<Vec<PluginMode>>(&json)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|mode| ScreenMode::new(&mode.name, Duration::from_secs(mode.duration)))
+                    .collect()
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+
+    // optional, plugins are told the mode to draw before they draw
+    fn set_mode(&self, mode: &str) {
+        if let Ok(set_mode) = unsafe {
+            self.library
+                .get::<libloading::Symbol<unsafe extern "C" fn(*mut i8)>>(b"set_mode")
+        } {
+            if let Ok(mode) = CString::new(mode) {
+                unsafe { set_mode(mode.into_raw()) }
+            }
+        }
+    }
+
     // TODO: maybe give this method parameters of which screen should be drawn
     fn get_screen(&self, device: &String) -> Option<ExchangeFormat> {
         match unsafe {
@@ -102,11 +150,28 @@ impl Lib {
             unsafe { set_current_config(config) }
         }
     }
+
+    // optional, plugins receive the latest sensor values as json before they draw
+    fn set_sensor_values(&self, values: &[SensorValue]) {
+        if let Ok(set_sensor_values) = unsafe {
+            self.library
+                .get::<libloading::Symbol<unsafe extern "C" fn(*mut i8)>>(b"set_sensor_values")
+        } {
+            let json = serde_json::to_string(values).unwrap_or_default();
+            if let Ok(values) = CString::new(json) {
+                unsafe { set_sensor_values(values.into_raw()) }
+            }
+        }
+    }
 }
 
 pub struct PluginScreen {
     screen: Screen,
     lib: Arc<Lib>,
+    // texts, that are wider than the display, scroll. keyed by the device and the index of the item.
+    marquees: HashMap<(String, usize), Marquee>,
+    // the mode the plugin was told last
+    plugin_mode: String,
 }
 
 impl Screenable for PluginScreen {
@@ -117,6 +182,13 @@ impl Screenable for PluginScreen {
 
 impl BasicScreen for PluginScreen {
     fn update(&mut self) {
+        let mode = self.screen.current_mode().to_string();
+        if mode != self.plugin_mode {
+            self.lib.clone().set_mode(&mode);
+            self.plugin_mode = mode;
+        }
+        let sensor_values = SENSORS.read().unwrap().values();
+        self.lib.clone().set_sensor_values(&sensor_values);
         for (key, device) in DEVICES.iter() {
             match self.lib.clone().get_screen(key) {
                 Some(screen) => {
@@ -147,16 +219,20 @@ impl PluginScreen {
         exchange_format: ExchangeFormat,
     ) {
         let mut image = RgbImage::new(width, height);
-        self.draw_exchange_format(&mut image, exchange_format);
+        self.draw_exchange_format(device, &mut image, exchange_format);
         *self.screen.device_screen_bytes.get_mut(device).unwrap() = image.into_vec();
     }
 
     pub fn draw_exchange_format(
         &mut self,
+        device: &str,
         image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
         exchange_format: ExchangeFormat,
     ) {
-        for item in exchange_format.items.iter() {
+        let item_count = exchange_format.items.len();
+        self.marquees
+            .retain(|(key, index), _| key != device || *index < item_count);
+        for (index, item) in exchange_format.items.iter().enumerate() {
             match item {
                 Item::Text(text) => {
                     // determine color
@@ -174,18 +250,26 @@ impl PluginScreen {
                         &self.screen.font
                     };
 
-                    // draw text
-                    draw_text_mut(
+                    // draw text, it scrolls between x and the right border, if it is too long
+                    let marquee = self
+                        .marquees
+                        .entry((device.to_string(), index))
+                        .or_default();
+                    marquee.set_text(&text.value);
+                    marquee.draw(
                         image,
-                        color,
-                        text.x,
-                        text.y,
+                        font,
                         PxScale {
                             x: text.scale_x,
                             y: text.scale_y,
                         },
-                        font,
-                        &text.value,
+                        color,
+                        MarqueeArea {
+                            x: text.x,
+                            y: text.y,
+                            width: image.width() as i32 - text.x,
+                            centered: false,
+                        },
                     );
                 }
                 Item::Image(overlay_image) => {
@@ -211,10 +295,13 @@ impl PluginScreen {
         let lib = Arc::new(Lib::new(library_path.clone()));
         let mut this = PluginScreen {
             lib: lib.clone(),
+            marquees: HashMap::new(),
+            plugin_mode: NORMAL_MODE.into(),
             screen: Screen {
                 description: lib.clone().get_description(),
                 key: lib.clone().get_key(),
                 config_layout: lib.clone().get_config_layout(),
+                modes: lib.clone().get_modes(),
                 font,
                 symbols,
                 config_manager: config_manager.clone(),
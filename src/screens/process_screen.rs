@@ -0,0 +1,294 @@
+use crate::{
+    config_manager::ConfigManager,
+    helpers::{
+        drawing::{draw_right_aligned, draw_text},
+        layout::Layout,
+        marquee::{Marquee, MarqueeArea},
+        paging::page_duration,
+    },
+    screens::{BasicScreen, Screen, Screenable},
+    system::processes::{top_processes, ProcessCollector, ProcessInfo, ProcessOrder},
+    DEVICES,
+};
+use ab_glyph::FontArc;
+use crossbeam_channel::{bounded, Receiver};
+use exchange_format::{ConfigParam, ExchangeableConfig};
+use image::{ImageBuffer, Rgb, RgbImage};
+use imageproc::drawing::draw_line_segment_mut;
+use indexmap::IndexMap;
+use std::{
+    collections::HashMap,
+    sync::{atomic::AtomicBool, atomic::Ordering, Arc, RwLock},
+    thread,
+    time::{Duration, Instant},
+};
+
+const DEFAULT_COUNT: u32 = 5;
+const DEFAULT_PAGE_DURATION: u32 = 5;
+const PAGES: [ProcessOrder; 2] = [ProcessOrder::Cpu, ProcessOrder::Memory];
+
+pub struct ProcessScreen {
+    screen: Screen,
+    receiver: Receiver<Arc<Vec<ProcessInfo>>>,
+    processes: Arc<Vec<ProcessInfo>>,
+    page: usize,
+    page_started: Instant,
+    // names, that are too long for their column, are scrolled
+    name_marquees: HashMap<u32, Marquee>,
+    scrolling: bool,
+}
+
+impl Screenable for ProcessScreen {
+    fn get_screen(&mut self) -> &mut Screen {
+        &mut self.screen
+    }
+}
+
+impl BasicScreen for ProcessScreen {
+    fn update(&mut self) {
+        let mut redraw = self.scrolling;
+        if let Ok(processes) = self.receiver.try_recv() {
+            self.processes = processes;
+            redraw = true;
+        }
+
+        if self.page_started.elapsed()
+            >= page_duration(
+                &self.screen.config_manager,
+                &self.screen.key,
+                DEFAULT_PAGE_DURATION,
+            )
+        {
+            self.page = (self.page + 1) % PAGES.len();
+            self.page_started = Instant::now();
+            self.name_marquees.clear();
+            redraw = true;
+        }
+
+        if redraw {
+            self.draw_screen();
+        }
+    }
+}
+
+impl ProcessScreen {
+    fn count(&mut self) -> usize {
+        self.screen
+            .config_manager
+            .read()
+            .unwrap()
+            .get_integer_value(&self.screen.key, "count")
+            .unwrap_or(DEFAULT_COUNT)
+            .max(1) as usize
+    }
+
+    fn title(order: ProcessOrder) -> &'static str {
+        match order {
+            ProcessOrder::Cpu => "Top CPU",
+            ProcessOrder::Memory => "Top Memory",
+        }
+    }
+
+    // returns true, if the name does not fit into the column and is scrolled
+    fn draw_name(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        process: &ProcessInfo,
+        layout: &Layout,
+        y: i32,
+        size: f32,
+        width: i32,
+    ) -> bool {
+        match self.name_marquees.get(&process.pid) {
+            Some(marquee) => marquee.draw(
+                image,
+                &self.screen.font,
+                layout.scale(size),
+                Rgb([255, 255, 255]),
+                MarqueeArea {
+                    x: layout.x(2),
+                    y,
+                    width,
+                    centered: false,
+                },
+            ),
+            None => false,
+        }
+    }
+
+    // returns true, if a name is scrolled
+    fn draw_page(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+        order: ProcessOrder,
+        count: usize,
+    ) -> bool {
+        let compact = layout.compact();
+        let width = layout.width() as i32;
+        let white = Rgb([255, 255, 255]);
+        let (font_size, row_height, top) = if compact {
+            (12.0, 13, 13)
+        } else {
+            (16.0, 23, 30)
+        };
+        let (row_height, top) = (layout.y(row_height).max(1), layout.y(top));
+        let scale = layout.scale(font_size);
+
+        if compact {
+            draw_text(
+                image,
+                &self.screen.font,
+                Self::title(order),
+                layout.x(2),
+                0,
+                scale,
+                white,
+            );
+        } else {
+            draw_text(
+                image,
+                &self.screen.font,
+                Self::title(order),
+                layout.x(4),
+                layout.y(2),
+                layout.scale(22.0),
+                white,
+            );
+            let line_y = layout.y(27) as f32;
+            draw_line_segment_mut(
+                image,
+                (0.0, line_y),
+                (width as f32, line_y),
+                Rgb([90, 90, 90]),
+            );
+        }
+        draw_right_aligned(
+            image,
+            &self.screen.font,
+            "PID",
+            width * 7 / 10,
+            if compact { 0 } else { layout.y(6) },
+            layout.scale(12.0),
+            white,
+        );
+
+        let name_width = width / 2 - layout.x(4);
+        let rows = ((layout.height() as i32 - top) / row_height).max(1) as usize;
+
+        let mut scrolling = false;
+        for (index, process) in top_processes(&self.processes, order, count.min(rows))
+            .iter()
+            .enumerate()
+        {
+            let y = top + index as i32 * row_height;
+            scrolling |= self.draw_name(image, process, layout, y, font_size, name_width);
+            draw_right_aligned(
+                image,
+                &self.screen.font,
+                &process.pid.to_string(),
+                width * 7 / 10,
+                y,
+                scale,
+                white,
+            );
+            let usage = match order {
+                ProcessOrder::Cpu => process.cpu_usage,
+                ProcessOrder::Memory => process.memory_usage,
+            };
+            draw_right_aligned(
+                image,
+                &self.screen.font,
+                &format!("{:.1}%", usage),
+                width - layout.x(4),
+                y,
+                scale,
+                white,
+            );
+        }
+        scrolling
+    }
+
+    fn draw_screen(&mut self) {
+        let order = PAGES[self.page % PAGES.len()];
+        let count = self.count();
+        // forget about processes, that are gone. a new process with a reused pid
+        // starts scrolling from the beginning.
+        let processes = top_processes(&self.processes, order, count);
+        self.name_marquees
+            .retain(|pid, _| processes.iter().any(|process| process.pid == *pid));
+        for process in processes.iter() {
+            self.name_marquees
+                .entry(process.pid)
+                .or_default()
+                .set_text(&process.name);
+        }
+
+        let mut scrolling = false;
+        for (key, device) in DEVICES.iter() {
+            let layout = Layout::for_display(device.screen_width(), device.screen_height());
+            let mut image = RgbImage::new(layout.width(), layout.height());
+            scrolling |= self.draw_page(&mut image, &layout, order, count);
+            self.screen
+                .device_screen_bytes
+                .insert(key.clone(), image.into_vec());
+        }
+        self.scrolling = scrolling;
+    }
+
+    pub fn new(
+        description: String,
+        key: String,
+        font: FontArc,
+        config_manager: Arc<RwLock<ConfigManager>>,
+    ) -> ProcessScreen {
+        let (tx, rx) = bounded::<Arc<Vec<ProcessInfo>>>(1);
+        let active = Arc::new(AtomicBool::new(false));
+
+        let mut params = IndexMap::new();
+        params.insert("count".into(), ConfigParam::Integer(DEFAULT_COUNT));
+        params.insert(
+            "page_duration".into(),
+            ConfigParam::Integer(DEFAULT_PAGE_DURATION),
+        );
+
+        let screen = Screen {
+            description,
+            key,
+            font,
+            config_manager,
+            config_layout: ExchangeableConfig { params },
+            active: active.clone(),
+            handle: Some(thread::spawn({
+                let active = active.clone();
+                move || {
+                    let mut collector = ProcessCollector::default();
+                    let sender = tx;
+                    loop {
+                        while !active.load(Ordering::Acquire) {
+                            thread::park();
+                        }
+
+                        let processes = Arc::new(collector.sample());
+                        let _ = sender.try_send(processes);
+                        thread::sleep(Duration::from_secs(2));
+                    }
+                }
+            })),
+            ..Default::default()
+        };
+
+        let mut this = ProcessScreen {
+            screen,
+            receiver: rx,
+            processes: Arc::new(Vec::new()),
+            page: 0,
+            page_started: Instant::now(),
+            name_marquees: HashMap::new(),
+            scrolling: false,
+        };
+
+        this.draw_screen(); // initial draw
+        this
+    }
+}
@@ -1,32 +1,88 @@
 use crate::{
     config_manager::ConfigManager,
+    helpers::{
+        drawing::{draw_right_aligned, draw_text},
+        graph::{draw_graph, Graph, GraphStyle},
+        layout::Layout,
+        paging::page_duration,
+        text_manipulation::{format_duration, humanize_bytes, DurationFormat},
+    },
     screens::{BasicScreen, Screen, Screenable},
-    TEENSY,
+    system::{
+        history::{MetricHistory, CPU, NETWORK_RX, RAM, TEMPERATURE},
+        service::{MetricService, SAMPLE_INTERVAL},
+        stats::SystemStats,
+    },
+    DEVICES,
 };
-use ab_glyph::{FontArc, PxScale};
-use cpu_monitor::CpuInstant;
-use crossbeam_channel::{bounded, Receiver, Sender};
+use ab_glyph::FontArc;
+use crossbeam_channel::Receiver;
+use exchange_format::{ConfigParam, ExchangeableConfig};
 use image::{ImageBuffer, Rgb, RgbImage};
 use imageproc::{
-    drawing::{draw_filled_rect_mut, draw_hollow_rect_mut, draw_text_mut},
+    drawing::{draw_filled_rect_mut, draw_hollow_rect_mut, draw_line_segment_mut},
     rect::Rect,
 };
+use indexmap::IndexMap;
 use std::{
-    sync::{atomic::AtomicBool, atomic::Ordering, Arc, RwLock},
-    thread,
-    time::Duration,
+    sync::{Arc, RwLock},
+    time::Instant,
 };
-use systemstat::{saturating_sub_bytes, Platform, System};
+
+const DEFAULT_PAGES: &str = "overview,cores,sensors,disks,network,history";
+const DEFAULT_PAGE_DURATION: u32 = 5;
+const DEFAULT_HISTORY_MINUTES: u32 = 5;
+const DEFAULT_GRAPH_STYLE: &str = "area";
 
 pub struct SystemInfoScreen {
     screen: Screen,
-    receiver: Receiver<Arc<SystemInfoState>>,
+    receiver: Receiver<Arc<SystemStats>>,
+    system_stats: Arc<SystemStats>,
+    history: Arc<RwLock<MetricHistory>>,
+    page: usize,
+    page_started: Instant,
 }
 
-#[derive(Default)]
-struct SystemInfoState {
-    cpu_usage: f64,
-    ram_usage: f64,
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Page {
+    Overview,
+    Cores,
+    Sensors,
+    Disks,
+    Network,
+    History,
+}
+
+impl Page {
+    fn from_key(key: &str) -> Option<Page> {
+        match key.trim().to_lowercase().as_str() {
+            "overview" => Some(Page::Overview),
+            "cores" => Some(Page::Cores),
+            "sensors" => Some(Page::Sensors),
+            "disks" => Some(Page::Disks),
+            "network" => Some(Page::Network),
+            "history" => Some(Page::History),
+            _ => None,
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Page::Overview => "System",
+            Page::Cores => "Cores",
+            Page::Sensors => "Sensors",
+            Page::Disks => "Disks",
+            Page::Network => "Network",
+            Page::History => "History",
+        }
+    }
+}
+
+// a single line of a page: text on the left, text on the right and an optional usage bar
+struct Row {
+    left: String,
+    right: String,
+    usage: Option<f64>,
 }
 
 impl Screenable for SystemInfoScreen {
@@ -37,87 +93,562 @@ impl Screenable for SystemInfoScreen {
 
 impl BasicScreen for SystemInfoScreen {
     fn update(&mut self) {
-        if let Ok(system_info_arc) = self.receiver.try_recv() {
-            let system_info: &SystemInfoState = &system_info_arc;
-            self.draw_screen(system_info.cpu_usage, system_info.ram_usage);
+        let mut redraw = false;
+        if let Ok(system_stats) = self.receiver.try_recv() {
+            self.system_stats = system_stats;
+            redraw = true;
+        }
+
+        let capacity = self.history_capacity();
+        self.history.write().unwrap().set_capacity(capacity);
+
+        let pages = self.pages();
+        if self.page_started.elapsed()
+            >= page_duration(
+                &self.screen.config_manager,
+                &self.screen.key,
+                DEFAULT_PAGE_DURATION,
+            )
+        {
+            self.page = (self.page + 1) % pages.len();
+            self.page_started = Instant::now();
+            redraw = true;
+        }
+
+        if redraw {
+            self.draw_screen();
         }
     }
 }
 
 impl SystemInfoScreen {
-    fn draw_cpu(&self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, cpu_usage: f64) {
-        let scale = PxScale { x: 16.0, y: 16.0 };
-        draw_text_mut(
+    fn pages(&mut self) -> Vec<Page> {
+        let pages = self
+            .screen
+            .config_manager
+            .read()
+            .unwrap()
+            .get_string_value(&self.screen.key, "pages")
+            .unwrap_or_else(|| DEFAULT_PAGES.into());
+        let pages: Vec<Page> = pages.split(',').filter_map(Page::from_key).collect();
+        if pages.is_empty() {
+            return vec![Page::Overview];
+        }
+        pages
+    }
+
+    fn history_capacity(&mut self) -> usize {
+        let minutes = self
+            .screen
+            .config_manager
+            .read()
+            .unwrap()
+            .get_integer_value(&self.screen.key, "history_minutes")
+            .unwrap_or(DEFAULT_HISTORY_MINUTES);
+        (minutes.max(1) as u64 * 60 / SAMPLE_INTERVAL.as_secs()) as usize
+    }
+
+    fn graph_style(&self) -> GraphStyle {
+        let style = self
+            .screen
+            .config_manager
+            .read()
+            .unwrap()
+            .get_string_value(&self.screen.key, "graph_style")
+            .unwrap_or_else(|| DEFAULT_GRAPH_STYLE.into());
+        GraphStyle::from_key(&style)
+    }
+
+    fn usage_color(usage: f64, colored: bool) -> Rgb<u8> {
+        if !colored {
+            return Rgb([255, 255, 255]);
+        }
+        if usage < 60.0 {
+            Rgb([80, 200, 120])
+        } else if usage < 85.0 {
+            Rgb([240, 190, 60])
+        } else {
+            Rgb([230, 70, 60])
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_bar(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+        label_y: i32,
+        bar_y: i32,
+        label: &str,
+        usage: f64,
+        color: Rgb<u8>,
+    ) {
+        let scale = layout.scale(16.0);
+        let white = Rgb([255, 255, 255]);
+        draw_text(
             image,
-            Rgb([255, 255, 255]),
-            0,
+            &self.screen.font,
+            label,
             0,
+            layout.y(label_y),
             scale,
-            &self.screen.font,
-            "CPU",
+            white,
         );
-        draw_text_mut(
+        draw_right_aligned(
             image,
-            Rgb([255, 255, 255]),
-            222,
-            0,
-            scale,
             &self.screen.font,
-            &format!("{:3.0}%", cpu_usage),
+            &format!("{:3.0}%", usage),
+            layout.width() as i32 - layout.x(2),
+            layout.y(label_y),
+            scale,
+            white,
         );
 
+        let bar_y = layout.y(bar_y);
+        let bar_height = layout.h(10);
         draw_hollow_rect_mut(
             image,
-            Rect::at(0, 16).of_size(256, 10),
-            Rgb([255, 255, 255]),
+            Rect::at(0, bar_y).of_size(layout.width(), bar_height),
+            white,
         );
-        let cpu_filled = ((cpu_usage * 2.56) + 1.0).floor() as u32;
+        let filled = ((usage / 100.0 * layout.width() as f64) + 1.0)
+            .floor()
+            .min(layout.width() as f64) as u32;
         draw_filled_rect_mut(
             image,
-            Rect::at(0, 16).of_size(cpu_filled, 10),
-            Rgb([255, 255, 255]),
+            Rect::at(0, bar_y).of_size(filled.max(1), bar_height),
+            color,
         );
     }
 
-    fn draw_memory(&self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, ram_usage: f64) {
-        let scale = PxScale { x: 16.0, y: 16.0 };
-        draw_text_mut(
+    // the regular layout has enough room for a title and a page indicator
+    fn draw_header(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+        page: Page,
+        page_count: usize,
+    ) -> i32 {
+        let width = layout.width() as i32;
+        draw_text(
             image,
-            Rgb([255, 255, 255]),
-            0,
-            30,
-            scale,
             &self.screen.font,
-            "RAM",
+            page.title(),
+            layout.x(4),
+            layout.y(2),
+            layout.scale(22.0),
+            Rgb([255, 255, 255]),
         );
-        draw_text_mut(
+        draw_right_aligned(
             image,
-            Rgb([255, 255, 255]),
-            222,
-            30,
-            scale,
             &self.screen.font,
-            &format!("{:3.0}%", ram_usage),
+            &format!("{}/{}", self.page % page_count + 1, page_count),
+            width - layout.x(4),
+            layout.y(6),
+            layout.scale(16.0),
+            Rgb([160, 160, 160]),
         );
+        let line_y = layout.y(27) as f32;
+        draw_line_segment_mut(
+            image,
+            (0.0, line_y),
+            (width as f32, line_y),
+            Rgb([90, 90, 90]),
+        );
+        layout.y(30)
+    }
 
-        draw_hollow_rect_mut(
+    fn draw_overview(&self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, layout: &Layout) {
+        let stats = &self.system_stats;
+        if layout.compact() {
+            self.draw_bar(
+                image,
+                layout,
+                0,
+                16,
+                "CPU",
+                stats.cpu_usage,
+                Rgb([255, 255, 255]),
+            );
+            self.draw_bar(
+                image,
+                layout,
+                30,
+                48,
+                "RAM",
+                stats.ram_usage,
+                Rgb([255, 255, 255]),
+            );
+            return;
+        }
+
+        let bars = [
+            ("CPU", Some(stats.cpu_usage)),
+            ("RAM", Some(stats.ram_usage)),
+            ("SWAP", stats.swap_usage),
+        ];
+        let mut y = 30;
+        for (label, usage) in bars.iter() {
+            if let Some(usage) = usage {
+                let color = Self::usage_color(*usage, true);
+                self.draw_bar(image, layout, y, y + 18, label, *usage, color);
+                y += 36;
+            }
+        }
+
+        let mut info = Vec::new();
+        if let Some(load) = stats.load_average {
+            info.push(format!("Load {:.2}", load.0));
+        }
+        if let Some(temperature) = stats.max_temperature() {
+            info.push(format!("{:.0}\u{00B0}C", temperature));
+        }
+        if let Some(uptime) = stats.uptime {
+            info.push(format!(
+                "Up {}",
+                format_duration(uptime, DurationFormat::Clock)
+            ));
+        }
+        draw_text(
             image,
-            Rect::at(0, 48).of_size(256, 10),
+            &self.screen.font,
+            &info.join("   "),
+            0,
+            layout.height() as i32 - layout.y(20),
+            layout.scale(16.0),
             Rgb([255, 255, 255]),
         );
-        let memory_filled = ((ram_usage * 2.56) + 1.0).floor() as u32;
-        draw_filled_rect_mut(
+    }
+
+    // the text size of the rows and their height
+    fn row_metrics(layout: &Layout) -> (f32, i32) {
+        if layout.compact() {
+            (12.0, 16)
+        } else {
+            (16.0, 23)
+        }
+    }
+
+    fn draw_cores(&self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, layout: &Layout, top: i32) {
+        let stats = &self.system_stats;
+        let compact = layout.compact();
+        let width = layout.width() as i32;
+        let height = layout.height() as i32;
+        let (font_size, _) = Self::row_metrics(layout);
+        let scale = layout.scale(font_size);
+
+        if compact {
+            draw_text(
+                image,
+                &self.screen.font,
+                "Cores",
+                0,
+                0,
+                scale,
+                Rgb([255, 255, 255]),
+            );
+        }
+        draw_right_aligned(
             image,
-            Rect::at(0, 48).of_size(memory_filled, 10),
+            &self.screen.font,
+            &format!("{:3.0}%", stats.cpu_usage),
+            width - layout.x(2),
+            top,
+            scale,
             Rgb([255, 255, 255]),
         );
+
+        let cores = stats.core_usage.len() as i32;
+        if cores == 0 {
+            return;
+        }
+        let bars_top = top + layout.y(font_size as i32 + 2);
+        let bars_height = height - bars_top - 1;
+        let gap = if cores > 16 { 1 } else { layout.x(2) };
+        let bar_width = ((width - (cores - 1) * gap) / cores).max(1);
+
+        for (index, usage) in stats.core_usage.iter().enumerate() {
+            let x = index as i32 * (bar_width + gap);
+            let filled = ((usage / 100.0) * bars_height as f64).round() as i32;
+            draw_hollow_rect_mut(
+                image,
+                Rect::at(x, bars_top).of_size(bar_width as u32, bars_height as u32),
+                Rgb([90, 90, 90]),
+            );
+            if filled > 0 {
+                draw_filled_rect_mut(
+                    image,
+                    Rect::at(x, bars_top + bars_height - filled)
+                        .of_size(bar_width as u32, filled as u32),
+                    Self::usage_color(*usage, !compact),
+                );
+            }
+        }
+    }
+
+    fn sensor_rows(&self, compact: bool) -> Vec<Row> {
+        let stats = &self.system_stats;
+        let mut rows = Vec::new();
+        if let Some(load) = stats.load_average {
+            rows.push(Row {
+                left: "Load".into(),
+                right: format!("{:.2} {:.2} {:.2}", load.0, load.1, load.2),
+                usage: None,
+            });
+        }
+        if compact {
+            if let Some(temperature) = stats.max_temperature() {
+                rows.push(Row {
+                    left: "Temp".into(),
+                    right: format!("{:.0}\u{00B0}C", temperature),
+                    usage: None,
+                });
+            }
+        } else {
+            for temperature in stats.temperatures.iter() {
+                rows.push(Row {
+                    left: temperature.label.clone(),
+                    right: format!("{:.0}\u{00B0}C", temperature.celsius),
+                    usage: None,
+                });
+            }
+        }
+        if let Some(swap) = stats.swap_usage {
+            rows.push(Row {
+                left: "Swap".into(),
+                right: format!("{:.0}%", swap),
+                usage: None,
+            });
+        }
+        if let Some(uptime) = stats.uptime {
+            rows.push(Row {
+                left: "Uptime".into(),
+                right: format_duration(uptime, DurationFormat::Clock),
+                usage: None,
+            });
+        }
+        if let Some(battery) = &stats.battery {
+            rows.push(Row {
+                left: "Battery".into(),
+                right: format!(
+                    "{:.0}%{}",
+                    battery.capacity,
+                    if battery.on_ac_power { " AC" } else { "" }
+                ),
+                usage: None,
+            });
+        }
+        rows
+    }
+
+    fn disk_rows(&self) -> Vec<Row> {
+        self.system_stats
+            .disks
+            .iter()
+            .map(|disk| Row {
+                left: format!("{} {:.0}%", disk.mount, disk.usage()),
+                right: format!(
+                    "R {}/s W {}/s",
+                    humanize_bytes(disk.read_per_second),
+                    humanize_bytes(disk.write_per_second)
+                ),
+                usage: Some(disk.usage()),
+            })
+            .collect()
     }
 
-    fn draw_screen(&mut self, cpu_usage: f64, ram_usage: f64) {
-        let mut image = RgbImage::new(256, 64);
-        self.draw_cpu(&mut image, cpu_usage);
-        self.draw_memory(&mut image, ram_usage);
-        *self.screen.device_screen_bytes.get_mut(TEENSY).unwrap() = image.into_vec();
+    fn network_rows(&self) -> Vec<Row> {
+        self.system_stats
+            .networks
+            .iter()
+            .map(|network| Row {
+                left: network.interface.clone(),
+                right: format!(
+                    "RX {}/s TX {}/s",
+                    humanize_bytes(network.received_per_second),
+                    humanize_bytes(network.transmitted_per_second)
+                ),
+                usage: None,
+            })
+            .collect()
+    }
+
+    // rows are laid out in columns from top to bottom, whatever does not fit is left out
+    fn draw_rows(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+        rows: Vec<Row>,
+        top: i32,
+        columns: i32,
+    ) {
+        let (font_size, row_height) = Self::row_metrics(layout);
+        let scale = layout.scale(font_size);
+        let text_height = layout.y(font_size as i32);
+        let row_height = layout.y(row_height).max(1);
+        let width = layout.width() as i32;
+        let height = layout.height() as i32;
+        let column_width = width / columns;
+        let rows_per_column = ((height - top) / row_height).max(1);
+
+        for (index, row) in rows.iter().enumerate() {
+            let column = index as i32 / rows_per_column;
+            if column >= columns {
+                break;
+            }
+            let x = column * column_width;
+            let y = top + (index as i32 % rows_per_column) * row_height;
+            draw_text(
+                image,
+                &self.screen.font,
+                &row.left,
+                x + layout.x(2),
+                y,
+                scale,
+                Rgb([255, 255, 255]),
+            );
+            draw_right_aligned(
+                image,
+                &self.screen.font,
+                &row.right,
+                x + column_width - layout.x(4),
+                y,
+                scale,
+                Rgb([255, 255, 255]),
+            );
+            if let Some(usage) = row.usage {
+                let bar_y = y + text_height + 1;
+                let bar_height = (row_height - text_height - 2).max(1) as u32;
+                let bar_width = (column_width - layout.x(6)).max(1) as u32;
+                draw_hollow_rect_mut(
+                    image,
+                    Rect::at(x + layout.x(2), bar_y).of_size(bar_width, bar_height),
+                    Rgb([90, 90, 90]),
+                );
+                let filled = ((usage / 100.0) * bar_width as f64).round() as u32;
+                if filled > 0 {
+                    draw_filled_rect_mut(
+                        image,
+                        Rect::at(x + layout.x(2), bar_y).of_size(filled.min(bar_width), bar_height),
+                        Self::usage_color(usage, !layout.compact()),
+                    );
+                }
+            }
+        }
+    }
+
+    // cpu, ram, network and temperature history in a 2x2 grid
+    fn draw_history(&self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, layout: &Layout, top: i32) {
+        let history = self.history.read().unwrap();
+        let capacity = history.capacity();
+        let compact = layout.compact();
+        let width = layout.width() as i32;
+        let height = layout.height() as i32;
+        let (font_size, _) = Self::row_metrics(layout);
+        let scale = layout.scale(font_size);
+        let cell_width = width / 2;
+        let cell_height = (height - top) / 2;
+        let style = if compact {
+            GraphStyle::Sparkline
+        } else {
+            self.graph_style()
+        };
+
+        let metrics = [
+            ("CPU", CPU, Some((0.0, 100.0)), Rgb([80, 200, 120])),
+            ("RAM", RAM, Some((0.0, 100.0)), Rgb([90, 160, 240])),
+            ("NET", NETWORK_RX, None, Rgb([240, 190, 60])),
+            ("TEMP", TEMPERATURE, None, Rgb([230, 70, 60])),
+        ];
+
+        for (index, (label, metric, range, color)) in metrics.iter().enumerate() {
+            let x = (index as i32 % 2) * cell_width;
+            let y = top + (index as i32 / 2) * cell_height;
+            let values = history
+                .get(metric)
+                .map(|history| history.values())
+                .unwrap_or_default();
+            let current = match (values.last(), *metric) {
+                (None, _) => "-".to_string(),
+                (Some(value), NETWORK_RX) => format!("{}/s", humanize_bytes(*value)),
+                (Some(value), TEMPERATURE) => format!("{:.0}\u{00B0}C", value),
+                (Some(value), _) => format!("{:.0}%", value),
+            };
+
+            draw_text(
+                image,
+                &self.screen.font,
+                label,
+                x + layout.x(2),
+                y,
+                scale,
+                Rgb([255, 255, 255]),
+            );
+            draw_right_aligned(
+                image,
+                &self.screen.font,
+                &current,
+                x + cell_width - layout.x(4),
+                y,
+                scale,
+                Rgb([255, 255, 255]),
+            );
+
+            let graph_top = y + layout.y(font_size as i32) + 1;
+            let graph = Graph {
+                x: x + layout.x(2),
+                y: graph_top,
+                width: (cell_width - layout.x(6)).max(1) as u32,
+                height: (y + cell_height - graph_top - 2).max(1) as u32,
+                style,
+                color: if compact {
+                    Rgb([255, 255, 255])
+                } else {
+                    *color
+                },
+                range: *range,
+                min_max_markers: !compact,
+            };
+            draw_graph(image, &graph, &values, capacity);
+        }
+    }
+
+    fn draw_page(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+        page: Page,
+        page_count: usize,
+    ) {
+        let compact = layout.compact();
+        let top = if compact {
+            0
+        } else {
+            self.draw_header(image, layout, page, page_count)
+        };
+
+        match page {
+            Page::Overview => self.draw_overview(image, layout),
+            Page::Cores => self.draw_cores(image, layout, top),
+            Page::Sensors => self.draw_rows(image, layout, self.sensor_rows(compact), top, 2),
+            Page::Disks => self.draw_rows(image, layout, self.disk_rows(), top, 1),
+            Page::Network => self.draw_rows(image, layout, self.network_rows(), top, 1),
+            Page::History => self.draw_history(image, layout, top),
+        }
+    }
+
+    fn draw_screen(&mut self) {
+        let pages = self.pages();
+        let page = pages[self.page % pages.len()];
+        for (key, device) in DEVICES.iter() {
+            // larger displays get a colored layout with a header
+            let layout = Layout::for_display(device.screen_width(), device.screen_height());
+            let mut image = RgbImage::new(layout.width(), layout.height());
+            self.draw_page(&mut image, &layout, page, pages.len());
+            self.screen
+                .device_screen_bytes
+                .insert(key.clone(), image.into_vec());
+        }
     }
 
     pub fn new(
@@ -125,58 +656,43 @@ impl SystemInfoScreen {
         key: String,
         font: FontArc,
         config_manager: Arc<RwLock<ConfigManager>>,
+        metric_service: &MetricService,
     ) -> SystemInfoScreen {
-        let (tx, rx): (Sender<Arc<SystemInfoState>>, Receiver<Arc<SystemInfoState>>) = bounded(1);
-        let active = Arc::new(AtomicBool::new(false));
+        let mut params = IndexMap::new();
+        params.insert("pages".into(), ConfigParam::String(DEFAULT_PAGES.into()));
+        params.insert(
+            "page_duration".into(),
+            ConfigParam::Integer(DEFAULT_PAGE_DURATION),
+        );
+        params.insert(
+            "history_minutes".into(),
+            ConfigParam::Integer(DEFAULT_HISTORY_MINUTES),
+        );
+        params.insert(
+            "graph_style".into(),
+            ConfigParam::String(DEFAULT_GRAPH_STYLE.into()),
+        );
 
         let screen = Screen {
             description,
             key,
             font,
             config_manager,
-            active: active.clone(),
-            handle: Some(thread::spawn({
-                let active = active.clone();
-                move || {
-                    let sys = System::new();
-                    let sender = tx;
-                    loop {
-                        while !active.load(Ordering::Acquire) {
-                            thread::park();
-                        }
-
-                        let start = CpuInstant::now().unwrap();
-                        thread::sleep(Duration::from_secs(1));
-                        let end = CpuInstant::now().unwrap();
-                        let duration = end - start;
-
-                        let cpu_usage = (duration.non_idle() * 100.0).floor();
-                        let ram_usage = match sys.memory() {
-                            Ok(mem) => {
-                                let used =
-                                    saturating_sub_bytes(mem.total, mem.free).as_u64() as f64;
-                                ((used / mem.total.as_u64() as f64) * 100.0).floor()
-                            }
-                            Err(_) => 0.0,
-                        };
-
-                        let system_info = Arc::new(SystemInfoState {
-                            cpu_usage,
-                            ram_usage,
-                        });
-                        let _ = sender.try_send(system_info);
-                    }
-                }
-            })),
+            config_layout: ExchangeableConfig { params },
             ..Default::default()
         };
 
+        // the metrics are sampled by the service, even while the screen is not shown
         let mut this = SystemInfoScreen {
             screen,
-            receiver: rx,
+            receiver: metric_service.subscribe(),
+            system_stats: Arc::new(SystemStats::default()),
+            history: metric_service.history(),
+            page: 0,
+            page_started: Instant::now(),
         };
 
-        this.draw_screen(0.0, 0.0); // initial draw
+        this.draw_screen(); // initial draw
         this
     }
 }
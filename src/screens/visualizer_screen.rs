@@ -0,0 +1,312 @@
+use crate::{
+    config_manager::{integer_value, string_value, ConfigManager},
+    helpers::{
+        audio_capture::start_capture,
+        spectrum::{spectrum_bands, vu_level, BarSmoother, DEFAULT_BANDS},
+    },
+    screens::{BasicScreen, Screen, Screenable},
+    DEVICES, ESP32,
+};
+use ab_glyph::{FontArc, PxScale};
+use crossbeam_channel::{bounded, Receiver};
+use exchange_format::{ConfigParam, ExchangeableConfig};
+use image::{ImageBuffer, Rgb, RgbImage};
+use imageproc::{
+    drawing::{draw_filled_rect_mut, draw_text_mut, text_size},
+    rect::Rect,
+};
+use indexmap::IndexMap;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    thread,
+    time::Duration,
+};
+
+// the bars fall at the speed of 20 frames per second. a device takes one frame at a time,
+// frames that arrive while it is still busy with the last one are skipped.
+const FRAME_INTERVAL: Duration = Duration::from_millis(50);
+// a missing audio device is looked up again after this
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+const MAX_BANDS: u32 = 128;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+enum VisualizerStyle {
+    #[default]
+    Spectrum,
+    Vu,
+}
+
+impl VisualizerStyle {
+    fn from_key(key: &str) -> VisualizerStyle {
+        match key.trim().to_lowercase().as_str() {
+            "vu" => VisualizerStyle::Vu,
+            _ => VisualizerStyle::Spectrum,
+        }
+    }
+}
+
+#[derive(Default)]
+struct VisualizerFrame {
+    bands: BarSmoother,
+    // left and right
+    levels: BarSmoother,
+    // shown instead of the bars, e.g. if there is no audio device
+    status: Option<String>,
+}
+
+pub struct VisualizerScreen {
+    screen: Screen,
+    receiver: Receiver<Arc<VisualizerFrame>>,
+    frame: Arc<VisualizerFrame>,
+}
+
+impl Screenable for VisualizerScreen {
+    fn get_screen(&mut self) -> &mut Screen {
+        &mut self.screen
+    }
+}
+
+impl BasicScreen for VisualizerScreen {
+    fn update(&mut self) {
+        if let Ok(frame) = self.receiver.try_recv() {
+            self.frame = frame;
+            self.draw_screen();
+        }
+    }
+
+    fn refresh_interval(&self) -> Duration {
+        FRAME_INTERVAL
+    }
+}
+
+// the color of the bar at the given height, from 0 at the bottom to 1 at the top. the teensy
+// shows 16 gray levels, so the levels are used exactly.
+fn bar_color(fraction: f32, color: bool) -> Rgb<u8> {
+    let fraction = fraction.clamp(0.0, 1.0);
+    if !color {
+        let level = 4 + (fraction * 11.0).round() as u8;
+        return Rgb([level * 17, level * 17, level * 17]);
+    }
+    // green, yellow and red at the top
+    if fraction < 0.6 {
+        Rgb([(fraction / 0.6 * 120.0) as u8, 200, 60])
+    } else if fraction < 0.85 {
+        Rgb([120 + ((fraction - 0.6) / 0.25 * 130.0) as u8, 200, 40])
+    } else {
+        Rgb([250, 200 - ((fraction - 0.85) / 0.15 * 150.0) as u8, 40])
+    }
+}
+
+impl VisualizerScreen {
+    fn style(&self) -> VisualizerStyle {
+        VisualizerStyle::from_key(&string_value(
+            &self.screen.config_manager,
+            &self.screen.key,
+            "style",
+            "",
+        ))
+    }
+
+    fn draw_status(&self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, status: &str) {
+        let scale = PxScale { x: 18.0, y: 18.0 };
+        let (width, height) = text_size(scale, &self.screen.font, status);
+        draw_text_mut(
+            image,
+            Rgb([160u8, 160u8, 160u8]),
+            (image.width() as i32 - width as i32) / 2,
+            (image.height() as i32 - height as i32) / 2,
+            scale,
+            &self.screen.font,
+            status,
+        );
+    }
+
+    // vertical bars with their peaks, drawn row by row for the gradient
+    fn draw_spectrum(image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, bands: &BarSmoother, color: bool) {
+        if bands.values.is_empty() {
+            return;
+        }
+        let height = image.height() as f32;
+        let bar_width = image.width() as f32 / bands.values.len() as f32;
+        let gap = if bar_width >= 4.0 { 1 } else { 0 };
+        for (index, (value, peak)) in bands.values.iter().zip(bands.peaks.iter()).enumerate() {
+            let x = (index as f32 * bar_width) as i32;
+            let width = ((bar_width as i32 - gap).max(1)) as u32;
+            let bar_height = (value * height) as u32;
+            for row in 0..bar_height {
+                let y = image.height() - 1 - row;
+                draw_filled_rect_mut(
+                    image,
+                    Rect::at(x, y as i32).of_size(width, 1),
+                    bar_color(row as f32 / height, color),
+                );
+            }
+            if *peak > 0.0 {
+                let y = ((1.0 - peak) * height) as i32;
+                draw_filled_rect_mut(
+                    image,
+                    Rect::at(x, y.min(image.height() as i32 - 2)).of_size(width, 2),
+                    Rgb([255u8, 255u8, 255u8]),
+                );
+            }
+        }
+    }
+
+    // two horizontal meters of segments, like leds
+    fn draw_vu(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        levels: &BarSmoother,
+        color: bool,
+    ) {
+        let label_width = 16;
+        let width = image.width() as i32 - label_width - 4;
+        let meter_height = (image.height() as i32 / 2 - 6).max(4);
+        let segment = (width / 40).max(3);
+        for (channel, label) in ["L", "R"].iter().enumerate() {
+            let y = 4 + channel as i32 * (image.height() as i32 / 2);
+            draw_text_mut(
+                image,
+                Rgb([160u8, 160u8, 160u8]),
+                2,
+                y + (meter_height - 14) / 2,
+                PxScale { x: 14.0, y: 14.0 },
+                &self.screen.font,
+                label,
+            );
+            let value = levels.values.get(channel).copied().unwrap_or_default();
+            let peak = levels.peaks.get(channel).copied().unwrap_or_default();
+            let mut x = 0;
+            while x + segment <= width {
+                let fraction = (x + segment) as f32 / width as f32;
+                let lit = fraction <= value;
+                let is_peak =
+                    peak > 0.0 && (fraction - peak).abs() <= segment as f32 / width as f32 / 2.0;
+                let segment_color = if lit || is_peak {
+                    bar_color(fraction, color)
+                } else if color {
+                    Rgb([35u8, 40u8, 45u8])
+                } else {
+                    Rgb([34u8, 34u8, 34u8])
+                };
+                draw_filled_rect_mut(
+                    image,
+                    Rect::at(label_width + x, y).of_size((segment - 1) as u32, meter_height as u32),
+                    segment_color,
+                );
+                x += segment;
+            }
+        }
+    }
+
+    fn draw_screen(&mut self) {
+        let style = self.style();
+        for (key, device) in DEVICES.iter() {
+            let mut image = RgbImage::new(device.screen_width(), device.screen_height());
+            let color = key == ESP32;
+            match (&self.frame.status, &style) {
+                (Some(status), _) => self.draw_status(&mut image, status),
+                (None, VisualizerStyle::Spectrum) => {
+                    Self::draw_spectrum(&mut image, &self.frame.bands, color)
+                }
+                (None, VisualizerStyle::Vu) => self.draw_vu(&mut image, &self.frame.levels, color),
+            }
+            self.screen
+                .device_screen_bytes
+                .insert(key.clone(), image.into_vec());
+        }
+    }
+
+    pub fn new(
+        description: String,
+        key: String,
+        font: FontArc,
+        config_manager: Arc<RwLock<ConfigManager>>,
+    ) -> VisualizerScreen {
+        let (tx, rx) = bounded::<Arc<VisualizerFrame>>(1);
+        let active = Arc::new(AtomicBool::new(false));
+
+        let mut params = IndexMap::new();
+        // "spectrum" or "vu"
+        params.insert("style".into(), ConfigParam::String("spectrum".into()));
+        params.insert("bands".into(), ConfigParam::Integer(DEFAULT_BANDS));
+        // a part of the device name, empty for the system output
+        params.insert("capture_device".into(), ConfigParam::String(String::new()));
+
+        let screen = Screen {
+            description,
+            key: key.clone(),
+            font,
+            active: active.clone(),
+            config_manager: config_manager.clone(),
+            config_layout: ExchangeableConfig { params },
+            // the audio is only captured while the screen is shown
+            handle: Some(thread::spawn(move || {
+                let sender = tx;
+                loop {
+                    while !active.load(Ordering::Acquire) {
+                        thread::park();
+                    }
+                    let device_name = string_value(&config_manager, &key, "capture_device", "");
+                    let capture = match start_capture(&device_name) {
+                        Ok(capture) => capture,
+                        Err(error) => {
+                            let _ = sender.try_send(Arc::new(VisualizerFrame {
+                                status: Some(error),
+                                ..Default::default()
+                            }));
+                            thread::park_timeout(RETRY_INTERVAL);
+                            continue;
+                        }
+                    };
+
+                    let mut frame = VisualizerFrame::default();
+                    while active.load(Ordering::Acquire)
+                        && string_value(&config_manager, &key, "capture_device", "") == device_name
+                    {
+                        let samples: Vec<[f32; 2]> =
+                            capture.buffer.lock().unwrap().iter().copied().collect();
+                        let mono: Vec<f32> = samples
+                            .iter()
+                            .map(|frame| (frame[0] + frame[1]) / 2.0)
+                            .collect();
+                        let bands = integer_value(&config_manager, &key, "bands", DEFAULT_BANDS)
+                            .clamp(1, MAX_BANDS) as usize;
+                        frame
+                            .bands
+                            .update(&spectrum_bands(&mono, capture.sample_rate, bands));
+
+                        // the level of the last frame interval
+                        let recent = &samples[samples.len().saturating_sub(
+                            (capture.sample_rate as f32 * FRAME_INTERVAL.as_secs_f32()) as usize,
+                        )..];
+                        let left: Vec<f32> = recent.iter().map(|frame| frame[0]).collect();
+                        let right: Vec<f32> = recent.iter().map(|frame| frame[1]).collect();
+                        frame.levels.update(&[vu_level(&left), vu_level(&right)]);
+
+                        let _ = sender.try_send(Arc::new(VisualizerFrame {
+                            bands: frame.bands.clone(),
+                            levels: frame.levels.clone(),
+                            status: None,
+                        }));
+                        thread::sleep(FRAME_INTERVAL);
+                    }
+                    // the capture stops, when it is dropped
+                }
+            })),
+            ..Default::default()
+        };
+
+        let mut this = VisualizerScreen {
+            screen,
+            receiver: rx,
+            frame: Arc::new(VisualizerFrame::default()),
+        };
+
+        this.draw_screen(); // initial draw
+        this
+    }
+}
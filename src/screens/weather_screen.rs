@@ -1,48 +1,108 @@
-use crate::config_manager::ConfigManager;
+use crate::climate::{celsius_to_fahrenheit, ClimateReading};
+use crate::config_manager::{integer_value, string_value, ConfigManager};
+use crate::helpers::drawing::{draw_right_aligned, draw_text};
+use crate::helpers::graph::{draw_graph, Graph, GraphStyle};
+use crate::helpers::layout::Layout;
+use crate::helpers::paging::page_duration;
+use crate::helpers::warning_frame::draw_warning_frame;
+use crate::screen_manager::PRIORITY_SWITCH_DURATION;
 use crate::screens::BasicScreen;
 use crate::screens::Screen;
 use crate::screens::Screenable;
-use crate::weather::weather::get_weather;
-use crate::weather::*;
-use crate::ESP32;
-use crate::LAST_BME_INFO;
-use crate::TEENSY;
+use crate::weather::cache::{WeatherCache, CACHE_PATH};
+use crate::weather::forecast::upcoming_hours;
+use crate::weather::places::configured_locations;
+use crate::weather::provider::{fetch_weather, provider_from_key, WeatherError, WeatherProvider};
+use crate::weather::report::WeatherReport;
+use crate::weather::warnings::{
+    evaluate, warning_kinds, WarningCooldown, WeatherWarning, DEFAULT_WARNINGS,
+    DEFAULT_WARNING_COOLDOWN,
+};
+use crate::weather::weather::{UnitSystem, WeatherSettings, DEFAULT_FORECAST_DAYS};
+use crate::DEVICES;
+use crate::SENSORS;
 use ab_glyph::FontArc;
-use ab_glyph::PxScale;
-use chrono::Datelike;
+use chrono::{DateTime, Local, Utc};
 use crossbeam_channel::bounded;
 use crossbeam_channel::{Receiver, Sender};
+use exchange_format::{ConfigParam, ExchangeableConfig};
 use image::{ImageBuffer, Rgb, RgbImage};
-use imageproc::drawing::draw_text_mut;
+use imageproc::drawing::{draw_filled_rect_mut, draw_line_segment_mut, draw_text_mut, text_size};
+use imageproc::rect::Rect;
+use indexmap::IndexMap;
 
+use std::path::Path;
 use std::sync::{atomic::AtomicBool, atomic::Ordering, Arc, RwLock};
 use std::thread;
 use std::time::Duration;
 use std::time::Instant;
 
+const DEFAULT_PAGE_DURATION: u32 = 10;
+const DEFAULT_LOCATION_DURATION: u32 = 30;
+const DEFAULT_PROVIDER: &str = "open-meteo";
+// while another screen is shown, the weather is only fetched to check for warnings
+const BACKGROUND_REFRESH: Duration = Duration::from_secs(300);
+const HOURS_COMPACT: usize = 12;
+const HOURS_FULL: usize = 24;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Page {
+    Current,
+    Forecast,
+    Hourly,
+    Details,
+    Sun,
+}
+
+impl Page {
+    fn from_key(key: &str) -> Option<Page> {
+        match key.trim().to_lowercase().as_str() {
+            "current" => Some(Page::Current),
+            "forecast" => Some(Page::Forecast),
+            "hourly" => Some(Page::Hourly),
+            "details" => Some(Page::Details),
+            "sun" => Some(Page::Sun),
+            _ => None,
+        }
+    }
+}
+
+// compact displays start with the current weather, the larger ones with the forecast
+fn default_pages(compact: bool) -> &'static str {
+    if compact {
+        "current,hourly,details,sun"
+    } else {
+        "forecast,hourly,details,sun"
+    }
+}
+
 pub struct WeatherScreen {
     screen: Screen,
     symbols: FontArc,
-    receiver: Receiver<Arc<WeatherInfo>>,
+    receiver: Receiver<WeatherInfos>,
+    weather_infos: WeatherInfos,
+    page: usize,
+    page_started: Instant,
+    location: usize,
+    location_started: Instant,
+    priority: Arc<AtomicBool>,
+    warning_receiver: Receiver<WarningEvent>,
+    // shown on top of the pages for the duration of the priority switch
+    warning: Option<(WeatherWarning, Instant)>,
 }
 
-#[derive(Default, Clone)]
-struct WeatherInfo {
-    weather_icon: u8,
-    is_day: u8,
-    city: String,
-    temperature: f64,
-    wind: f64,
-    wind_direction: String,
-    weather_forecast: Vec<WeatherForecast>,
-}
+// one entry for every saved location
+type WeatherInfos = Arc<Vec<WeatherInfo>>;
+// index of the location, the warning and when it was raised
+type WarningEvent = (usize, WeatherWarning, Instant);
 
 #[derive(Default, Clone)]
-struct WeatherForecast {
-    day: String,
-    min: f64,
-    max: f64,
-    weather_icon: u8,
+struct WeatherInfo {
+    // none until the first forecast arrives or when nothing is cached
+    report: Option<WeatherReport>,
+    // set while the last good report is shown because fetching failed
+    stale_since: Option<DateTime<Utc>>,
+    error: Option<String>,
 }
 
 impl Screenable for WeatherScreen {
@@ -53,39 +113,93 @@ impl Screenable for WeatherScreen {
 
 impl BasicScreen for WeatherScreen {
     fn update(&mut self) {
-        if let Ok(weather_info_arc) = self.receiver.try_recv() {
-            let weather_info: &WeatherInfo = &weather_info_arc;
-            self.draw_screen(weather_info);
-            self.draw_companion_screen(weather_info);
+        let mut redraw = false;
+        if let Ok(weather_infos) = self.receiver.try_recv() {
+            self.weather_infos = weather_infos;
+            redraw = true;
+        }
+
+        if self.page_started.elapsed()
+            >= page_duration(
+                &self.screen.config_manager,
+                &self.screen.key,
+                DEFAULT_PAGE_DURATION,
+            )
+        {
+            self.page = self.page.wrapping_add(1);
+            self.page_started = Instant::now();
+            redraw = true;
+        }
+
+        // warnings that waited too long for the switch are outdated
+        if let Ok((location, warning, raised)) = self.warning_receiver.try_recv() {
+            if raised.elapsed() < PRIORITY_SWITCH_DURATION {
+                self.location = location;
+                self.location_started = Instant::now();
+                self.warning = Some((warning, raised));
+                redraw = true;
+            }
+        }
+        if matches!(&self.warning, Some((_, started)) if started.elapsed() >= PRIORITY_SWITCH_DURATION)
+        {
+            self.warning = None;
+            redraw = true;
+        }
+
+        if self.weather_infos.len() > 1
+            && self.location_started.elapsed() >= self.location_duration()
+        {
+            self.location = self.location.wrapping_add(1);
+            self.location_started = Instant::now();
+            redraw = true;
+        }
+
+        if redraw {
+            self.draw_screen();
         }
     }
+
+    fn priority_request(&mut self) -> bool {
+        self.priority.swap(false, Ordering::AcqRel)
+    }
 }
 
 impl WeatherScreen {
-    fn draw_companion_screen(&mut self, weather_info: &WeatherInfo) {
-        // draw initial image
-        let mut image = RgbImage::new(320, 170);
-
-        let mut x: i32 = 24;
+    fn draw_forecast(
+        &mut self,
+        weather_info: &WeatherReport,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+    ) {
+        // the columns share the reference width of 320, the text shrinks with narrower columns
+        let columns = weather_info.weather_forecast.len().max(1) as i32;
+        let column_width = 320 / columns;
+        let size = (column_width as f32 / 103.0).min(1.0);
+        let temperature_label = if columns > 4 {
+            "\u{00B0}"
+        } else {
+            weather_info.unit_system.temperature_label()
+        };
+        let mut x: i32 = column_width / 2 - (column_width as f32 * 0.27) as i32;
         for forecast in &weather_info.weather_forecast {
             // day
             draw_text_mut(
-                &mut image,
+                image,
                 Rgb([255u8, 255u8, 255u8]),
-                x,
-                6,
-                PxScale { x: 38.0, y: 38.0 },
+                layout.x(x),
+                layout.y(6),
+                layout.scale(38.0 * size),
                 &self.screen.font,
                 forecast.day.as_str(),
             );
 
             // icon
             draw_text_mut(
-                &mut image,
+                image,
                 Rgb([255u8, 255u8, 255u8]),
-                x - 8,
-                40,
-                PxScale { x: 32.0, y: 32.0 },
+                layout.x(x - (8.0 * size) as i32),
+                layout.y(40),
+                layout.scale(32.0 * size),
                 &self.symbols,
                 format!(
                     "{: >3}",
@@ -96,50 +210,524 @@ impl WeatherScreen {
 
             // min
             draw_text_mut(
-                &mut image,
+                image,
                 Rgb([255u8, 255u8, 255u8]),
-                x,
-                80,
-                PxScale { x: 22.0, y: 22.0 },
+                layout.x(x),
+                layout.y(80),
+                layout.scale(22.0 * size),
                 &self.screen.font,
-                format!("{: >2} \u{00B0}C", forecast.min.round() as i64).as_str(),
+                format!("{: >2} {}", forecast.min.round() as i64, temperature_label).as_str(),
             );
 
             // max
             draw_text_mut(
-                &mut image,
+                image,
                 Rgb([255u8, 255u8, 255u8]),
-                x,
-                100,
-                PxScale { x: 22.0, y: 22.0 },
+                layout.x(x),
+                layout.y(100),
+                layout.scale(22.0 * size),
+                &self.screen.font,
+                format!("{: >2} {}", forecast.max.round() as i64, temperature_label).as_str(),
+            );
+
+            x += column_width;
+        }
+    }
+
+    // every device rotates through its own list of pages
+    fn pages(&self, device: &str, layout: &Layout) -> Vec<Page> {
+        let default = default_pages(layout.compact());
+        let pages = string_value(
+            &self.screen.config_manager,
+            &self.screen.key,
+            &format!("{}_pages", device),
+            default,
+        );
+        let pages: Vec<Page> = pages.split(',').filter_map(Page::from_key).collect();
+        if pages.is_empty() {
+            return default.split(',').filter_map(Page::from_key).collect();
+        }
+        pages
+    }
+
+    fn location_duration(&self) -> Duration {
+        let seconds = integer_value(
+            &self.screen.config_manager,
+            &self.screen.key,
+            "location_duration",
+            DEFAULT_LOCATION_DURATION,
+        );
+        Duration::from_secs(seconds.max(1) as u64)
+    }
+
+    fn draw_screen(&mut self) {
+        let weather_info = match self.weather_infos.len() {
+            0 => WeatherInfo::default(),
+            count => self.weather_infos[self.location % count].clone(),
+        };
+        for (key, device) in DEVICES.iter() {
+            let layout = Layout::for_display(device.screen_width(), device.screen_height());
+            let pages = self.pages(key, &layout);
+            let page = pages[self.page % pages.len()];
+            let mut image = RgbImage::new(layout.width(), layout.height());
+            match &weather_info.report {
+                Some(report) => {
+                    // the current weather and the forecast have one design, scaled to every display
+                    match page {
+                        Page::Current => self.draw_weather_info(
+                            report,
+                            &mut image,
+                            &Layout::new(layout.width(), layout.height(), 256, 64),
+                        ),
+                        Page::Forecast => self.draw_forecast(
+                            report,
+                            &mut image,
+                            &Layout::new(layout.width(), layout.height(), 320, 170),
+                        ),
+                        Page::Hourly => self.draw_hourly(report, &mut image, &layout),
+                        Page::Details => self.draw_details(report, &mut image, &layout),
+                        Page::Sun => self.draw_sun(report, &mut image, &layout),
+                    }
+                    if let Some(stale_since) = weather_info.stale_since {
+                        self.draw_stale_marker(&mut image, &layout, stale_since);
+                    }
+                }
+                None => self.draw_status(
+                    &mut image,
+                    &layout,
+                    weather_info.error.as_deref().unwrap_or("Loading weather"),
+                ),
+            }
+            let mut bytes = image.into_vec();
+            if let Some((warning, _)) = &self.warning {
+                bytes = draw_warning_frame(
+                    bytes,
+                    layout.width(),
+                    layout.height(),
+                    &warning.message(),
+                    &self.screen.font,
+                );
+            }
+            self.screen.device_screen_bytes.insert(key.clone(), bytes);
+        }
+    }
+
+    // shown instead of the pages when there is nothing to show
+    fn draw_status(&self, image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>, layout: &Layout, text: &str) {
+        let scale = layout.scale(if layout.compact() { 16.0 } else { 22.0 });
+        let (width, height) = text_size(scale, &self.screen.font, text);
+        let x = (layout.width() as i32 - width as i32) / 2;
+        let y = (layout.height() as i32 - height as i32) / 2;
+        draw_text(
+            image,
+            &self.screen.font,
+            text,
+            x.max(0),
+            y,
+            scale,
+            Rgb([255, 255, 255]),
+        );
+    }
+
+    // the last good report is shown while fetching fails, the marker tells how old it is
+    fn draw_stale_marker(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+        stale_since: DateTime<Utc>,
+    ) {
+        let stale_since = stale_since.with_timezone(&Local);
+        let format = if stale_since.date_naive() == Local::now().date_naive() {
+            "%H:%M"
+        } else {
+            "%d.%m. %H:%M"
+        };
+        let text = format!("stale since {}", stale_since.format(format));
+        let scale = layout.scale(if layout.compact() { 11.0 } else { 14.0 });
+        let (width, height) = text_size(scale, &self.screen.font, &text);
+        let x = layout.width() as i32 - width as i32 - layout.x(4);
+        let y = layout.height() as i32 - height as i32 - layout.y(3);
+        draw_filled_rect_mut(
+            image,
+            Rect::at(x - 2, y - 1).of_size(width + 4, height + 3),
+            Rgb([0, 0, 0]),
+        );
+        draw_text(
+            image,
+            &self.screen.font,
+            &text,
+            x,
+            y,
+            scale,
+            Rgb([250, 170, 60]),
+        );
+    }
+
+    // the city tells the saved locations apart
+    fn draw_title(
+        &self,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+        title: &str,
+        city: &str,
+    ) {
+        let width = layout.width() as i32;
+        draw_text(
+            image,
+            &self.screen.font,
+            title,
+            layout.x(4),
+            layout.y(2),
+            layout.scale(22.0),
+            Rgb([255, 255, 255]),
+        );
+        draw_right_aligned(
+            image,
+            &self.screen.font,
+            city,
+            width - layout.x(4),
+            layout.y(7),
+            layout.scale(14.0),
+            Rgb([160, 160, 160]),
+        );
+        let line_y = layout.y(27) as f32;
+        draw_line_segment_mut(
+            image,
+            (0.0, line_y),
+            (width as f32, line_y),
+            Rgb([90, 90, 90]),
+        );
+    }
+
+    // temperature line above the bars of the precipitation probability
+    fn draw_hourly(
+        &self,
+        weather_info: &WeatherReport,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+    ) {
+        let white = Rgb([255u8, 255u8, 255u8]);
+        let gray = Rgb([160u8, 160u8, 160u8]);
+        let compact = layout.compact();
+        let width = layout.width() as i32;
+        let (count, top, font_size) = if compact {
+            (HOURS_COMPACT, 0, 12.0)
+        } else {
+            self.draw_title(image, layout, "Next hours", &weather_info.city);
+            (HOURS_FULL, layout.y(34), 16.0)
+        };
+        let scale = layout.scale(font_size);
+        let text_height = layout.y(font_size as i32);
+
+        let hours = match weather_info.observed {
+            Some(observed) => upcoming_hours(&weather_info.hourly, observed, count),
+            None => Vec::new(),
+        };
+        if hours.is_empty() {
+            draw_text(
+                image,
+                &self.screen.font,
+                "No data",
+                layout.x(2),
+                top,
+                scale,
+                white,
+            );
+            return;
+        }
+
+        let temperatures: Vec<f64> = hours.iter().map(|hour| hour.temperature).collect();
+        let label_width = layout.x(if compact { 26 } else { 36 });
+        let graph = Graph {
+            x: label_width,
+            y: top,
+            width: (width - label_width - layout.x(2)).max(2) as u32,
+            height: (layout.height() as i32 - top - text_height - layout.y(4)).max(2) as u32,
+            style: GraphStyle::Line,
+            color: if compact { white } else { Rgb([250, 170, 60]) },
+            range: None,
+            min_max_markers: !compact,
+        };
+
+        // precipitation probability as bars, the chance of rain matters more than the amount
+        let step = (graph.width - 1) as f32 / (hours.len().max(2) - 1) as f32;
+        let bar_width = ((step as i32) - 1).max(1);
+        for (index, hour) in hours.iter().enumerate() {
+            let height = (hour.precipitation_probability.clamp(0.0, 100.0) / 100.0
+                * graph.height as f64) as u32;
+            if height == 0 {
+                continue;
+            }
+            let x = graph.x + (index as f32 * step) as i32 - bar_width / 2;
+            draw_filled_rect_mut(
+                image,
+                Rect::at(
+                    x.max(graph.x),
+                    graph.y + graph.height as i32 - height as i32,
+                )
+                .of_size(bar_width as u32, height),
+                if compact {
+                    Rgb([70, 70, 70])
+                } else {
+                    Rgb([50, 100, 190])
+                },
+            );
+        }
+        draw_graph(image, &graph, &temperatures, temperatures.len());
+
+        let unit = weather_info.unit_system.temperature_label();
+        let min = temperatures.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = temperatures
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max);
+        draw_text(
+            image,
+            &self.screen.font,
+            &format!("{}{}", max.round() as i64, unit),
+            0,
+            top,
+            scale,
+            white,
+        );
+        draw_text(
+            image,
+            &self.screen.font,
+            &format!("{}{}", min.round() as i64, unit),
+            0,
+            top + graph.height as i32 - text_height,
+            scale,
+            white,
+        );
+
+        // an hour label every three hours
+        let label_y = top + graph.height as i32 + layout.y(2);
+        for (index, hour) in hours.iter().enumerate().step_by(3) {
+            let x = graph.x + (index as f32 * step) as i32;
+            draw_text(
+                image,
                 &self.screen.font,
-                format!("{: >2} \u{00B0}C", forecast.max.round() as i64).as_str(),
+                &hour.time.format("%H").to_string(),
+                (x - layout.x(6)).max(graph.x),
+                label_y,
+                scale,
+                gray,
             );
+        }
+    }
+
+    fn draw_details(
+        &self,
+        weather_info: &WeatherReport,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+    ) {
+        let white = Rgb([255u8, 255u8, 255u8]);
+        let gray = Rgb([160u8, 160u8, 160u8]);
+        let width = layout.width() as i32;
+        let missing = || String::from("-");
+        let unit = weather_info.unit_system.temperature_label();
 
-            x += 103;
+        let rows = [
+            (
+                "Feels like",
+                weather_info
+                    .apparent_temperature
+                    .map(|value| format!("{}{}", value.round() as i64, unit))
+                    .unwrap_or_else(missing),
+            ),
+            (
+                "Humidity",
+                weather_info
+                    .humidity
+                    .map(|value| format!("{:.0}%", value))
+                    .unwrap_or_else(missing),
+            ),
+            (
+                "UV index",
+                weather_info
+                    .uv_index
+                    .map(|value| format!("{:.1}", value))
+                    .unwrap_or_else(missing),
+            ),
+            (
+                "Pressure",
+                weather_info
+                    .pressure
+                    .map(|value| {
+                        let trend = weather_info
+                            .pressure_trend
+                            .map(|trend| trend.arrow())
+                            .unwrap_or_default();
+                        format!(
+                            "{} {}",
+                            weather_info.unit_system.format_pressure(value),
+                            trend
+                        )
+                        .trim_end()
+                        .to_string()
+                    })
+                    .unwrap_or_else(missing),
+            ),
+        ];
+
+        if layout.compact() {
+            // two columns with two rows each
+            let column_width = width / 2;
+            for (index, (label, value)) in rows.iter().enumerate() {
+                let x = (index % 2) as i32 * column_width + layout.x(2);
+                let y = (index / 2) as i32 * 32;
+                draw_text(
+                    image,
+                    &self.screen.font,
+                    label,
+                    x,
+                    layout.y(y),
+                    layout.scale(12.0),
+                    gray,
+                );
+                draw_text(
+                    image,
+                    &self.screen.font,
+                    value,
+                    x,
+                    layout.y(y + 13),
+                    layout.scale(18.0),
+                    white,
+                );
+            }
+            return;
         }
 
-        *self.screen.device_screen_bytes.get_mut(ESP32).unwrap() = image.into_vec();
+        self.draw_title(image, layout, "Details", &weather_info.city);
+        for (index, (label, value)) in rows.iter().enumerate() {
+            let y = 36 + index as i32 * 32;
+            draw_text(
+                image,
+                &self.screen.font,
+                label,
+                layout.x(4),
+                layout.y(y),
+                layout.scale(20.0),
+                gray,
+            );
+            draw_right_aligned(
+                image,
+                &self.screen.font,
+                value,
+                width - layout.x(4),
+                layout.y(y - 2),
+                layout.scale(24.0),
+                white,
+            );
+        }
     }
 
-    fn draw_screen(&mut self, weather_info: &WeatherInfo) {
-        // draw initial image
-        let mut image = RgbImage::new(256, 64);
-        self.draw_weather_info(weather_info, &mut image);
-        *self.screen.device_screen_bytes.get_mut(TEENSY).unwrap() = image.into_vec();
+    fn draw_sun(
+        &self,
+        weather_info: &WeatherReport,
+        image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
+    ) {
+        let white = Rgb([255u8, 255u8, 255u8]);
+        let gray = Rgb([160u8, 160u8, 160u8]);
+        let compact = layout.compact();
+        let width = layout.width() as i32;
+        let (top, row_height, label_size, value_size) = if compact {
+            (0, 16, 14.0, 14.0)
+        } else {
+            self.draw_title(image, layout, "Sun", &weather_info.city);
+            (36, 30, 20.0, 24.0)
+        };
+
+        let sun = match weather_info.sun {
+            Some(sun) => sun,
+            None => {
+                draw_text(
+                    image,
+                    &self.screen.font,
+                    "No data",
+                    layout.x(2),
+                    layout.y(top),
+                    layout.scale(value_size),
+                    white,
+                );
+                return;
+            }
+        };
+        let daylight = sun.sunset - sun.sunrise;
+        let rows = [
+            ("Sunrise", sun.sunrise.format("%H:%M").to_string()),
+            ("Sunset", sun.sunset.format("%H:%M").to_string()),
+            (
+                "Daylight",
+                format!(
+                    "{}h {:0>2}m",
+                    daylight.num_hours(),
+                    daylight.num_minutes() % 60
+                ),
+            ),
+        ];
+        for (index, (label, value)) in rows.iter().enumerate() {
+            let y = layout.y(top + index as i32 * row_height);
+            draw_text(
+                image,
+                &self.screen.font,
+                label,
+                layout.x(4),
+                y,
+                layout.scale(label_size),
+                gray,
+            );
+            draw_right_aligned(
+                image,
+                &self.screen.font,
+                value,
+                width - layout.x(4),
+                y,
+                layout.scale(value_size),
+                white,
+            );
+        }
+
+        // progress of the day between sunrise and sunset
+        if let Some(observed) = weather_info.observed {
+            let total = daylight.num_seconds().max(1) as f32;
+            let passed = (observed - sun.sunrise).num_seconds() as f32;
+            let progress = (passed / total).clamp(0.0, 1.0);
+            let y = layout.y(top + rows.len() as i32 * row_height + if compact { 2 } else { 8 });
+            let x = layout.x(4);
+            let bar_width = (width - 2 * x).max(1) as u32;
+            let bar_height = layout.h(if compact { 4 } else { 8 });
+            draw_filled_rect_mut(
+                image,
+                Rect::at(x, y).of_size(bar_width, bar_height),
+                Rgb([60, 60, 60]),
+            );
+            let filled = (bar_width as f32 * progress) as u32;
+            if filled > 0 {
+                draw_filled_rect_mut(
+                    image,
+                    Rect::at(x, y).of_size(filled, bar_height),
+                    if compact { white } else { Rgb([250, 200, 60]) },
+                );
+            }
+        }
     }
+
     fn draw_weather_info(
         &mut self,
-        weather_info: &WeatherInfo,
+        weather_info: &WeatherReport,
         image: &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+        layout: &Layout,
     ) {
         // icon
         draw_text_mut(
             image,
             Rgb([255u8, 255u8, 255u8]),
-            6,
-            6,
-            PxScale { x: 40.0, y: 40.0 },
+            layout.x(6),
+            layout.y(6),
+            layout.scale(40.0),
             &self.symbols,
             WeatherScreen::get_weather_icon(weather_info.weather_icon, weather_info.is_day),
         );
@@ -148,20 +736,25 @@ impl WeatherScreen {
         draw_text_mut(
             image,
             Rgb([255u8, 255u8, 255u8]),
-            72,
-            6,
-            PxScale { x: 32.0, y: 32.0 },
+            layout.x(72),
+            layout.y(6),
+            layout.scale(32.0),
             &self.screen.font,
-            format!("{}\u{00B0}C", (weather_info.temperature.round() as i64)).as_str(),
+            format!(
+                "{}{}",
+                (weather_info.temperature.round() as i64),
+                weather_info.unit_system.temperature_label()
+            )
+            .as_str(),
         );
 
         // city
         draw_text_mut(
             image,
             Rgb([255u8, 255u8, 255u8]),
-            4,
-            50,
-            PxScale { x: 14.0, y: 14.0 },
+            layout.x(4),
+            layout.y(50),
+            layout.scale(14.0),
             &self.screen.font,
             weather_info.city.as_str(),
         );
@@ -170,9 +763,9 @@ impl WeatherScreen {
         draw_text_mut(
             image,
             Rgb([255u8, 255u8, 255u8]),
-            160,
-            10,
-            PxScale { x: 14.0, y: 14.0 },
+            layout.x(160),
+            layout.y(10),
+            layout.scale(14.0),
             &self.symbols,
             "\u{f72e}",
         );
@@ -180,35 +773,54 @@ impl WeatherScreen {
         draw_text_mut(
             image,
             Rgb([255u8, 255u8, 255u8]),
-            178,
-            10,
-            PxScale { x: 14.0, y: 14.0 },
+            layout.x(178),
+            layout.y(10),
+            layout.scale(14.0),
             &self.screen.font,
-            format!("{} km/h", ((weather_info.wind) * 3.6).round()).as_str(),
+            format!(
+                "{} {}",
+                weather_info.wind.round(),
+                weather_info.unit_system.wind_speed_label()
+            )
+            .as_str(),
         );
 
         // wind direction
         draw_text_mut(
             image,
             Rgb([255u8, 255u8, 255u8]),
-            178,
-            24,
-            PxScale { x: 14.0, y: 14.0 },
+            layout.x(178),
+            layout.y(24),
+            layout.scale(14.0),
             &self.screen.font,
             weather_info.wind_direction.as_str(),
         );
 
         // indoor temperature / indoor humidity
-        let (temperature, humidity) = LAST_BME_INFO.lock().unwrap().clone();
-        draw_text_mut(
-            image,
-            Rgb([255u8, 255u8, 255u8]),
-            72,
-            38,
-            PxScale { x: 14.0, y: 14.0 },
-            &self.screen.font,
-            format!("{}°C / {}%", temperature, humidity).as_str(),
-        );
+        // like on the climate screen, an old reading is not shown
+        if let Some(reading) = ClimateReading::from_sensors(&SENSORS.read().unwrap())
+            .filter(|reading| reading.is_recent(Utc::now()))
+        {
+            let temperature = match weather_info.unit_system {
+                UnitSystem::Metric => reading.temperature,
+                UnitSystem::Imperial => celsius_to_fahrenheit(reading.temperature),
+            };
+            draw_text_mut(
+                image,
+                Rgb([255u8, 255u8, 255u8]),
+                layout.x(72),
+                layout.y(38),
+                layout.scale(14.0),
+                &self.screen.font,
+                format!(
+                    "{:.1}{} / {:.0}%",
+                    temperature,
+                    weather_info.unit_system.temperature_label(),
+                    reading.humidity
+                )
+                .as_str(),
+            );
+        }
     }
 
     fn get_weather_icon(code: u8, is_day: u8) -> &'static str {
@@ -239,147 +851,230 @@ impl WeatherScreen {
         symbols: FontArc,
         config_manager: Arc<RwLock<ConfigManager>>,
     ) -> WeatherScreen {
-        let (tx, rx): (Sender<Arc<WeatherInfo>>, Receiver<Arc<WeatherInfo>>) = bounded(1);
+        let (tx, rx): (Sender<WeatherInfos>, Receiver<WeatherInfos>) = bounded(1);
+        let (warning_tx, warning_rx): (Sender<WarningEvent>, Receiver<WarningEvent>) = bounded(4);
         let active = Arc::new(AtomicBool::new(false));
+        let priority = Arc::new(AtomicBool::new(false));
+
+        let mut params = IndexMap::new();
+        params.insert(
+            "provider".into(),
+            ConfigParam::String(DEFAULT_PROVIDER.into()),
+        );
+        // "Label=City" or "Label=latitude,longitude", separated by ';'
+        params.insert("locations".into(), ConfigParam::String(String::new()));
+        params.insert(
+            "location_duration".into(),
+            ConfigParam::Integer(DEFAULT_LOCATION_DURATION),
+        );
+        // "rain" and "thunderstorm", separated by ','
+        params.insert(
+            "warnings".into(),
+            ConfigParam::String(DEFAULT_WARNINGS.into()),
+        );
+        params.insert(
+            "warning_cooldown".into(),
+            ConfigParam::Integer(DEFAULT_WARNING_COOLDOWN),
+        );
+        params.insert("unit_system".into(), ConfigParam::String("metric".into()));
+        params.insert(
+            "forecast_days".into(),
+            ConfigParam::Integer(DEFAULT_FORECAST_DAYS),
+        );
+        for (key, device) in DEVICES.iter() {
+            let layout = Layout::for_display(device.screen_width(), device.screen_height());
+            params.insert(
+                format!("{}_pages", key),
+                ConfigParam::String(default_pages(layout.compact()).into()),
+            );
+        }
+        params.insert(
+            "page_duration".into(),
+            ConfigParam::Integer(DEFAULT_PAGE_DURATION),
+        );
+
         let mut this = WeatherScreen {
             screen: Screen {
                 description,
                 key: key.clone(),
                 font,
                 config_manager: config_manager.clone(),
+                config_layout: ExchangeableConfig { params },
                 active: active.clone(),
-                handle: Some(thread::spawn(move || {
-                    let runtime = tokio::runtime::Builder::new_current_thread()
-                        .enable_all()
-                        .build()
-                        .expect("Failed to create Tokio runtime");
-
-                    let sender = tx;
-                    let client = open_meteo_rs::Client::new();
-
-                    let deg_to_dir = |deg: f64| {
-                        const DIRS: [&str; 16] = [
-                            "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW",
-                            "WSW", "W", "WNW", "NW", "NNW",
-                        ];
-                        let val = ((deg / 22.5) + 0.5).floor() as usize;
-                        DIRS[val % 16]
-                    };
-
-                    let mut last_weather_info = Arc::new(WeatherInfo::default());
-                    let mut last_update = Instant::now() - Duration::from_secs(61);
-
-                    loop {
-                        while !active.load(Ordering::Acquire) {
-                            thread::park();
-                        }
+                handle: Some(thread::spawn({
+                    let priority = priority.clone();
+                    move || {
+                        let sender = tx;
+                        let mut cache = WeatherCache::load(Path::new(CACHE_PATH));
+                        let mut provider_key = String::from(DEFAULT_PROVIDER);
+                        let mut provider: Box<dyn WeatherProvider> =
+                            provider_from_key(&provider_key);
 
-                        if last_update.elapsed().as_secs() > 60 {
-                            last_update = Instant::now();
+                        let mut last_weather_infos = Arc::new(Vec::new());
+                        let mut last_update = Instant::now() - Duration::from_secs(61);
+                        let mut last_settings = WeatherSettings::default();
+                        let mut last_locations = Vec::new();
+                        let mut cooldown = WarningCooldown::default();
 
-                            let location = {
+                        loop {
+                            // warnings are checked in the background, as long as the screen is enabled
+                            let watching = || {
                                 let guard = config_manager.read().unwrap();
-                                guard
-                                    .get_value(&key, "weather_location")
-                                    .and_then(|v| match v {
-                                        exchange_format::ConfigParam::String(s) => Some(s.clone()),
-                                        _ => None,
-                                    })
-                                    .unwrap_or_default()
+                                !warning_kinds(
+                                    &guard
+                                        .get_string_value(&key, "warnings")
+                                        .unwrap_or_else(|| DEFAULT_WARNINGS.into()),
+                                )
+                                .is_empty()
+                                    && guard.is_screen_enabled(&key)
+                            };
+                            while !active.load(Ordering::Acquire) && !watching() {
+                                thread::park_timeout(Duration::from_secs(60));
+                            }
+                            let refresh = if active.load(Ordering::Acquire) {
+                                Duration::from_secs(60)
+                            } else {
+                                BACKGROUND_REFRESH
+                            };
+
+                            let (settings, locations, key_of_provider, kinds, cooldown_minutes) = {
+                                let guard = config_manager.read().unwrap();
+                                (
+                                    WeatherSettings::new(
+                                        &guard
+                                            .get_string_value(&key, "unit_system")
+                                            .unwrap_or_default(),
+                                        guard
+                                            .get_integer_value(&key, "forecast_days")
+                                            .unwrap_or(DEFAULT_FORECAST_DAYS),
+                                    ),
+                                    configured_locations(
+                                        &guard
+                                            .get_string_value(&key, "locations")
+                                            .unwrap_or_default(),
+                                        &guard
+                                            .get_string_value(&key, "weather_location")
+                                            .unwrap_or_default(),
+                                    ),
+                                    guard
+                                        .get_string_value(&key, "provider")
+                                        .unwrap_or_else(|| DEFAULT_PROVIDER.into()),
+                                    warning_kinds(
+                                        &guard
+                                            .get_string_value(&key, "warnings")
+                                            .unwrap_or_else(|| DEFAULT_WARNINGS.into()),
+                                    ),
+                                    guard
+                                        .get_integer_value(&key, "warning_cooldown")
+                                        .unwrap_or(DEFAULT_WARNING_COOLDOWN),
+                                )
                             };
 
-                            if let Ok(locations) = location::get_location(location) {
-                                if let Some(closest_location) = locations.results.first() {
-                                    let mut opts = open_meteo_rs::forecast::Options::default();
-                                    weather::set_opts(&mut opts, &locations);
-
-                                    let res = runtime.block_on(get_weather(&client, opts));
-
-                                    if let Some(current) = res.current {
-                                        let mut weather_info = WeatherInfo::default();
-
-                                        weather_info.is_day = current
-                                            .values
-                                            .get("is_day")
-                                            .and_then(|v| v.value.as_u64())
-                                            .unwrap_or_default()
-                                            as u8;
-
-                                        weather_info.weather_icon = current
-                                            .values
-                                            .get("weather_code")
-                                            .and_then(|v| v.value.as_u64())
-                                            .unwrap_or_default()
-                                            as u8;
-
-                                        weather_info.temperature = current
-                                            .values
-                                            .get("temperature_2m")
-                                            .and_then(|v| v.value.as_f64())
-                                            .unwrap_or_default();
-
-                                        weather_info.wind = current
-                                            .values
-                                            .get("wind_speed_10m")
-                                            .and_then(|v| v.value.as_f64())
-                                            .unwrap_or_default();
-
-                                        weather_info.wind_direction = current
-                                            .values
-                                            .get("wind_direction_10m")
-                                            .and_then(|v| v.value.as_f64())
-                                            .map(deg_to_dir)
-                                            .unwrap_or("N")
-                                            .to_string();
-
-                                        weather_info.city = format!(
-                                            "{},{}",
-                                            closest_location.name, closest_location.country_code
-                                        );
-
-                                        if let Some(daily) = res.daily {
-                                            for weather in daily.iter() {
-                                                weather_info.weather_forecast.push(
-                                                    WeatherForecast {
-                                                        day: weather.date.weekday().to_string(),
-                                                        min: weather
-                                                            .values
-                                                            .get("temperature_2m_min")
-                                                            .and_then(|v| v.value.as_f64())
-                                                            .unwrap_or_default(),
-                                                        max: weather
-                                                            .values
-                                                            .get("temperature_2m_max")
-                                                            .and_then(|v| v.value.as_f64())
-                                                            .unwrap_or_default(),
-                                                        weather_icon: weather
-                                                            .values
-                                                            .get("weathercode")
-                                                            .and_then(|v| v.value.as_u64())
-                                                            .unwrap_or_default()
-                                                            as u8,
-                                                    },
-                                                );
+                            if key_of_provider != provider_key {
+                                provider = provider_from_key(&key_of_provider);
+                                provider_key = key_of_provider;
+                                last_update = Instant::now() - Duration::from_secs(61);
+                            }
+
+                            // changed settings are fetched right away
+                            if last_update.elapsed() > refresh
+                                || settings != last_settings
+                                || locations != last_locations
+                            {
+                                last_update = Instant::now();
+                                last_settings = settings;
+
+                                let mut weather_infos: Vec<WeatherInfo> = locations
+                                    .iter()
+                                    .map(|place| {
+                                        match fetch_weather(
+                                            provider.as_ref(),
+                                            &mut cache,
+                                            place,
+                                            &settings,
+                                            Utc::now(),
+                                        ) {
+                                            Ok(report) => WeatherInfo {
+                                                report: Some(report),
+                                                ..Default::default()
+                                            },
+                                            Err(e) => {
+                                                eprintln!("Error: {:?}", e);
+                                                let cached = cache.report(&place.query.cache_key());
+                                                WeatherInfo {
+                                                    stale_since: cached
+                                                        .as_ref()
+                                                        .map(|c| c.fetched_at),
+                                                    report: cached.map(|c| c.report),
+                                                    error: Some(e.to_string()),
+                                                }
                                             }
                                         }
+                                    })
+                                    .collect();
+                                if weather_infos.is_empty() {
+                                    weather_infos.push(WeatherInfo {
+                                        error: Some(WeatherError::NoLocation.to_string()),
+                                        ..Default::default()
+                                    });
+                                }
 
-                                        last_weather_info = Arc::new(weather_info);
+                                // stale reports would warn about the past
+                                for (index, (place, weather_info)) in
+                                    locations.iter().zip(weather_infos.iter()).enumerate()
+                                {
+                                    let report =
+                                        match (&weather_info.report, weather_info.stale_since) {
+                                            (Some(report), None) => report,
+                                            _ => continue,
+                                        };
+                                    let location = place.query.cache_key();
+                                    for warning in evaluate(report, &kinds) {
+                                        let kind = warning.kind;
+                                        if !cooldown.fire(
+                                            &location,
+                                            kind,
+                                            Utc::now(),
+                                            chrono::Duration::minutes(cooldown_minutes as i64),
+                                        ) {
+                                            continue;
+                                        }
+                                        // a full queue must not use up the cooldown
+                                        if warning_tx
+                                            .try_send((index, warning, Instant::now()))
+                                            .is_ok()
+                                        {
+                                            priority.store(true, Ordering::Release);
+                                        } else {
+                                            cooldown.reset(&location, kind);
+                                        }
                                     }
                                 }
+
+                                last_locations = locations;
+                                last_weather_infos = Arc::new(weather_infos);
                             }
-                        }
 
-                        let _ = sender.try_send(last_weather_info.clone());
-                        thread::park_timeout(Duration::from_secs(1));
+                            let _ = sender.try_send(last_weather_infos.clone());
+                            thread::park_timeout(Duration::from_secs(1));
+                        }
                     }
                 })),
                 ..Default::default()
             },
             symbols: FontArc::clone(&symbols),
             receiver: rx,
+            weather_infos: Arc::new(Vec::new()),
+            page: 0,
+            page_started: Instant::now(),
+            location: 0,
+            location_started: Instant::now(),
+            priority,
+            warning_receiver: warning_rx,
+            warning: None,
         };
 
-        this.draw_screen(&Default::default());
+        this.draw_screen();
         this
     }
 }
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use super::{SensorError, SensorKind, SensorSource};
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+// a host sensor, that exposes its value as a number in a file, e.g. an ambient light sensor:
+// {"name": "desk", "kind": "light", "path": "/sys/bus/iio/devices/iio:device0/in_illuminance_raw"}
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FileSensor {
+    pub name: String,
+    pub kind: SensorKind,
+    pub path: String,
+    // the value of the file is multiplied with it, e.g. 0.001 for millidegrees
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+}
+
+impl SensorSource for FileSensor {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn kinds(&self) -> Vec<SensorKind> {
+        vec![self.kind]
+    }
+
+    fn poll(&mut self) -> Result<Vec<(SensorKind, f64)>, SensorError> {
+        let content = fs::read_to_string(&self.path)
+            .map_err(|_| SensorError::Unavailable(self.path.clone()))?;
+        let value = content
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| SensorError::Invalid(content.trim().into()))?;
+        Ok(vec![(self.kind, value * self.scale)])
+    }
+}
+
+pub fn host_sources(sensors: &[FileSensor]) -> Vec<Box<dyn SensorSource>> {
+    sensors
+        .iter()
+        .cloned()
+        .map(|sensor| Box::new(sensor) as Box<dyn SensorSource>)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_dir::TestDir;
+
+    #[test]
+    fn test_poll_file_sensor() {
+        let dir = TestDir::new("file_sensor");
+        let path = dir.join("in_temp_input");
+        fs::write(&path, "23500\n").unwrap();
+
+        let mut sensor: FileSensor = serde_json::from_str(&format!(
+            r#"{{"name": "desk", "kind": "temperature", "path": {:?}, "scale": 0.001}}"#,
+            path.to_string_lossy()
+        ))
+        .unwrap();
+        assert_eq!(sensor.poll(), Ok(vec![(SensorKind::Temperature, 23.5)]));
+
+        fs::write(&path, "garbage").unwrap();
+        assert!(matches!(sensor.poll(), Err(SensorError::Invalid(_))));
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(sensor.poll(), Err(SensorError::Unavailable(_))));
+    }
+}
@@ -0,0 +1,226 @@
+use chrono::{DateTime, Utc};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Display, Error as FmtError, Formatter},
+    thread,
+    time::Duration,
+};
+
+use crate::{CLOSE_REQUESTED, SENSORS};
+
+pub mod file;
+
+// host sources are polled as often as the sensor of the teensy
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SensorKind {
+    Temperature,
+    Humidity,
+    Pressure,
+    Light,
+    Presence,
+    Co2,
+}
+
+impl SensorKind {
+    pub fn key(&self) -> &'static str {
+        match self {
+            SensorKind::Temperature => "temperature",
+            SensorKind::Humidity => "humidity",
+            SensorKind::Pressure => "pressure",
+            SensorKind::Light => "light",
+            SensorKind::Presence => "presence",
+            SensorKind::Co2 => "co2",
+        }
+    }
+
+    // presence is 1 or 0
+    pub fn unit(&self) -> &'static str {
+        match self {
+            SensorKind::Temperature => "\u{00B0}C",
+            SensorKind::Humidity => "%",
+            SensorKind::Pressure => "hPa",
+            SensorKind::Light => "lx",
+            SensorKind::Presence => "",
+            SensorKind::Co2 => "ppm",
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct SensorValue {
+    pub source: String,
+    pub kind: SensorKind,
+    pub value: f64,
+    pub unit: &'static str,
+    pub time: DateTime<Utc>,
+}
+
+// e.g. "teensy.temperature"
+pub fn sensor_name(source: &str, kind: SensorKind) -> String {
+    format!("{}.{}", source, kind.key())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SensorError {
+    NotAdvertised(String),
+    Unavailable(String),
+    Invalid(String),
+}
+
+impl Display for SensorError {
+    fn fmt(&self, f: &mut Formatter) -> core::result::Result<(), FmtError> {
+        match self {
+            Self::NotAdvertised(name) => write!(f, "sensor {} was not advertised", name),
+            Self::Unavailable(name) => write!(f, "sensor {} is unavailable", name),
+            Self::Invalid(value) => write!(f, "invalid sensor value {:?}", value),
+        }
+    }
+}
+
+// a source of sensor values on the host, devices publish their values themselves
+pub trait SensorSource: Send {
+    fn name(&self) -> String;
+    fn kinds(&self) -> Vec<SensorKind>;
+    fn poll(&mut self) -> Result<Vec<(SensorKind, f64)>, SensorError>;
+}
+
+// the latest value of every sensor, that devices and host sources advertise
+#[derive(Default)]
+pub struct SensorRegistry {
+    advertised: IndexMap<String, Vec<SensorKind>>,
+    values: IndexMap<String, SensorValue>,
+}
+
+impl SensorRegistry {
+    pub fn advertise(&mut self, source: &str, kinds: &[SensorKind]) {
+        let advertised = self.advertised.entry(source.to_string()).or_default();
+        for kind in kinds {
+            if !advertised.contains(kind) {
+                advertised.push(*kind);
+            }
+        }
+    }
+
+    pub fn publish(
+        &mut self,
+        source: &str,
+        kind: SensorKind,
+        value: f64,
+        time: DateTime<Utc>,
+    ) -> Result<(), SensorError> {
+        let name = sensor_name(source, kind);
+        if !matches!(self.advertised.get(source), Some(kinds) if kinds.contains(&kind)) {
+            return Err(SensorError::NotAdvertised(name));
+        }
+        if !value.is_finite() {
+            return Err(SensorError::Invalid(value.to_string()));
+        }
+        self.values.insert(
+            name,
+            SensorValue {
+                source: source.to_string(),
+                kind,
+                value,
+                unit: kind.unit(),
+                time,
+            },
+        );
+        Ok(())
+    }
+
+    // "teensy.temperature"
+    pub fn value(&self, name: &str) -> Option<&SensorValue> {
+        self.values.get(name)
+    }
+
+    // the newest value of the kind, no matter which source sent it
+    pub fn latest(&self, kind: SensorKind) -> Option<&SensorValue> {
+        self.values
+            .values()
+            .filter(|value| value.kind == kind)
+            .max_by_key(|value| value.time)
+    }
+
+    pub fn values(&self) -> Vec<SensorValue> {
+        self.values.values().cloned().collect()
+    }
+}
+
+// polls the host sources in the background, a failing source does not stop the others
+pub fn start_host_sources(mut sources: Vec<Box<dyn SensorSource>>) {
+    if sources.is_empty() {
+        return;
+    }
+    {
+        let mut registry = SENSORS.write().unwrap();
+        for source in sources.iter() {
+            registry.advertise(&source.name(), &source.kinds());
+        }
+    }
+    thread::spawn(move || loop {
+        for source in sources.iter_mut() {
+            match source.poll() {
+                Ok(values) => {
+                    let now = Utc::now();
+                    let mut registry = SENSORS.write().unwrap();
+                    for (kind, value) in values {
+                        if let Err(e) = registry.publish(&source.name(), kind, value, now) {
+                            eprintln!("Error: {}", e);
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Error: {}", e),
+            }
+        }
+        if CLOSE_REQUESTED.load(std::sync::atomic::Ordering::Acquire) {
+            return;
+        }
+        thread::sleep(POLL_INTERVAL);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_advertised_sensors() {
+        let mut registry = SensorRegistry::default();
+        let now = Utc::now();
+        assert_eq!(
+            registry.publish("teensy", SensorKind::Temperature, 21.5, now),
+            Err(SensorError::NotAdvertised("teensy.temperature".into()))
+        );
+
+        registry.advertise("teensy", &[SensorKind::Temperature, SensorKind::Humidity]);
+        registry.advertise("desk", &[SensorKind::Temperature, SensorKind::Light]);
+        registry
+            .publish("teensy", SensorKind::Temperature, 21.5, now)
+            .unwrap();
+        registry
+            .publish(
+                "desk",
+                SensorKind::Temperature,
+                23.0,
+                now + chrono::Duration::seconds(1),
+            )
+            .unwrap();
+        assert!(registry
+            .publish("desk", SensorKind::Light, f64::NAN, now)
+            .is_err());
+
+        let value = registry.value("teensy.temperature").unwrap();
+        assert_eq!(value.value, 21.5);
+        assert_eq!(value.unit, "\u{00B0}C");
+        assert_eq!(
+            registry.latest(SensorKind::Temperature).unwrap().source,
+            "desk"
+        );
+        assert_eq!(registry.latest(SensorKind::Light), None);
+        assert_eq!(registry.values().len(), 2);
+    }
+}
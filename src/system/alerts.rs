@@ -0,0 +1,354 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use super::stats::SystemStats;
+use crate::helpers::text_manipulation::humanize_bytes;
+
+const GIGABYTE: f64 = 1024.0 * 1024.0 * 1024.0;
+const KILOBYTE: f64 = 1024.0;
+
+// the unit of the threshold depends on the metric:
+// percent for usages, degree celsius for temperatures, GB for free disk space and KB/s for network traffic
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertMetric {
+    Cpu,
+    Ram,
+    Swap,
+    Temperature,
+    DiskUsage,
+    DiskFree,
+    NetworkRx,
+    NetworkTx,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    Above,
+    Below,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertAction {
+    // switch the display to the system screen
+    #[default]
+    SwitchScreen,
+    // flash a warning frame on top of the current screen
+    Flash,
+}
+
+// e.g. {"metric": "cpu", "comparison": "above", "threshold": 90, "duration": 30}
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AlertRule {
+    pub metric: AlertMetric,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    // seconds, the condition has to hold, before the alert is raised
+    #[serde(default)]
+    pub duration: u64,
+    #[serde(default)]
+    pub action: AlertAction,
+    // restricts disk metrics to a single mount point, otherwise the worst disk counts
+    #[serde(default)]
+    pub mount: Option<String>,
+}
+
+impl AlertRule {
+    pub fn value(&self, stats: &SystemStats) -> Option<f64> {
+        let disks = stats.disks.iter().filter(|disk| match &self.mount {
+            Some(mount) => disk.mount == *mount,
+            None => true,
+        });
+        match self.metric {
+            AlertMetric::Cpu => Some(stats.cpu_usage),
+            AlertMetric::Ram => Some(stats.ram_usage),
+            AlertMetric::Swap => stats.swap_usage,
+            AlertMetric::Temperature => stats.max_temperature().map(|value| value as f64),
+            AlertMetric::DiskUsage => disks.map(|disk| disk.usage()).reduce(f64::max),
+            AlertMetric::DiskFree => disks
+                .map(|disk| disk.total.saturating_sub(disk.used) as f64 / GIGABYTE)
+                .reduce(f64::min),
+            AlertMetric::NetworkRx => Some(
+                stats
+                    .networks
+                    .iter()
+                    .map(|network| network.received_per_second)
+                    .sum::<f64>()
+                    / KILOBYTE,
+            ),
+            AlertMetric::NetworkTx => Some(
+                stats
+                    .networks
+                    .iter()
+                    .map(|network| network.transmitted_per_second)
+                    .sum::<f64>()
+                    / KILOBYTE,
+            ),
+        }
+    }
+
+    pub fn exceeded(&self, value: f64) -> bool {
+        match self.comparison {
+            Comparison::Above => value > self.threshold,
+            Comparison::Below => value < self.threshold,
+        }
+    }
+
+    fn label(&self) -> String {
+        let label = match self.metric {
+            AlertMetric::Cpu => "CPU",
+            AlertMetric::Ram => "RAM",
+            AlertMetric::Swap => "Swap",
+            AlertMetric::Temperature => "Temp",
+            AlertMetric::DiskUsage => "Disk",
+            AlertMetric::DiskFree => "Disk free",
+            AlertMetric::NetworkRx => "RX",
+            AlertMetric::NetworkTx => "TX",
+        };
+        match &self.mount {
+            Some(mount) => format!("{} {}", label, mount),
+            None => label.into(),
+        }
+    }
+
+    pub fn format_value(&self, value: f64) -> String {
+        match self.metric {
+            AlertMetric::Temperature => format!("{:.0}\u{00B0}C", value),
+            AlertMetric::DiskFree => humanize_bytes(value * GIGABYTE),
+            AlertMetric::NetworkRx | AlertMetric::NetworkTx => {
+                format!("{}/s", humanize_bytes(value * KILOBYTE))
+            }
+            _ => format!("{:.0}%", value),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Alert {
+    pub rule: AlertRule,
+    pub value: f64,
+}
+
+impl Alert {
+    // e.g. "CPU 97% > 90%"
+    pub fn message(&self) -> String {
+        format!(
+            "{} {} {} {}",
+            self.rule.label(),
+            self.rule.format_value(self.value),
+            match self.rule.comparison {
+                Comparison::Above => ">",
+                Comparison::Below => "<",
+            },
+            self.rule.format_value(self.rule.threshold)
+        )
+    }
+}
+
+#[derive(Default, Clone, Debug)]
+struct RuleState {
+    exceeded_since: Option<Instant>,
+    alert: Option<Alert>,
+    acknowledged: bool,
+}
+
+// raised alerts stay until they are acknowledged, even if the condition is gone in the meantime.
+// an acknowledged rule is raised again only after its condition was gone once.
+#[derive(Default, Debug)]
+pub struct AlertMonitor {
+    rules: Vec<AlertRule>,
+    states: Vec<RuleState>,
+}
+
+impl AlertMonitor {
+    pub fn new() -> AlertMonitor {
+        AlertMonitor::default()
+    }
+
+    pub fn evaluate(&mut self, rules: &[AlertRule], stats: &SystemStats, now: Instant) {
+        if rules != self.rules.as_slice() {
+            self.rules = rules.to_vec();
+            self.states = vec![RuleState::default(); rules.len()];
+        }
+
+        for (rule, state) in self.rules.iter().zip(self.states.iter_mut()) {
+            let value = match rule.value(stats) {
+                Some(value) if rule.exceeded(value) => value,
+                _ => {
+                    state.exceeded_since = None;
+                    state.acknowledged = false;
+                    continue;
+                }
+            };
+
+            let since = *state.exceeded_since.get_or_insert(now);
+            if state.acknowledged {
+                continue;
+            }
+            if now.duration_since(since) >= Duration::from_secs(rule.duration) {
+                state.alert = Some(Alert {
+                    rule: rule.clone(),
+                    value,
+                });
+            }
+        }
+    }
+
+    pub fn active_alerts(&self) -> Vec<Alert> {
+        self.states
+            .iter()
+            .filter_map(|state| state.alert.clone())
+            .collect()
+    }
+
+    pub fn acknowledge(&mut self) {
+        for state in self.states.iter_mut() {
+            if state.alert.take().is_some() && state.exceeded_since.is_some() {
+                state.acknowledged = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::stats::DiskStats;
+
+    fn cpu_rule() -> AlertRule {
+        AlertRule {
+            metric: AlertMetric::Cpu,
+            comparison: Comparison::Above,
+            threshold: 90.0,
+            duration: 30,
+            action: AlertAction::SwitchScreen,
+            mount: None,
+        }
+    }
+
+    fn cpu_stats(cpu_usage: f64) -> SystemStats {
+        SystemStats {
+            cpu_usage,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_alert_is_raised_after_duration() {
+        let rules = vec![cpu_rule()];
+        let mut monitor = AlertMonitor::new();
+        let start = Instant::now();
+
+        monitor.evaluate(&rules, &cpu_stats(95.0), start);
+        monitor.evaluate(&rules, &cpu_stats(95.0), start + Duration::from_secs(29));
+        assert!(monitor.active_alerts().is_empty());
+
+        monitor.evaluate(&rules, &cpu_stats(97.0), start + Duration::from_secs(30));
+        let alerts = monitor.active_alerts();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].message(), "CPU 97% > 90%");
+    }
+
+    #[test]
+    fn test_short_peaks_do_not_raise_alerts() {
+        let rules = vec![cpu_rule()];
+        let mut monitor = AlertMonitor::new();
+        let start = Instant::now();
+
+        monitor.evaluate(&rules, &cpu_stats(95.0), start);
+        monitor.evaluate(&rules, &cpu_stats(50.0), start + Duration::from_secs(20));
+        monitor.evaluate(&rules, &cpu_stats(95.0), start + Duration::from_secs(40));
+        assert!(monitor.active_alerts().is_empty());
+    }
+
+    #[test]
+    fn test_alert_stays_until_acknowledged() {
+        let rules = vec![cpu_rule()];
+        let mut monitor = AlertMonitor::new();
+        let start = Instant::now();
+
+        monitor.evaluate(&rules, &cpu_stats(95.0), start);
+        monitor.evaluate(&rules, &cpu_stats(95.0), start + Duration::from_secs(30));
+        monitor.evaluate(&rules, &cpu_stats(10.0), start + Duration::from_secs(31));
+        assert_eq!(monitor.active_alerts().len(), 1);
+
+        monitor.acknowledge();
+        assert!(monitor.active_alerts().is_empty());
+    }
+
+    #[test]
+    fn test_acknowledged_alert_is_not_raised_again_while_exceeded() {
+        let rules = vec![cpu_rule()];
+        let mut monitor = AlertMonitor::new();
+        let start = Instant::now();
+
+        monitor.evaluate(&rules, &cpu_stats(95.0), start);
+        monitor.evaluate(&rules, &cpu_stats(95.0), start + Duration::from_secs(30));
+        monitor.acknowledge();
+        monitor.evaluate(&rules, &cpu_stats(95.0), start + Duration::from_secs(90));
+        assert!(monitor.active_alerts().is_empty());
+
+        // condition gone and back again
+        monitor.evaluate(&rules, &cpu_stats(10.0), start + Duration::from_secs(91));
+        monitor.evaluate(&rules, &cpu_stats(95.0), start + Duration::from_secs(92));
+        monitor.evaluate(&rules, &cpu_stats(95.0), start + Duration::from_secs(122));
+        assert_eq!(monitor.active_alerts().len(), 1);
+    }
+
+    #[test]
+    fn test_disk_free_rule() {
+        let rule = AlertRule {
+            metric: AlertMetric::DiskFree,
+            comparison: Comparison::Below,
+            threshold: 5.0,
+            duration: 0,
+            action: AlertAction::Flash,
+            mount: None,
+        };
+        let stats = SystemStats {
+            disks: vec![
+                DiskStats {
+                    mount: "/".into(),
+                    total: 100 * GIGABYTE as u64,
+                    used: 50 * GIGABYTE as u64,
+                    ..Default::default()
+                },
+                DiskStats {
+                    mount: "/home".into(),
+                    total: 100 * GIGABYTE as u64,
+                    used: 98 * GIGABYTE as u64,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(rule.value(&stats), Some(2.0));
+        let mut monitor = AlertMonitor::new();
+        monitor.evaluate(std::slice::from_ref(&rule), &stats, Instant::now());
+        assert_eq!(
+            monitor.active_alerts()[0].message(),
+            "Disk free 2.0G < 5.0G"
+        );
+
+        let root_only = AlertRule {
+            mount: Some("/".into()),
+            ..rule
+        };
+        assert_eq!(root_only.value(&stats), Some(50.0));
+    }
+
+    #[test]
+    fn test_deserialize_rule_with_defaults() {
+        let rule: AlertRule = serde_json::from_str(
+            r#"{"metric": "temperature", "comparison": "above", "threshold": 80}"#,
+        )
+        .unwrap();
+        assert_eq!(rule.metric, AlertMetric::Temperature);
+        assert_eq!(rule.duration, 0);
+        assert_eq!(rule.action, AlertAction::SwitchScreen);
+        assert_eq!(rule.mount, None);
+    }
+}
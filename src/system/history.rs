@@ -0,0 +1,145 @@
+use std::collections::{HashMap, VecDeque};
+
+use super::stats::SystemStats;
+
+pub const CPU: &str = "cpu";
+pub const RAM: &str = "ram";
+pub const TEMPERATURE: &str = "temperature";
+pub const NETWORK_RX: &str = "network_rx";
+
+// fixed size ring buffer of the latest values of a single metric
+#[derive(Debug, Clone)]
+pub struct History {
+    capacity: usize,
+    values: VecDeque<f64>,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> History {
+        History {
+            capacity: capacity.max(1),
+            values: VecDeque::with_capacity(capacity.max(1)),
+        }
+    }
+
+    pub fn push(&mut self, value: f64) {
+        while self.values.len() >= self.capacity {
+            self.values.pop_front();
+        }
+        self.values.push_back(value);
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        while self.values.len() > self.capacity {
+            self.values.pop_front();
+        }
+    }
+
+    pub fn values(&self) -> Vec<f64> {
+        self.values.iter().copied().collect()
+    }
+}
+
+// one history per metric, all of them sharing the same capacity
+#[derive(Debug, Clone)]
+pub struct MetricHistory {
+    capacity: usize,
+    series: HashMap<String, History>,
+}
+
+impl MetricHistory {
+    pub fn new(capacity: usize) -> MetricHistory {
+        MetricHistory {
+            capacity,
+            series: HashMap::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        if capacity == self.capacity {
+            return;
+        }
+        self.capacity = capacity;
+        for history in self.series.values_mut() {
+            history.set_capacity(capacity);
+        }
+    }
+
+    pub fn record(&mut self, metric: &str, value: f64) {
+        let capacity = self.capacity;
+        self.series
+            .entry(metric.to_string())
+            .or_insert_with(|| History::new(capacity))
+            .push(value);
+    }
+
+    pub fn record_stats(&mut self, stats: &SystemStats) {
+        self.record(CPU, stats.cpu_usage);
+        self.record(RAM, stats.ram_usage);
+        if let Some(temperature) = stats.max_temperature() {
+            self.record(TEMPERATURE, temperature as f64);
+        }
+        self.record(
+            NETWORK_RX,
+            stats
+                .networks
+                .iter()
+                .map(|network| network.received_per_second)
+                .sum(),
+        );
+    }
+
+    pub fn get(&self, metric: &str) -> Option<&History> {
+        self.series.get(metric)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_drops_oldest_values() {
+        let mut history = History::new(3);
+        for value in 1..=5 {
+            history.push(value as f64);
+        }
+        assert_eq!(history.values(), vec![3.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn test_history_shrinks_on_capacity_change() {
+        let mut history = History::new(5);
+        for value in 1..=5 {
+            history.push(value as f64);
+        }
+        history.set_capacity(2);
+        assert_eq!(history.values(), vec![4.0, 5.0]);
+    }
+
+    #[test]
+    fn test_metric_history_records_stats() {
+        let mut metric_history = MetricHistory::new(10);
+        let stats = SystemStats {
+            cpu_usage: 42.0,
+            ram_usage: 21.0,
+            ..Default::default()
+        };
+        metric_history.record_stats(&stats);
+        metric_history.record_stats(&stats);
+
+        assert_eq!(metric_history.get(CPU).unwrap().values(), vec![42.0, 42.0]);
+        assert_eq!(metric_history.get(RAM).unwrap().values(), vec![21.0, 21.0]);
+        assert_eq!(
+            metric_history.get(NETWORK_RX).unwrap().values(),
+            vec![0.0, 0.0]
+        );
+        // no temperature sensor, no history
+        assert!(metric_history.get(TEMPERATURE).is_none());
+    }
+}
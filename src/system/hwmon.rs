@@ -0,0 +1,99 @@
+use std::{fs, path::Path};
+
+pub const HWMON_PATH: &str = "/sys/class/hwmon";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Temperature {
+    pub label: String,
+    pub celsius: f32,
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|content| content.trim().to_string())
+}
+
+// reads every temp*_input of every hwmon chip below the given root.
+// labels are taken from temp*_label, if the driver offers one, otherwise the chip name is used.
+pub fn read_temperatures(root: &Path) -> Vec<Temperature> {
+    let mut temperatures = Vec::new();
+    let mut chips: Vec<_> = match fs::read_dir(root) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok()).collect(),
+        Err(_) => return temperatures,
+    };
+    chips.sort_by_key(|entry| entry.file_name());
+
+    for chip in chips {
+        let chip_path = chip.path();
+        let chip_name = read_trimmed(&chip_path.join("name"))
+            .unwrap_or_else(|| chip.file_name().to_string_lossy().to_string());
+
+        let mut inputs: Vec<_> = match fs::read_dir(&chip_path) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| name.starts_with("temp") && name.ends_with("_input"))
+                .collect(),
+            Err(_) => continue,
+        };
+        inputs.sort();
+
+        for input in inputs {
+            let millidegrees = match read_trimmed(&chip_path.join(&input))
+                .and_then(|value| value.parse::<f32>().ok())
+            {
+                Some(value) => value,
+                None => continue,
+            };
+            let label_file = input.replace("_input", "_label");
+            let label =
+                read_trimmed(&chip_path.join(label_file)).unwrap_or_else(|| chip_name.clone());
+            temperatures.push(Temperature {
+                label,
+                celsius: millidegrees / 1000.0,
+            });
+        }
+    }
+    temperatures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_dir::TestDir;
+
+    #[test]
+    fn test_read_temperatures() {
+        let root = TestDir::new("hwmon");
+        let chip = root.join("hwmon0");
+        fs::create_dir_all(&chip).unwrap();
+        fs::write(chip.join("name"), "coretemp\n").unwrap();
+        fs::write(chip.join("temp1_input"), "45000\n").unwrap();
+        fs::write(chip.join("temp1_label"), "Package id 0\n").unwrap();
+        fs::write(chip.join("temp2_input"), "51500\n").unwrap();
+        fs::write(chip.join("temp3_input"), "garbage\n").unwrap();
+
+        let temperatures = read_temperatures(root.path());
+
+        assert_eq!(
+            temperatures,
+            vec![
+                Temperature {
+                    label: "Package id 0".into(),
+                    celsius: 45.0
+                },
+                Temperature {
+                    label: "coretemp".into(),
+                    celsius: 51.5
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_temperatures_without_hwmon() {
+        let root = TestDir::new("hwmon_missing");
+        assert!(read_temperatures(&root.join("missing")).is_empty());
+    }
+}
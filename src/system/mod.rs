@@ -0,0 +1,6 @@
+pub mod alerts;
+pub mod history;
+pub mod hwmon;
+pub mod processes;
+pub mod service;
+pub mod stats;
@@ -0,0 +1,262 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub const PROC_PATH: &str = "/proc";
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    // share of the whole machine, 100% means all cores are busy
+    pub cpu_usage: f64,
+    pub memory: u64,
+    pub memory_usage: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProcessOrder {
+    Cpu,
+    Memory,
+}
+
+// returns the name and the consumed cpu time (user + system) in clock ticks.
+// the name is wrapped in parentheses and may contain spaces or parentheses itself.
+pub fn parse_stat(stat: &str) -> Option<(String, u64)> {
+    let name_start = stat.find('(')?;
+    let name_end = stat.rfind(')')?;
+    if name_end < name_start {
+        return None;
+    }
+    let name = stat[name_start + 1..name_end].to_string();
+    // the fields after the name start with the state (field 3), utime and stime are fields 14 and 15
+    let fields: Vec<&str> = stat[name_end + 1..].split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some((name, utime + stime))
+}
+
+// returns the resident memory in bytes
+pub fn parse_status_rss(status: &str) -> Option<u64> {
+    parse_kilobytes(status, "VmRSS:")
+}
+
+// returns the sum of all cpu times of the first line of /proc/stat in clock ticks
+pub fn parse_total_cpu_time(stat: &str) -> Option<u64> {
+    let line = stat.lines().find(|line| line.starts_with("cpu "))?;
+    Some(
+        line.split_whitespace()
+            .skip(1)
+            .filter_map(|value| value.parse::<u64>().ok())
+            .sum(),
+    )
+}
+
+fn parse_kilobytes(content: &str, key: &str) -> Option<u64> {
+    let line = content.lines().find(|line| line.starts_with(key))?;
+    let kilobytes: u64 = line[key.len()..].split_whitespace().next()?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+pub fn top_processes(
+    processes: &[ProcessInfo],
+    order: ProcessOrder,
+    count: usize,
+) -> Vec<ProcessInfo> {
+    let mut processes = processes.to_vec();
+    processes.sort_by(|a, b| {
+        let ordering = match order {
+            ProcessOrder::Cpu => b.cpu_usage.total_cmp(&a.cpu_usage),
+            ProcessOrder::Memory => b.memory.cmp(&a.memory),
+        };
+        ordering.then(a.pid.cmp(&b.pid))
+    });
+    processes.truncate(count);
+    processes
+}
+
+// keeps the cpu times of the last sample, the cpu usage is derived from the difference
+pub struct ProcessCollector {
+    root: PathBuf,
+    last_total_cpu_time: u64,
+    last_cpu_times: HashMap<u32, u64>,
+}
+
+impl Default for ProcessCollector {
+    fn default() -> Self {
+        Self::new(Path::new(PROC_PATH))
+    }
+}
+
+impl ProcessCollector {
+    pub fn new(root: &Path) -> ProcessCollector {
+        ProcessCollector {
+            root: root.to_path_buf(),
+            last_total_cpu_time: 0,
+            last_cpu_times: HashMap::new(),
+        }
+    }
+
+    // on platforms without /proc the list is simply empty
+    pub fn sample(&mut self) -> Vec<ProcessInfo> {
+        let total_cpu_time = fs::read_to_string(self.root.join("stat"))
+            .ok()
+            .and_then(|stat| parse_total_cpu_time(&stat))
+            .unwrap_or(0);
+        let total_memory = fs::read_to_string(self.root.join("meminfo"))
+            .ok()
+            .and_then(|meminfo| parse_kilobytes(&meminfo, "MemTotal:"))
+            .unwrap_or(0);
+        let elapsed = total_cpu_time.saturating_sub(self.last_total_cpu_time);
+
+        let entries = match fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(_) => return vec![],
+        };
+
+        let mut cpu_times = HashMap::new();
+        let mut processes = Vec::new();
+        for entry in entries.flatten() {
+            let pid: u32 = match entry.file_name().to_string_lossy().parse() {
+                Ok(pid) => pid,
+                Err(_) => continue,
+            };
+            // processes may exit while we are reading them
+            let (name, cpu_time) = match fs::read_to_string(entry.path().join("stat"))
+                .ok()
+                .and_then(|stat| parse_stat(&stat))
+            {
+                Some(stat) => stat,
+                None => continue,
+            };
+            // kernel threads have no resident memory
+            let memory = fs::read_to_string(entry.path().join("status"))
+                .ok()
+                .and_then(|status| parse_status_rss(&status))
+                .unwrap_or(0);
+
+            let cpu_usage = match self.last_cpu_times.get(&pid) {
+                Some(last) if elapsed > 0 => {
+                    (cpu_time.saturating_sub(*last) as f64 / elapsed as f64 * 100.0)
+                        .clamp(0.0, 100.0)
+                }
+                _ => 0.0,
+            };
+            let memory_usage = if total_memory > 0 {
+                memory as f64 / total_memory as f64 * 100.0
+            } else {
+                0.0
+            };
+
+            cpu_times.insert(pid, cpu_time);
+            processes.push(ProcessInfo {
+                pid,
+                name,
+                cpu_usage,
+                memory,
+                memory_usage,
+            });
+        }
+
+        self.last_total_cpu_time = total_cpu_time;
+        self.last_cpu_times = cpu_times;
+        processes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_dir::TestDir;
+
+    #[test]
+    fn test_parse_stat() {
+        let stat = "1234 (Web Content (x)) S 1 1234 1234 0 -1 4194560 5000 0 0 0 150 25 0 0 20 0 30 0 100 0 0";
+        assert_eq!(parse_stat(stat), Some(("Web Content (x)".into(), 175)));
+        assert_eq!(parse_stat("1234 (broken"), None);
+    }
+
+    #[test]
+    fn test_parse_status_rss() {
+        let status = "Name:\tbash\nVmPeak:\t   10000 kB\nVmRSS:\t    2048 kB\n";
+        assert_eq!(parse_status_rss(status), Some(2048 * 1024));
+        assert_eq!(parse_status_rss("Name:\tkthreadd\n"), None);
+    }
+
+    #[test]
+    fn test_top_processes() {
+        let processes = vec![
+            ProcessInfo {
+                pid: 1,
+                name: "a".into(),
+                cpu_usage: 10.0,
+                memory: 300,
+                ..Default::default()
+            },
+            ProcessInfo {
+                pid: 2,
+                name: "b".into(),
+                cpu_usage: 50.0,
+                memory: 100,
+                ..Default::default()
+            },
+            ProcessInfo {
+                pid: 3,
+                name: "c".into(),
+                cpu_usage: 30.0,
+                memory: 200,
+                ..Default::default()
+            },
+        ];
+        let by_cpu: Vec<u32> = top_processes(&processes, ProcessOrder::Cpu, 2)
+            .iter()
+            .map(|process| process.pid)
+            .collect();
+        let by_memory: Vec<u32> = top_processes(&processes, ProcessOrder::Memory, 5)
+            .iter()
+            .map(|process| process.pid)
+            .collect();
+        assert_eq!(by_cpu, vec![2, 3]);
+        assert_eq!(by_memory, vec![1, 3, 2]);
+    }
+
+    #[test]
+    fn test_sample_processes() {
+        let root = TestDir::new("proc");
+        let process = root.join("42");
+        fs::create_dir_all(&process).unwrap();
+        fs::create_dir_all(root.join("self_not_a_pid")).unwrap();
+        fs::write(root.join("meminfo"), "MemTotal:       4096 kB\n").unwrap();
+        fs::write(process.join("status"), "VmRSS:\t1024 kB\n").unwrap();
+
+        let write_sample = |total: u64, process_time: u64| {
+            fs::write(root.join("stat"), format!("cpu  {} 0 0 0\n", total)).unwrap();
+            fs::write(
+                process.join("stat"),
+                format!("42 (worker) S 1 1 1 0 -1 0 0 0 0 0 {} 0 0 0", process_time),
+            )
+            .unwrap();
+        };
+
+        let mut collector = ProcessCollector::new(root.path());
+        write_sample(1000, 100);
+        let first = collector.sample();
+        write_sample(1200, 150);
+        let second = collector.sample();
+
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].cpu_usage, 0.0);
+        assert_eq!(
+            second,
+            vec![ProcessInfo {
+                pid: 42,
+                name: "worker".into(),
+                cpu_usage: 25.0,
+                memory: 1024 * 1024,
+                memory_usage: 25.0,
+            }]
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use crossbeam_channel::{bounded, Receiver, Sender};
+use std::{
+    sync::{Arc, Mutex, RwLock},
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{
+    alerts::{Alert, AlertMonitor},
+    history::MetricHistory,
+    stats::{StatsCollector, SystemStats},
+};
+use crate::config_manager::ConfigManager;
+
+// the collector takes one sample per second
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+// five minutes of history, until a screen asks for something else
+const DEFAULT_HISTORY_CAPACITY: usize = 300;
+
+// samples the system metrics in the background, independent of the screen that is currently shown.
+// the latest sample is published to all subscribers, recorded in the history and checked against
+// the alert rules of the config.
+pub struct MetricService {
+    history: Arc<RwLock<MetricHistory>>,
+    alert_monitor: Arc<RwLock<AlertMonitor>>,
+    subscribers: Arc<Mutex<Vec<Sender<Arc<SystemStats>>>>>,
+}
+
+impl MetricService {
+    pub fn new(config_manager: Arc<RwLock<ConfigManager>>) -> MetricService {
+        let history = Arc::new(RwLock::new(MetricHistory::new(DEFAULT_HISTORY_CAPACITY)));
+        let alert_monitor = Arc::new(RwLock::new(AlertMonitor::new()));
+        let subscribers: Arc<Mutex<Vec<Sender<Arc<SystemStats>>>>> =
+            Arc::new(Mutex::new(Vec::new()));
+
+        thread::spawn({
+            let history = history.clone();
+            let alert_monitor = alert_monitor.clone();
+            let subscribers = subscribers.clone();
+            move || {
+                let mut collector = StatsCollector::new();
+                loop {
+                    let system_stats = Arc::new(collector.sample(SAMPLE_INTERVAL));
+                    history.write().unwrap().record_stats(&system_stats);
+
+                    let rules = config_manager.read().unwrap().get_alert_rules();
+                    alert_monitor
+                        .write()
+                        .unwrap()
+                        .evaluate(&rules, &system_stats, Instant::now());
+
+                    for subscriber in subscribers.lock().unwrap().iter() {
+                        let _ = subscriber.try_send(system_stats.clone());
+                    }
+                }
+            }
+        });
+
+        MetricService {
+            history,
+            alert_monitor,
+            subscribers,
+        }
+    }
+
+    // every subscriber gets the latest sample, as long as it keeps up with reading
+    pub fn subscribe(&self) -> Receiver<Arc<SystemStats>> {
+        let (tx, rx) = bounded(1);
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    pub fn history(&self) -> Arc<RwLock<MetricHistory>> {
+        self.history.clone()
+    }
+
+    pub fn active_alerts(&self) -> Vec<Alert> {
+        self.alert_monitor.read().unwrap().active_alerts()
+    }
+
+    pub fn acknowledge_alerts(&self) {
+        self.alert_monitor.write().unwrap().acknowledge();
+    }
+}
@@ -0,0 +1,263 @@
+use cpu_monitor::CpuInstant;
+use std::{
+    collections::HashMap,
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+use systemstat::{saturating_sub_bytes, Platform, System};
+
+use super::hwmon::{read_temperatures, Temperature, HWMON_PATH};
+
+// file systems, that do not represent a real disk
+const PSEUDO_FILE_SYSTEMS: [&str; 12] = [
+    "tmpfs",
+    "devtmpfs",
+    "squashfs",
+    "overlay",
+    "proc",
+    "sysfs",
+    "cgroup",
+    "cgroup2",
+    "devpts",
+    "efivarfs",
+    "fuse.portal",
+    "ramfs",
+];
+
+// blocks in /proc/diskstats are always 512 bytes, regardless of the device
+const SECTOR_SIZE: f64 = 512.0;
+
+#[derive(Default, Debug, Clone)]
+pub struct DiskStats {
+    pub mount: String,
+    pub total: u64,
+    pub used: u64,
+    pub read_per_second: f64,
+    pub write_per_second: f64,
+}
+
+impl DiskStats {
+    pub fn usage(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        (self.used as f64 / self.total as f64) * 100.0
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct NetworkThroughput {
+    pub interface: String,
+    pub received_per_second: f64,
+    pub transmitted_per_second: f64,
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct BatteryStats {
+    pub capacity: f32,
+    pub on_ac_power: bool,
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct SystemStats {
+    pub cpu_usage: f64,
+    pub core_usage: Vec<f64>,
+    pub load_average: Option<(f32, f32, f32)>,
+    pub temperatures: Vec<Temperature>,
+    pub ram_usage: f64,
+    pub swap_usage: Option<f64>,
+    pub disks: Vec<DiskStats>,
+    pub networks: Vec<NetworkThroughput>,
+    pub uptime: Option<Duration>,
+    pub battery: Option<BatteryStats>,
+}
+
+impl SystemStats {
+    pub fn max_temperature(&self) -> Option<f32> {
+        self.temperatures
+            .iter()
+            .map(|temperature| temperature.celsius)
+            .reduce(f32::max)
+    }
+}
+
+// keeps the counters of the last sample to derive rates (disk io, network throughput)
+pub struct StatsCollector {
+    sys: System,
+    last_sample: Instant,
+    last_block_sectors: HashMap<String, (usize, usize)>,
+    last_network_bytes: HashMap<String, (u64, u64)>,
+}
+
+impl Default for StatsCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StatsCollector {
+    pub fn new() -> StatsCollector {
+        StatsCollector {
+            sys: System::new(),
+            last_sample: Instant::now(),
+            last_block_sectors: HashMap::new(),
+            last_network_bytes: HashMap::new(),
+        }
+    }
+
+    // blocks for the given interval to measure the cpu load
+    pub fn sample(&mut self, interval: Duration) -> SystemStats {
+        let core_measurement = self.sys.cpu_load();
+        let start = CpuInstant::now();
+        thread::sleep(interval);
+        let end = CpuInstant::now();
+
+        let cpu_usage = match (start, end) {
+            (Ok(start), Ok(end)) => ((end - start).non_idle() * 100.0).floor(),
+            _ => 0.0,
+        };
+        let core_usage = core_measurement
+            .and_then(|measurement| measurement.done())
+            .map(|cores| {
+                cores
+                    .iter()
+                    .map(|core| ((1.0 - core.idle as f64) * 100.0).clamp(0.0, 100.0))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let elapsed = self.last_sample.elapsed().as_secs_f64().max(0.001);
+        self.last_sample = Instant::now();
+
+        SystemStats {
+            cpu_usage,
+            core_usage,
+            load_average: self
+                .sys
+                .load_average()
+                .ok()
+                .map(|load| (load.one, load.five, load.fifteen)),
+            temperatures: self.temperatures(),
+            ram_usage: self.ram_usage(),
+            swap_usage: self.swap_usage(),
+            disks: self.disks(elapsed),
+            networks: self.networks(elapsed),
+            uptime: self.sys.uptime().ok(),
+            battery: self.sys.battery_life().ok().map(|battery| BatteryStats {
+                capacity: battery.remaining_capacity * 100.0,
+                on_ac_power: self.sys.on_ac_power().unwrap_or(false),
+            }),
+        }
+    }
+
+    fn temperatures(&self) -> Vec<Temperature> {
+        let temperatures = read_temperatures(Path::new(HWMON_PATH));
+        if !temperatures.is_empty() {
+            return temperatures;
+        }
+        match self.sys.cpu_temp() {
+            Ok(celsius) => vec![Temperature {
+                label: "CPU".into(),
+                celsius,
+            }],
+            Err(_) => vec![],
+        }
+    }
+
+    fn ram_usage(&self) -> f64 {
+        match self.sys.memory() {
+            Ok(mem) => {
+                let used = saturating_sub_bytes(mem.total, mem.free).as_u64() as f64;
+                ((used / mem.total.as_u64() as f64) * 100.0).floor()
+            }
+            Err(_) => 0.0,
+        }
+    }
+
+    fn swap_usage(&self) -> Option<f64> {
+        match self.sys.swap() {
+            Ok(swap) if swap.total.as_u64() > 0 => {
+                let used = saturating_sub_bytes(swap.total, swap.free).as_u64() as f64;
+                Some(((used / swap.total.as_u64() as f64) * 100.0).floor())
+            }
+            _ => None,
+        }
+    }
+
+    fn disks(&mut self, elapsed: f64) -> Vec<DiskStats> {
+        let block_devices = self.sys.block_device_statistics().unwrap_or_default();
+        let mut block_sectors = HashMap::new();
+        let mut disks = Vec::new();
+
+        for mount in self.sys.mounts().unwrap_or_default() {
+            if mount.total.as_u64() == 0
+                || PSEUDO_FILE_SYSTEMS.contains(&mount.fs_type.as_str())
+                || disks
+                    .iter()
+                    .any(|disk: &DiskStats| disk.mount == mount.fs_mounted_on)
+            {
+                continue;
+            }
+
+            let mut disk = DiskStats {
+                mount: mount.fs_mounted_on.clone(),
+                total: mount.total.as_u64(),
+                used: saturating_sub_bytes(mount.total, mount.avail).as_u64(),
+                ..Default::default()
+            };
+
+            // /dev/sda1 is listed as sda1 in /proc/diskstats
+            let device = mount
+                .fs_mounted_from
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            if let Some(stats) = block_devices.get(&device) {
+                let sectors = (stats.read_sectors, stats.write_sectors);
+                if let Some(last) = self.last_block_sectors.get(&device) {
+                    disk.read_per_second =
+                        sectors.0.saturating_sub(last.0) as f64 * SECTOR_SIZE / elapsed;
+                    disk.write_per_second =
+                        sectors.1.saturating_sub(last.1) as f64 * SECTOR_SIZE / elapsed;
+                }
+                block_sectors.insert(device, sectors);
+            }
+            disks.push(disk);
+        }
+
+        self.last_block_sectors = block_sectors;
+        disks
+    }
+
+    fn networks(&mut self, elapsed: f64) -> Vec<NetworkThroughput> {
+        let interfaces = self.sys.networks().unwrap_or_default();
+        let mut network_bytes = HashMap::new();
+        let mut networks = Vec::new();
+
+        for interface in interfaces.keys() {
+            if interface == "lo" {
+                continue;
+            }
+            let stats = match self.sys.network_stats(interface) {
+                Ok(stats) => stats,
+                Err(_) => continue,
+            };
+            let bytes = (stats.rx_bytes.as_u64(), stats.tx_bytes.as_u64());
+            let mut throughput = NetworkThroughput {
+                interface: interface.clone(),
+                ..Default::default()
+            };
+            if let Some(last) = self.last_network_bytes.get(interface) {
+                throughput.received_per_second = bytes.0.saturating_sub(last.0) as f64 / elapsed;
+                throughput.transmitted_per_second = bytes.1.saturating_sub(last.1) as f64 / elapsed;
+            }
+            network_bytes.insert(interface.clone(), bytes);
+            networks.push(throughput);
+        }
+
+        self.last_network_bytes = network_bytes;
+        networks
+    }
+}
@@ -0,0 +1,145 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::location::Result as Location;
+use super::report::WeatherReport;
+
+pub const CACHE_PATH: &str = "./weather_cache.json";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CachedReport {
+    pub fetched_at: DateTime<Utc>,
+    pub report: WeatherReport,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+struct CacheContent {
+    // coordinates of a city do not change, so geocoding results are kept forever
+    locations: HashMap<String, Location>,
+    // the last good forecast of every location, shown while the provider is unreachable
+    reports: HashMap<String, CachedReport>,
+}
+
+// geocoding results and the last good forecast, persisted next to the settings
+pub struct WeatherCache {
+    path: PathBuf,
+    content: CacheContent,
+}
+
+fn cache_key(city: &str) -> String {
+    city.trim().to_lowercase()
+}
+
+impl WeatherCache {
+    pub fn load(path: &Path) -> WeatherCache {
+        let content = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        WeatherCache {
+            path: path.to_path_buf(),
+            content,
+        }
+    }
+
+    fn save(&self) {
+        match serde_json::to_string(&self.content) {
+            Ok(content) => {
+                if let Err(e) = fs::write(&self.path, content) {
+                    eprintln!("Error: {:?}", e);
+                }
+            }
+            Err(e) => eprintln!("Error: {:?}", e),
+        }
+    }
+
+    pub fn location(&self, city: &str) -> Option<Location> {
+        self.content.locations.get(&cache_key(city)).cloned()
+    }
+
+    pub fn store_location(&mut self, city: &str, location: &Location) {
+        self.content
+            .locations
+            .insert(cache_key(city), location.clone());
+        self.save();
+    }
+
+    // reports are stored by the cache key of the location query
+    pub fn report(&self, key: &str) -> Option<CachedReport> {
+        self.content.reports.get(&cache_key(key)).cloned()
+    }
+
+    // e.g. for the sun times of the display location, no matter which location it is
+    pub fn newest_report(&self) -> Option<CachedReport> {
+        self.content
+            .reports
+            .values()
+            .max_by_key(|cached| cached.fetched_at)
+            .cloned()
+    }
+
+    pub fn store_report(&mut self, key: &str, report: &WeatherReport, fetched_at: DateTime<Utc>) {
+        self.content.reports.insert(
+            cache_key(key),
+            CachedReport {
+                fetched_at,
+                report: report.clone(),
+            },
+        );
+        self.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_dir::TestDir;
+
+    #[test]
+    fn test_cache_survives_reload() {
+        let dir = TestDir::new("weather_cache");
+        let path = dir.join("weather_cache.json");
+
+        let location = Location {
+            name: "Berlin".into(),
+            latitude: 52.52,
+            longitude: 13.41,
+            ..Default::default()
+        };
+        let report = WeatherReport {
+            city: "Berlin,DE".into(),
+            temperature: 12.0,
+            ..Default::default()
+        };
+        let fetched_at = Utc::now();
+
+        let mut cache = WeatherCache::load(&path);
+        assert_eq!(cache.location("Berlin"), None);
+        cache.store_location("Berlin", &location);
+        cache.store_report("Berlin", &report, fetched_at);
+
+        let cache = WeatherCache::load(&path);
+        assert_eq!(cache.location(" berlin "), Some(location));
+        let cached = cache.report("BERLIN").unwrap();
+        assert_eq!(cached.report, report);
+        assert_eq!(cached.fetched_at, fetched_at);
+        assert_eq!(cache.report("Paris"), None);
+        assert_eq!(cache.newest_report().unwrap().report, report);
+    }
+
+    #[test]
+    fn test_broken_cache_file_is_ignored() {
+        let dir = TestDir::new("weather_cache_broken");
+        let path = dir.join("weather_cache.json");
+        fs::write(&path, "{ not json").unwrap();
+
+        let cache = WeatherCache::load(&path);
+        assert_eq!(cache.location("Berlin"), None);
+    }
+}
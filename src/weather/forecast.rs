@@ -0,0 +1,243 @@
+use chrono::{Duration, NaiveDateTime, Timelike};
+use open_meteo_rs::forecast::{ForecastResultDaily, ForecastResultHourly, ForecastResultItem};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// the pressure has to change by more than this within three hours to count as a trend (hPa)
+const PRESSURE_TREND_THRESHOLD: f64 = 1.0;
+const PRESSURE_TREND_HOURS: i64 = 3;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HourlyForecast {
+    pub time: NaiveDateTime,
+    pub temperature: f64,
+    pub precipitation_probability: f64,
+    pub pressure: Option<f64>,
+    pub uv_index: Option<f64>,
+    // sum of the preceding hour
+    pub precipitation: Option<f64>,
+    pub weather_code: Option<u8>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct SunTimes {
+    pub sunrise: NaiveDateTime,
+    pub sunset: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PressureTrend {
+    Rising,
+    Steady,
+    Falling,
+}
+
+impl PressureTrend {
+    pub fn arrow(&self) -> &'static str {
+        match self {
+            PressureTrend::Rising => "\u{2191}",
+            PressureTrend::Steady => "\u{2192}",
+            PressureTrend::Falling => "\u{2193}",
+        }
+    }
+}
+
+pub fn value_f64(values: &HashMap<String, ForecastResultItem>, key: &str) -> Option<f64> {
+    values.get(key).and_then(|item| item.value.as_f64())
+}
+
+// open-meteo sends times in the time zone of the location, e.g. "2026-10-18T07:45"
+fn value_time(values: &HashMap<String, ForecastResultItem>, key: &str) -> Option<NaiveDateTime> {
+    let value = values.get(key)?.value.as_str()?;
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M").ok()
+}
+
+pub fn hourly_forecasts(hourly: &[ForecastResultHourly]) -> Vec<HourlyForecast> {
+    hourly
+        .iter()
+        .filter_map(|hour| {
+            Some(HourlyForecast {
+                time: hour.datetime,
+                temperature: value_f64(&hour.values, "temperature_2m")?,
+                precipitation_probability: value_f64(&hour.values, "precipitation_probability")
+                    .unwrap_or_default(),
+                pressure: value_f64(&hour.values, "pressure_msl"),
+                uv_index: value_f64(&hour.values, "uv_index"),
+                precipitation: value_f64(&hour.values, "precipitation"),
+                weather_code: hour
+                    .values
+                    .get("weather_code")
+                    .and_then(|v| v.value.as_u64())
+                    .map(|code| code as u8),
+            })
+        })
+        .collect()
+}
+
+// the given number of hours, starting with the current one
+pub fn upcoming_hours(
+    hours: &[HourlyForecast],
+    now: NaiveDateTime,
+    count: usize,
+) -> Vec<HourlyForecast> {
+    let current_hour = now.with_minute(0).and_then(|hour| hour.with_second(0));
+    hours
+        .iter()
+        .filter(|hour| Some(hour.time) >= current_hour)
+        .take(count)
+        .cloned()
+        .collect()
+}
+
+fn pressure_at(hours: &[HourlyForecast], time: NaiveDateTime) -> Option<f64> {
+    hours
+        .iter()
+        .find(|hour| hour.time == time)
+        .and_then(|hour| hour.pressure)
+}
+
+// compares the pressure of the current hour with the one three hours ago,
+// early in the morning the forecast of the next three hours is used instead
+pub fn pressure_trend(hours: &[HourlyForecast], now: NaiveDateTime) -> Option<PressureTrend> {
+    let current_hour = now.with_minute(0)?.with_second(0)?;
+    let current = pressure_at(hours, current_hour)?;
+    let (earlier, later) =
+        match pressure_at(hours, current_hour - Duration::hours(PRESSURE_TREND_HOURS)) {
+            Some(earlier) => (earlier, current),
+            None => (
+                current,
+                pressure_at(hours, current_hour + Duration::hours(PRESSURE_TREND_HOURS))?,
+            ),
+        };
+
+    let change = later - earlier;
+    Some(if change > PRESSURE_TREND_THRESHOLD {
+        PressureTrend::Rising
+    } else if change < -PRESSURE_TREND_THRESHOLD {
+        PressureTrend::Falling
+    } else {
+        PressureTrend::Steady
+    })
+}
+
+pub fn sun_times(daily: &ForecastResultDaily) -> Option<SunTimes> {
+    Some(SunTimes {
+        sunrise: value_time(&daily.values, "sunrise")?,
+        sunset: value_time(&daily.values, "sunset")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn time(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, 18)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    fn item(value: serde_json::Value) -> ForecastResultItem {
+        ForecastResultItem { unit: None, value }
+    }
+
+    fn hours(pressures: &[f64]) -> Vec<HourlyForecast> {
+        pressures
+            .iter()
+            .enumerate()
+            .map(|(index, pressure)| HourlyForecast {
+                time: time(index as u32),
+                temperature: 10.0,
+                precipitation_probability: 0.0,
+                pressure: Some(*pressure),
+                uv_index: None,
+                precipitation: None,
+                weather_code: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_hourly_forecasts() {
+        let hourly = vec![
+            ForecastResultHourly {
+                datetime: time(0),
+                values: HashMap::from([
+                    ("temperature_2m".into(), item(serde_json::json!(8.5))),
+                    (
+                        "precipitation_probability".into(),
+                        item(serde_json::json!(40)),
+                    ),
+                    ("pressure_msl".into(), item(serde_json::json!(1013.2))),
+                    ("weather_code".into(), item(serde_json::json!(95))),
+                ]),
+            },
+            // hours without a temperature are useless for the chart
+            ForecastResultHourly {
+                datetime: time(1),
+                values: HashMap::new(),
+            },
+        ];
+
+        let forecasts = hourly_forecasts(&hourly);
+        assert_eq!(forecasts.len(), 1);
+        assert_eq!(forecasts[0].temperature, 8.5);
+        assert_eq!(forecasts[0].precipitation_probability, 40.0);
+        assert_eq!(forecasts[0].pressure, Some(1013.2));
+        assert_eq!(forecasts[0].uv_index, None);
+        assert_eq!(forecasts[0].weather_code, Some(95));
+    }
+
+    #[test]
+    fn test_upcoming_hours_start_with_the_current_hour() {
+        let hours = hours(&[1000.0; 24]);
+        let now = time(5) + Duration::minutes(40);
+        let upcoming = upcoming_hours(&hours, now, 3);
+        assert_eq!(
+            upcoming.iter().map(|hour| hour.time).collect::<Vec<_>>(),
+            vec![time(5), time(6), time(7)]
+        );
+    }
+
+    #[test]
+    fn test_pressure_trend() {
+        let rising = hours(&[1000.0, 1000.5, 1001.0, 1002.0, 1002.5]);
+        assert_eq!(
+            pressure_trend(&rising, time(3)),
+            Some(PressureTrend::Rising)
+        );
+        let steady = hours(&[1000.0, 1000.2, 1000.4, 1000.6]);
+        assert_eq!(
+            pressure_trend(&steady, time(3)),
+            Some(PressureTrend::Steady)
+        );
+
+        // no history yet, the forecast is used
+        let falling = hours(&[1010.0, 1009.0, 1008.0, 1007.0]);
+        assert_eq!(
+            pressure_trend(&falling, time(0)),
+            Some(PressureTrend::Falling)
+        );
+        assert_eq!(pressure_trend(&falling, time(20)), None);
+    }
+
+    #[test]
+    fn test_sun_times() {
+        let daily = ForecastResultDaily {
+            date: NaiveDate::from_ymd_opt(2026, 10, 18).unwrap(),
+            values: HashMap::from([
+                (
+                    "sunrise".into(),
+                    item(serde_json::json!("2026-10-18T07:45")),
+                ),
+                ("sunset".into(), item(serde_json::json!("2026-10-18T18:21"))),
+            ]),
+        };
+        let sun = sun_times(&daily).unwrap();
+        assert_eq!(sun.sunrise, time(7) + Duration::minutes(45));
+        assert_eq!(sun.sunset, time(18) + Duration::minutes(21));
+    }
+}
@@ -1,12 +1,12 @@
-
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json;
 use std::fmt::Formatter;
 use std::fmt::{Display, Error as FmtError};
 
+// the api leaves out "results" for unknown cities and admin fields that do not apply
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct Locations {
     pub results: Vec<Result>,
     #[serde(rename = "generationtime_ms")]
@@ -14,7 +14,7 @@ pub struct Locations {
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct Result {
     pub id: i64,
     pub name: String,
@@ -68,14 +68,47 @@ impl Display for ExtractCodeError {
         }
     }
 }
+const GEOCODING_URL: &str = "https://geocoding-api.open-meteo.com/v1/search";
+
 pub fn get_location(city: String) -> core::result::Result<Locations, ExtractCodeError> {
     let locations: Locations = serde_json::from_str(
-        reqwest::blocking::get(format!(
-        "https://geocoding-api.open-meteo.com/v1/search?name={}&count=1&language=en&format=json",
-        city
-    ))?
-        .text()?
-        .as_str(),
+        reqwest::blocking::Client::new()
+            .get(GEOCODING_URL)
+            .query(&[
+                ("name", city.as_str()),
+                ("count", "1"),
+                ("language", "en"),
+                ("format", "json"),
+            ])
+            .send()?
+            .error_for_status()?
+            .text()?
+            .as_str(),
     )?;
     Ok(locations)
 }
+
+// all candidates of an ambiguous name, so that the user can pick one
+pub async fn search_locations(
+    city: String,
+    count: u8,
+) -> core::result::Result<Vec<Result>, ExtractCodeError> {
+    let count = count.to_string();
+    let locations: Locations = serde_json::from_str(
+        reqwest::Client::new()
+            .get(GEOCODING_URL)
+            .query(&[
+                ("name", city.as_str()),
+                ("count", count.as_str()),
+                ("language", "en"),
+                ("format", "json"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?
+            .as_str(),
+    )?;
+    Ok(locations.results)
+}
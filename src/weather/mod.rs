@@ -1,2 +1,8 @@
+pub mod cache;
+pub mod forecast;
 pub mod location;
+pub mod places;
+pub mod provider;
+pub mod report;
+pub mod warnings;
 pub mod weather;
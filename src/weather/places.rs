@@ -0,0 +1,217 @@
+use super::location::Result as Location;
+
+// a configured location is either a name for the geocoding api or coordinates
+#[derive(Clone, Debug, PartialEq)]
+pub enum LocationQuery {
+    City(String),
+    Coordinates { latitude: f64, longitude: f64 },
+}
+
+impl LocationQuery {
+    // "52.52, 13.41" are coordinates, everything else is a name
+    pub fn parse(text: &str) -> Option<LocationQuery> {
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        if let Some((latitude, longitude)) = text.split_once(',') {
+            if let (Ok(latitude), Ok(longitude)) = (
+                latitude.trim().parse::<f64>(),
+                longitude.trim().parse::<f64>(),
+            ) {
+                if (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude) {
+                    return Some(LocationQuery::Coordinates {
+                        latitude,
+                        longitude,
+                    });
+                }
+            }
+        }
+        Some(LocationQuery::City(text.into()))
+    }
+
+    pub fn cache_key(&self) -> String {
+        match self {
+            LocationQuery::City(city) => city.trim().to_lowercase(),
+            LocationQuery::Coordinates {
+                latitude,
+                longitude,
+            } => format!("{:.4},{:.4}", latitude, longitude),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SavedLocation {
+    // shown instead of the name the geocoding api returns
+    pub label: Option<String>,
+    pub query: LocationQuery,
+}
+
+// ';' separates the entries and '=' the label from the query
+fn clean_label(label: &str) -> String {
+    label.replace([';', '='], " ").trim().to_string()
+}
+
+impl SavedLocation {
+    // "Home=52.52,13.41", "Work=Frankfurt am Main" or just "Berlin"
+    pub fn parse(entry: &str) -> Option<SavedLocation> {
+        match entry.split_once('=') {
+            Some((label, query)) => Some(SavedLocation {
+                label: Some(label.trim().to_string()).filter(|label| !label.is_empty()),
+                query: LocationQuery::parse(query)?,
+            }),
+            None => Some(SavedLocation {
+                label: None,
+                query: LocationQuery::parse(entry)?,
+            }),
+        }
+    }
+
+    // a picked geocoding result is saved with its coordinates, so it is never ambiguous again
+    pub fn from_geocoding(location: &Location) -> SavedLocation {
+        SavedLocation {
+            label: Some(clean_label(&location.name)),
+            query: LocationQuery::Coordinates {
+                latitude: location.latitude,
+                longitude: location.longitude,
+            },
+        }
+    }
+
+    pub fn to_entry(&self) -> String {
+        let query = match &self.query {
+            LocationQuery::City(city) => clean_label(city),
+            LocationQuery::Coordinates {
+                latitude,
+                longitude,
+            } => format!("{:.4},{:.4}", latitude, longitude),
+        };
+        match &self.label {
+            Some(label) => format!("{}={}", clean_label(label), query),
+            None => query,
+        }
+    }
+
+    // coordinates need no geocoding, the location is built from them
+    pub fn coordinates_location(&self) -> Option<Location> {
+        match self.query {
+            LocationQuery::Coordinates {
+                latitude,
+                longitude,
+            } => Some(Location {
+                name: self
+                    .label
+                    .clone()
+                    .unwrap_or_else(|| format!("{:.2},{:.2}", latitude, longitude)),
+                latitude,
+                longitude,
+                ..Default::default()
+            }),
+            LocationQuery::City(_) => None,
+        }
+    }
+}
+
+pub fn parse_locations(text: &str) -> Vec<SavedLocation> {
+    text.split(';').filter_map(SavedLocation::parse).collect()
+}
+
+pub fn format_locations(locations: &[SavedLocation]) -> String {
+    locations
+        .iter()
+        .map(SavedLocation::to_entry)
+        .collect::<Vec<String>>()
+        .join(";")
+}
+
+// the saved locations win over the single location of older configurations
+pub fn configured_locations(locations: &str, weather_location: &str) -> Vec<SavedLocation> {
+    let saved = parse_locations(locations);
+    if !saved.is_empty() {
+        return saved;
+    }
+    SavedLocation::parse(weather_location).into_iter().collect()
+}
+
+// "Frankfurt am Main, Hesse, Germany" tells the candidates of an ambiguous name apart
+pub fn describe(location: &Location) -> String {
+    [
+        location.name.as_str(),
+        location.admin1.as_str(),
+        location.country.as_str(),
+    ]
+    .iter()
+    .filter(|part| !part.is_empty())
+    .copied()
+    .collect::<Vec<&str>>()
+    .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_locations() {
+        let locations = parse_locations("Home=52.52, 13.41; Work = Frankfurt am Main;;Paris");
+        assert_eq!(locations.len(), 3);
+        assert_eq!(locations[0].label.as_deref(), Some("Home"));
+        assert_eq!(
+            locations[0].query,
+            LocationQuery::Coordinates {
+                latitude: 52.52,
+                longitude: 13.41
+            }
+        );
+        assert_eq!(
+            locations[1].query,
+            LocationQuery::City("Frankfurt am Main".into())
+        );
+        assert_eq!(locations[2].label, None);
+
+        // out of range, so it is a (strange) name
+        assert_eq!(
+            LocationQuery::parse("123,456"),
+            Some(LocationQuery::City("123,456".into()))
+        );
+        assert_eq!(LocationQuery::parse(" "), None);
+        assert_eq!(
+            format_locations(&locations),
+            "Home=52.5200,13.4100;Work=Frankfurt am Main;Paris"
+        );
+    }
+
+    #[test]
+    fn test_configured_locations() {
+        assert_eq!(configured_locations("", "").len(), 0);
+        let single = configured_locations(" ", "Springfield");
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].query, LocationQuery::City("Springfield".into()));
+        assert_eq!(configured_locations("A=1,2;B=3,4", "Springfield").len(), 2);
+    }
+
+    #[test]
+    fn test_saved_from_geocoding() {
+        let location = Location {
+            name: "Frankfurt (Oder)".into(),
+            latitude: 52.34714,
+            longitude: 14.55062,
+            admin1: "Brandenburg".into(),
+            country: "Germany".into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            describe(&location),
+            "Frankfurt (Oder), Brandenburg, Germany"
+        );
+        let saved = SavedLocation::from_geocoding(&location);
+        assert_eq!(saved.to_entry(), "Frankfurt (Oder)=52.3471,14.5506");
+        let reparsed = SavedLocation::parse(&saved.to_entry()).unwrap();
+        assert_eq!(reparsed.to_entry(), saved.to_entry());
+        assert_eq!(
+            saved.coordinates_location().unwrap().name,
+            "Frankfurt (Oder)"
+        );
+    }
+}
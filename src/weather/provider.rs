@@ -0,0 +1,260 @@
+use chrono::{DateTime, Utc};
+use std::fmt::{Display, Error as FmtError, Formatter};
+use std::{fs, path::PathBuf};
+
+use super::cache::WeatherCache;
+use super::location::{get_location, Result as Location};
+use super::places::{LocationQuery, SavedLocation};
+use super::report::WeatherReport;
+use super::weather::{get_weather, set_opts, WeatherSettings};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WeatherError {
+    NoLocation,
+    LocationFailed(String),
+    UnknownLocation(String),
+    ForecastFailed(String),
+    NoData,
+}
+
+// short messages, they are shown on the display
+impl Display for WeatherError {
+    fn fmt(&self, f: &mut Formatter) -> core::result::Result<(), FmtError> {
+        match self {
+            Self::NoLocation => write!(f, "No location configured"),
+            Self::LocationFailed(_) => write!(f, "Location lookup failed"),
+            Self::UnknownLocation(city) => write!(f, "Unknown location {}", city),
+            Self::ForecastFailed(_) => write!(f, "Forecast request failed"),
+            Self::NoData => write!(f, "No weather data"),
+        }
+    }
+}
+
+pub trait WeatherProvider {
+    fn locate(&self, city: &str) -> Result<Location, WeatherError>;
+    fn forecast(
+        &self,
+        location: &Location,
+        settings: &WeatherSettings,
+    ) -> Result<WeatherReport, WeatherError>;
+}
+
+pub struct OpenMeteoProvider {
+    client: open_meteo_rs::Client,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl OpenMeteoProvider {
+    pub fn new() -> OpenMeteoProvider {
+        OpenMeteoProvider {
+            client: open_meteo_rs::Client::new(),
+            runtime: tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to create Tokio runtime"),
+        }
+    }
+}
+
+impl Default for OpenMeteoProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WeatherProvider for OpenMeteoProvider {
+    fn locate(&self, city: &str) -> Result<Location, WeatherError> {
+        let locations =
+            get_location(city.into()).map_err(|e| WeatherError::LocationFailed(e.to_string()))?;
+        locations
+            .results
+            .into_iter()
+            .next()
+            .ok_or_else(|| WeatherError::UnknownLocation(city.into()))
+    }
+
+    fn forecast(
+        &self,
+        location: &Location,
+        settings: &WeatherSettings,
+    ) -> Result<WeatherReport, WeatherError> {
+        let mut opts = open_meteo_rs::forecast::Options::default();
+        set_opts(&mut opts, location, settings);
+        let result = self
+            .runtime
+            .block_on(get_weather(&self.client, opts))
+            .map_err(|e| WeatherError::ForecastFailed(e.to_string()))?;
+        WeatherReport::from_forecast(&result, location, settings.unit_system)
+            .ok_or(WeatherError::NoData)
+    }
+}
+
+// reads "geocoding.json" (the answer of the geocoding api) and "forecast.json" (a weather report)
+// from a directory, for tests and for running without network
+pub struct FileProvider {
+    dir: PathBuf,
+}
+
+impl FileProvider {
+    pub fn new(dir: PathBuf) -> FileProvider {
+        FileProvider { dir }
+    }
+
+    fn read(&self, name: &str) -> Result<String, String> {
+        fs::read_to_string(self.dir.join(name)).map_err(|e| e.to_string())
+    }
+}
+
+impl WeatherProvider for FileProvider {
+    fn locate(&self, city: &str) -> Result<Location, WeatherError> {
+        let content = self
+            .read("geocoding.json")
+            .map_err(WeatherError::LocationFailed)?;
+        let locations: super::location::Locations = serde_json::from_str(&content)
+            .map_err(|e| WeatherError::LocationFailed(e.to_string()))?;
+        locations
+            .results
+            .into_iter()
+            .next()
+            .ok_or_else(|| WeatherError::UnknownLocation(city.into()))
+    }
+
+    fn forecast(
+        &self,
+        _location: &Location,
+        _settings: &WeatherSettings,
+    ) -> Result<WeatherReport, WeatherError> {
+        let content = self
+            .read("forecast.json")
+            .map_err(WeatherError::ForecastFailed)?;
+        serde_json::from_str(&content).map_err(|e| WeatherError::ForecastFailed(e.to_string()))
+    }
+}
+
+// "open-meteo" (default) or "file:<directory>"
+pub fn provider_from_key(key: &str) -> Box<dyn WeatherProvider> {
+    match key.trim().strip_prefix("file:") {
+        Some(dir) => Box::new(FileProvider::new(PathBuf::from(dir.trim()))),
+        None => Box::new(OpenMeteoProvider::new()),
+    }
+}
+
+// geocoding results are taken from the cache, good forecasts are stored in it
+pub fn fetch_weather(
+    provider: &dyn WeatherProvider,
+    cache: &mut WeatherCache,
+    place: &SavedLocation,
+    settings: &WeatherSettings,
+    now: DateTime<Utc>,
+) -> Result<WeatherReport, WeatherError> {
+    let location = match &place.query {
+        LocationQuery::Coordinates { .. } => place.coordinates_location(),
+        LocationQuery::City(city) => cache.location(city),
+    };
+    let location = match (location, &place.query) {
+        (Some(location), _) => location,
+        (None, LocationQuery::City(city)) => {
+            let location = provider.locate(city)?;
+            cache.store_location(city, &location);
+            location
+        }
+        (None, LocationQuery::Coordinates { .. }) => return Err(WeatherError::NoLocation),
+    };
+    let mut report = provider.forecast(&location, settings)?;
+    if let Some(label) = &place.label {
+        report.city = label.clone();
+    }
+    cache.store_report(&place.query.cache_key(), &report, now);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::test_dir::TestDir;
+    use std::path::Path;
+
+    fn fixtures() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("src/weather/fixtures")
+    }
+
+    #[test]
+    fn test_file_provider() {
+        let provider = FileProvider::new(fixtures());
+        let location = provider.locate("Berlin").unwrap();
+        assert_eq!(location.name, "Berlin");
+        assert_eq!(location.timezone, "Europe/Berlin");
+
+        let report = provider
+            .forecast(&location, &WeatherSettings::default())
+            .unwrap();
+        assert_eq!(report.city, "Berlin,DE");
+        assert_eq!(report.weather_forecast.len(), 3);
+
+        let missing = FileProvider::new(PathBuf::from("/does/not/exist"));
+        assert!(matches!(
+            missing.locate("Berlin"),
+            Err(WeatherError::LocationFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_fetch_weather_uses_and_fills_the_cache() {
+        let dir = TestDir::new("weather_fetch");
+        let mut cache = WeatherCache::load(&dir.join("cache.json"));
+        let settings = WeatherSettings::default();
+        let now = Utc::now();
+        let berlin = SavedLocation::parse("Berlin").unwrap();
+
+        let report = fetch_weather(
+            &FileProvider::new(fixtures()),
+            &mut cache,
+            &berlin,
+            &settings,
+            now,
+        )
+        .unwrap();
+        assert_eq!(cache.report("Berlin").unwrap().report, report);
+        assert!(cache.location("Berlin").is_some());
+
+        // the location comes from the cache, only the forecast is needed
+        let forecast_only = TestDir::new("weather_forecast_only");
+        fs::copy(
+            fixtures().join("forecast.json"),
+            forecast_only.join("forecast.json"),
+        )
+        .unwrap();
+        assert!(fetch_weather(
+            &FileProvider::new(forecast_only.path().to_path_buf()),
+            &mut cache,
+            &berlin,
+            &settings,
+            now,
+        )
+        .is_ok());
+
+        // offline: the last good report stays in the cache
+        let error = fetch_weather(
+            &FileProvider::new(PathBuf::from("/does/not/exist")),
+            &mut cache,
+            &berlin,
+            &settings,
+            now,
+        );
+        assert!(matches!(error, Err(WeatherError::ForecastFailed(_))));
+        assert_eq!(cache.report("Berlin").unwrap().fetched_at, now);
+
+        // coordinates need no geocoding, the label replaces the city
+        let home = SavedLocation::parse("Home=52.52,13.41").unwrap();
+        let report = fetch_weather(
+            &FileProvider::new(forecast_only.path().to_path_buf()),
+            &mut cache,
+            &home,
+            &settings,
+            now,
+        )
+        .unwrap();
+        assert_eq!(report.city, "Home");
+        assert!(cache.report("52.5200,13.4100").is_some());
+    }
+}
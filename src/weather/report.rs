@@ -0,0 +1,183 @@
+use chrono::{Datelike, NaiveDateTime};
+use open_meteo_rs::forecast::ForecastResult;
+use serde::{Deserialize, Serialize};
+
+use super::forecast::{
+    hourly_forecasts, pressure_trend, sun_times, value_f64, HourlyForecast, PressureTrend, SunTimes,
+};
+use super::location::Result as Location;
+use super::weather::UnitSystem;
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+pub struct DailyForecast {
+    pub day: String,
+    pub min: f64,
+    pub max: f64,
+    pub weather_icon: u8,
+}
+
+// everything the weather screen shows, independent of the provider it came from
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct WeatherReport {
+    pub weather_icon: u8,
+    pub is_day: u8,
+    pub city: String,
+    pub temperature: f64,
+    pub wind: f64,
+    pub wind_direction: String,
+    pub unit_system: UnitSystem,
+    pub weather_forecast: Vec<DailyForecast>,
+    // time of the measurement in the time zone of the location
+    pub observed: Option<NaiveDateTime>,
+    pub apparent_temperature: Option<f64>,
+    pub humidity: Option<f64>,
+    pub uv_index: Option<f64>,
+    pub pressure: Option<f64>,
+    pub precipitation: Option<f64>,
+    pub pressure_trend: Option<PressureTrend>,
+    pub hourly: Vec<HourlyForecast>,
+    pub sun: Option<SunTimes>,
+}
+
+pub fn wind_direction(degrees: f64) -> &'static str {
+    const DIRS: [&str; 16] = [
+        "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW",
+        "NW", "NNW",
+    ];
+    let val = ((degrees.rem_euclid(360.0) / 22.5) + 0.5).floor() as usize;
+    DIRS[val % 16]
+}
+
+impl WeatherReport {
+    // a forecast without current values is not usable
+    pub fn from_forecast(
+        result: &ForecastResult,
+        location: &Location,
+        unit_system: UnitSystem,
+    ) -> Option<WeatherReport> {
+        let current = result.current.as_ref()?;
+        let values = &current.values;
+        let mut report = WeatherReport {
+            weather_icon: values
+                .get("weather_code")
+                .and_then(|v| v.value.as_u64())
+                .unwrap_or_default() as u8,
+            is_day: values
+                .get("is_day")
+                .and_then(|v| v.value.as_u64())
+                .unwrap_or_default() as u8,
+            // locations given by coordinates have no country
+            city: if location.country_code.is_empty() {
+                location.name.clone()
+            } else {
+                format!("{},{}", location.name, location.country_code)
+            },
+            temperature: value_f64(values, "temperature_2m").unwrap_or_default(),
+            wind: value_f64(values, "wind_speed_10m").unwrap_or_default(),
+            wind_direction: value_f64(values, "wind_direction_10m")
+                .map(wind_direction)
+                .unwrap_or("N")
+                .to_string(),
+            unit_system,
+            observed: Some(current.datetime),
+            apparent_temperature: value_f64(values, "apparent_temperature"),
+            humidity: value_f64(values, "relative_humidity_2m"),
+            uv_index: value_f64(values, "uv_index"),
+            pressure: value_f64(values, "pressure_msl"),
+            precipitation: value_f64(values, "precipitation"),
+            ..Default::default()
+        };
+
+        if let Some(hourly) = &result.hourly {
+            report.hourly = hourly_forecasts(hourly);
+            report.pressure_trend = pressure_trend(&report.hourly, current.datetime);
+        }
+
+        if let Some(daily) = &result.daily {
+            report.sun = daily.first().and_then(sun_times);
+            report.weather_forecast = daily
+                .iter()
+                .map(|weather| DailyForecast {
+                    day: weather.date.weekday().to_string(),
+                    min: value_f64(&weather.values, "temperature_2m_min").unwrap_or_default(),
+                    max: value_f64(&weather.values, "temperature_2m_max").unwrap_or_default(),
+                    weather_icon: weather
+                        .values
+                        .get("weathercode")
+                        .and_then(|v| v.value.as_u64())
+                        .unwrap_or_default() as u8,
+                })
+                .collect();
+        }
+        Some(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use open_meteo_rs::forecast::{ForecastResultCurrent, ForecastResultDaily, ForecastResultItem};
+    use std::collections::HashMap;
+
+    fn item(value: serde_json::Value) -> ForecastResultItem {
+        ForecastResultItem { unit: None, value }
+    }
+
+    #[test]
+    fn test_wind_direction() {
+        assert_eq!(wind_direction(0.0), "N");
+        assert_eq!(wind_direction(350.0), "N");
+        assert_eq!(wind_direction(90.0), "E");
+        assert_eq!(wind_direction(200.0), "SSW");
+    }
+
+    #[test]
+    fn test_report_from_forecast() {
+        let date = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let result = ForecastResult {
+            current: Some(ForecastResultCurrent {
+                datetime: date.and_hms_opt(14, 0, 0).unwrap(),
+                values: HashMap::from([
+                    ("temperature_2m".into(), item(serde_json::json!(12.4))),
+                    ("weather_code".into(), item(serde_json::json!(3))),
+                    ("is_day".into(), item(serde_json::json!(1))),
+                    ("wind_direction_10m".into(), item(serde_json::json!(270.0))),
+                    ("relative_humidity_2m".into(), item(serde_json::json!(81))),
+                ]),
+            }),
+            daily: Some(vec![ForecastResultDaily {
+                date,
+                values: HashMap::from([
+                    ("temperature_2m_min".into(), item(serde_json::json!(6.1))),
+                    ("temperature_2m_max".into(), item(serde_json::json!(13.0))),
+                ]),
+            }]),
+            ..Default::default()
+        };
+        let location = Location {
+            name: "Berlin".into(),
+            country_code: "DE".into(),
+            ..Default::default()
+        };
+
+        let report = WeatherReport::from_forecast(&result, &location, UnitSystem::Metric).unwrap();
+        assert_eq!(report.city, "Berlin,DE");
+        assert_eq!(report.temperature, 12.4);
+        assert_eq!(report.weather_icon, 3);
+        assert_eq!(report.wind_direction, "W");
+        assert_eq!(report.humidity, Some(81.0));
+        assert_eq!(report.uv_index, None);
+        assert_eq!(report.weather_forecast.len(), 1);
+        assert_eq!(report.weather_forecast[0].day, "Sun");
+        assert_eq!(report.sun, None);
+
+        assert!(WeatherReport::from_forecast(
+            &ForecastResult::default(),
+            &location,
+            UnitSystem::Metric
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,258 @@
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use std::collections::HashMap;
+
+use super::report::WeatherReport;
+
+pub const DEFAULT_WARNINGS: &str = "rain,thunderstorm";
+pub const DEFAULT_WARNING_COOLDOWN: u32 = 120;
+
+// rain within this many minutes is announced
+const RAIN_WINDOW_MINUTES: i64 = 30;
+// the chance of rain in percent that counts as rain
+const RAIN_PROBABILITY: f64 = 50.0;
+// thunderstorms are announced this many hours in advance
+const THUNDERSTORM_HOURS: i64 = 6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WarningKind {
+    Rain,
+    Thunderstorm,
+}
+
+impl WarningKind {
+    pub fn from_key(key: &str) -> Option<WarningKind> {
+        match key.trim().to_lowercase().as_str() {
+            "rain" => Some(WarningKind::Rain),
+            "thunderstorm" => Some(WarningKind::Thunderstorm),
+            _ => None,
+        }
+    }
+}
+
+// "rain,thunderstorm", an empty list disables the warnings
+pub fn warning_kinds(keys: &str) -> Vec<WarningKind> {
+    keys.split(',').filter_map(WarningKind::from_key).collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeatherWarning {
+    pub kind: WarningKind,
+    pub city: String,
+    // local time of the location
+    pub starts: NaiveDateTime,
+    pub now: bool,
+}
+
+impl WeatherWarning {
+    // short enough for the warning frame of the small display
+    pub fn message(&self) -> String {
+        match (self.kind, self.now) {
+            (WarningKind::Rain, _) => format!("Rain soon: {}", self.city),
+            (WarningKind::Thunderstorm, true) => format!("Thunderstorm: {}", self.city),
+            (WarningKind::Thunderstorm, false) => {
+                format!(
+                    "Thunderstorm {}: {}",
+                    self.starts.format("%H:%M"),
+                    self.city
+                )
+            }
+        }
+    }
+}
+
+fn is_thunderstorm(code: u8) -> bool {
+    matches!(code, 95 | 96 | 99)
+}
+
+// drizzle, rain, freezing rain, showers and thunderstorms
+fn is_rain(code: u8) -> bool {
+    matches!(code, 51..=67 | 80..=82 | 95..=99)
+}
+
+fn raining_now(report: &WeatherReport) -> bool {
+    report.precipitation.unwrap_or_default() > 0.0 || is_rain(report.weather_icon)
+}
+
+// hourly values sum up the preceding hour, so every hour ending within the window
+// plus one hour overlaps the window
+fn rain_warning(report: &WeatherReport, now: NaiveDateTime) -> Option<WeatherWarning> {
+    if raining_now(report) {
+        return None;
+    }
+    let window_end = now + Duration::minutes(RAIN_WINDOW_MINUTES) + Duration::hours(1);
+    let hour = report.hourly.iter().find(|hour| {
+        hour.time > now
+            && hour.time <= window_end
+            && (hour.precipitation.unwrap_or_default() > 0.0
+                || hour.precipitation_probability >= RAIN_PROBABILITY
+                || hour.weather_code.is_some_and(is_rain))
+    })?;
+    Some(WeatherWarning {
+        kind: WarningKind::Rain,
+        city: report.city.clone(),
+        starts: (hour.time - Duration::hours(1)).max(now),
+        now: false,
+    })
+}
+
+fn thunderstorm_warning(report: &WeatherReport, now: NaiveDateTime) -> Option<WeatherWarning> {
+    if is_thunderstorm(report.weather_icon) {
+        return Some(WeatherWarning {
+            kind: WarningKind::Thunderstorm,
+            city: report.city.clone(),
+            starts: now,
+            now: true,
+        });
+    }
+    let horizon = now + Duration::hours(THUNDERSTORM_HOURS);
+    let hour = report.hourly.iter().find(|hour| {
+        hour.time > now && hour.time <= horizon && hour.weather_code.is_some_and(is_thunderstorm)
+    })?;
+    Some(WeatherWarning {
+        kind: WarningKind::Thunderstorm,
+        city: report.city.clone(),
+        starts: hour.time,
+        now: false,
+    })
+}
+
+// the time of the measurement is "now" in the time zone of the location
+pub fn evaluate(report: &WeatherReport, kinds: &[WarningKind]) -> Vec<WeatherWarning> {
+    let now = match report.observed {
+        Some(observed) => observed,
+        None => return Vec::new(),
+    };
+    kinds
+        .iter()
+        .filter_map(|kind| match kind {
+            WarningKind::Rain => rain_warning(report, now),
+            WarningKind::Thunderstorm => thunderstorm_warning(report, now),
+        })
+        .collect()
+}
+
+// a warning of the same kind for the same location is shown only once within the cooldown
+#[derive(Default)]
+pub struct WarningCooldown {
+    last_fired: HashMap<(String, WarningKind), DateTime<Utc>>,
+}
+
+impl WarningCooldown {
+    pub fn fire(
+        &mut self,
+        location: &str,
+        kind: WarningKind,
+        now: DateTime<Utc>,
+        cooldown: Duration,
+    ) -> bool {
+        let key = (location.to_string(), kind);
+        if matches!(self.last_fired.get(&key), Some(last) if now - *last < cooldown) {
+            return false;
+        }
+        self.last_fired.insert(key, now);
+        true
+    }
+
+    // forgets the last warning, e.g. if it could not be shown
+    pub fn reset(&mut self, location: &str, kind: WarningKind) {
+        self.last_fired.remove(&(location.to_string(), kind));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather::forecast::HourlyForecast;
+    use chrono::NaiveDate;
+
+    fn time(hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, 18)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn hour(hour: u32, precipitation: f64, weather_code: u8) -> HourlyForecast {
+        HourlyForecast {
+            time: time(hour, 0),
+            temperature: 12.0,
+            precipitation_probability: 0.0,
+            pressure: None,
+            uv_index: None,
+            precipitation: Some(precipitation),
+            weather_code: Some(weather_code),
+        }
+    }
+
+    fn report(observed: NaiveDateTime, hourly: Vec<HourlyForecast>) -> WeatherReport {
+        WeatherReport {
+            city: "Berlin,DE".into(),
+            weather_icon: 3,
+            observed: Some(observed),
+            precipitation: Some(0.0),
+            hourly,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rain_within_the_next_half_hour() {
+        let kinds = warning_kinds("rain");
+        // the 16:00 value covers 15:00 - 16:00
+        let hours = vec![hour(15, 0.0, 3), hour(16, 1.2, 61)];
+        let warnings = evaluate(&report(time(14, 45), hours.clone()), &kinds);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].starts, time(15, 0));
+        assert_eq!(warnings[0].message(), "Rain soon: Berlin,DE");
+
+        // too far away
+        assert!(evaluate(&report(time(14, 15), hours.clone()), &kinds).is_empty());
+
+        // it is raining already
+        let mut raining = report(time(14, 45), hours);
+        raining.precipitation = Some(0.4);
+        assert!(evaluate(&raining, &kinds).is_empty());
+    }
+
+    #[test]
+    fn test_thunderstorm_codes() {
+        let kinds = warning_kinds("thunderstorm, unknown");
+        assert_eq!(kinds, vec![WarningKind::Thunderstorm]);
+
+        let hours = vec![hour(15, 0.0, 3), hour(18, 0.0, 96)];
+        let warnings = evaluate(&report(time(14, 0), hours), &kinds);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].message(), "Thunderstorm 18:00: Berlin,DE");
+
+        let mut now = report(time(14, 0), Vec::new());
+        now.weather_icon = 95;
+        assert!(evaluate(&now, &kinds)[0].now);
+        assert!(evaluate(&now, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let mut cooldown = WarningCooldown::default();
+        let now = Utc::now();
+        let minutes = Duration::minutes(DEFAULT_WARNING_COOLDOWN as i64);
+        assert!(cooldown.fire("berlin", WarningKind::Rain, now, minutes));
+        assert!(!cooldown.fire(
+            "berlin",
+            WarningKind::Rain,
+            now + Duration::minutes(30),
+            minutes
+        ));
+        assert!(cooldown.fire("berlin", WarningKind::Thunderstorm, now, minutes));
+        assert!(cooldown.fire("paris", WarningKind::Rain, now, minutes));
+        assert!(cooldown.fire("berlin", WarningKind::Rain, now + minutes, minutes));
+
+        // a warning that was not shown does not count
+        cooldown.reset("paris", WarningKind::Rain);
+        assert!(cooldown.fire(
+            "paris",
+            WarningKind::Rain,
+            now + Duration::minutes(30),
+            minutes
+        ));
+    }
+}
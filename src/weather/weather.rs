@@ -1,19 +1,98 @@
-use open_meteo_rs::forecast::{ForecastResult, Options};
+use open_meteo_rs::forecast::{
+    ForecastResult, Options, PrecipitationUnit, TemperatureUnit, WindSpeedUnit,
+};
 
-use super::location::Locations;
+use serde::{Deserialize, Serialize};
+
+use super::location::Result as Location;
+
+pub const DEFAULT_FORECAST_DAYS: u32 = 3;
+pub const MAX_FORECAST_DAYS: u32 = 7;
+const HPA_TO_INHG: f64 = 0.02953;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitSystem {
+    #[default]
+    Metric,
+    Imperial,
+}
+
+impl UnitSystem {
+    pub fn from_key(key: &str) -> UnitSystem {
+        match key.trim().to_lowercase().as_str() {
+            "imperial" => UnitSystem::Imperial,
+            _ => UnitSystem::Metric,
+        }
+    }
+
+    pub fn temperature_label(&self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "\u{00B0}C",
+            UnitSystem::Imperial => "\u{00B0}F",
+        }
+    }
+
+    pub fn wind_speed_label(&self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "km/h",
+            UnitSystem::Imperial => "mph",
+        }
+    }
+
+    // open-meteo reports the pressure in hPa for both unit systems
+    pub fn format_pressure(&self, hpa: f64) -> String {
+        match self {
+            UnitSystem::Metric => format!("{:.0} hPa", hpa),
+            UnitSystem::Imperial => format!("{:.2} inHg", hpa * HPA_TO_INHG),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WeatherSettings {
+    pub unit_system: UnitSystem,
+    pub forecast_days: u8,
+}
+
+impl Default for WeatherSettings {
+    fn default() -> WeatherSettings {
+        WeatherSettings {
+            unit_system: UnitSystem::default(),
+            forecast_days: DEFAULT_FORECAST_DAYS as u8,
+        }
+    }
+}
+
+impl WeatherSettings {
+    pub fn new(unit_system: &str, forecast_days: u32) -> WeatherSettings {
+        WeatherSettings {
+            unit_system: UnitSystem::from_key(unit_system),
+            forecast_days: forecast_days.clamp(1, MAX_FORECAST_DAYS) as u8,
+        }
+    }
+}
+
+// the geocoding result knows the time zone of the location, open-meteo guesses it otherwise
+pub fn time_zone(location: &Location) -> String {
+    match location.timezone.parse::<chrono_tz::Tz>() {
+        Ok(time_zone) => time_zone.name().into(),
+        Err(_) => "auto".into(),
+    }
+}
 
 pub async fn get_weather(
     client: &open_meteo_rs::Client,
     opts: open_meteo_rs::forecast::Options,
-) -> ForecastResult {
-    client.forecast(opts).await.unwrap_or_default()
+) -> Result<ForecastResult, Box<dyn std::error::Error>> {
+    client.forecast(opts).await
 }
 
-pub fn set_opts(opts: &mut Options, locations: &Locations) {
+pub fn set_opts(opts: &mut Options, location: &Location, settings: &WeatherSettings) {
     // Location
     opts.location = open_meteo_rs::Location {
-        lat: locations.results[0].latitude,
-        lng: locations.results[0].longitude,
+        lat: location.latitude,
+        lng: location.longitude,
     };
 
     // Current weather
@@ -22,21 +101,39 @@ pub fn set_opts(opts: &mut Options, locations: &Locations) {
     opts.current.push("weather_code".into());
     opts.current.push("wind_speed_10m".into());
     opts.current.push("wind_direction_10m".into());
+    opts.current.push("apparent_temperature".into());
+    opts.current.push("relative_humidity_2m".into());
+    opts.current.push("uv_index".into());
+    opts.current.push("pressure_msl".into());
+    opts.current.push("precipitation".into());
 
-    // Temperature unit
-    opts.temperature_unit = Some(open_meteo_rs::forecast::TemperatureUnit::Celsius); // or
-
-    // Wind speed unit
-    opts.wind_speed_unit = Some(open_meteo_rs::forecast::WindSpeedUnit::Kmh); // or
+    // Hourly parameters
+    opts.hourly.push("temperature_2m".into());
+    opts.hourly.push("precipitation_probability".into());
+    opts.hourly.push("pressure_msl".into());
+    opts.hourly.push("uv_index".into());
+    opts.hourly.push("precipitation".into());
+    opts.hourly.push("weather_code".into());
 
-    // Precipitation unit
-    opts.precipitation_unit = Some(open_meteo_rs::forecast::PrecipitationUnit::Millimeters); // or
+    // Units
+    match settings.unit_system {
+        UnitSystem::Metric => {
+            opts.temperature_unit = Some(TemperatureUnit::Celsius);
+            opts.wind_speed_unit = Some(WindSpeedUnit::Kmh);
+            opts.precipitation_unit = Some(PrecipitationUnit::Millimeters);
+        }
+        UnitSystem::Imperial => {
+            opts.temperature_unit = Some(TemperatureUnit::Fahrenheit);
+            opts.wind_speed_unit = Some(WindSpeedUnit::Mph);
+            opts.precipitation_unit = Some(PrecipitationUnit::Inches);
+        }
+    }
 
-    // Time zone (default to UTC)
-    opts.time_zone = Some(chrono_tz::Europe::Paris.name().into());
+    // Time zone of the location, so that days and hours are local
+    opts.time_zone = Some(time_zone(location));
 
-    // Forecast days (0-16)
-    opts.forecast_days = Some(3); // !! mutually exclusive with dates
+    // Forecast days (1-7)
+    opts.forecast_days = Some(settings.forecast_days); // !! mutually exclusive with dates
 
     // Cell selection
     opts.cell_selection = Some(open_meteo_rs::forecast::CellSelection::Nearest); // or
@@ -45,4 +142,44 @@ pub fn set_opts(opts: &mut Options, locations: &Locations) {
     opts.daily.push("temperature_2m_max".into());
     opts.daily.push("temperature_2m_min".into());
     opts.daily.push("weathercode".into());
+    opts.daily.push("sunrise".into());
+    opts.daily.push("sunset".into());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings() {
+        let settings = WeatherSettings::new(" Imperial ", 12);
+        assert_eq!(settings.unit_system, UnitSystem::Imperial);
+        assert_eq!(settings.forecast_days, 7);
+        assert_eq!(settings.unit_system.temperature_label(), "\u{00B0}F");
+
+        let settings = WeatherSettings::new("unknown", 0);
+        assert_eq!(settings.unit_system, UnitSystem::Metric);
+        assert_eq!(settings.forecast_days, 1);
+    }
+
+    #[test]
+    fn test_format_pressure() {
+        assert_eq!(UnitSystem::Metric.format_pressure(1013.2), "1013 hPa");
+        assert_eq!(UnitSystem::Imperial.format_pressure(1013.2), "29.92 inHg");
+    }
+
+    #[test]
+    fn test_time_zone_from_location() {
+        let mut location = Location {
+            timezone: "America/New_York".into(),
+            ..Default::default()
+        };
+        let mut opts = Options::default();
+        set_opts(&mut opts, &location, &WeatherSettings::new("metric", 5));
+        assert_eq!(opts.time_zone.as_deref(), Some("America/New_York"));
+        assert_eq!(opts.forecast_days, Some(5));
+
+        location.timezone = String::new();
+        assert_eq!(time_zone(&location), "auto");
+    }
 }